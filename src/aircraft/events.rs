@@ -0,0 +1,54 @@
+//! Holds the events that make up a boarding.
+//!
+//! These are only collected when enabled with `Aircraft::set_event_log`, and
+//! can be passed to `Aircraft::replay` to reconstruct the boarding without
+//! running the simulation again.
+
+/// Something that happened to a single passenger on a single update.
+///
+/// `id` is the passenger's id, and `passing` is set when the passenger was
+/// sharing its tile with another, squeezing past it, rather than occupying it.
+/// `gate_checked` is set when a passenger's bag was checked at the door.
+/// `Reassigned` records a passenger standing at `position` being given the
+/// seat `to` in place of `from`, having not reached it in time.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SimEvent {
+    Boarded {
+        tick: u16,
+        id: Option<usize>,
+        position: (u16, u16),
+        gate_checked: bool,
+    },
+    Moved {
+        tick: u16,
+        id: Option<usize>,
+        from: (u16, u16),
+        to: (u16, u16),
+        passing: bool,
+    },
+    Stowed {
+        tick: u16,
+        id: Option<usize>,
+        position: (u16, u16),
+        passing: bool,
+    },
+    Reassigned {
+        tick: u16,
+        id: Option<usize>,
+        position: (u16, u16),
+        from: (u16, u16),
+        to: (u16, u16),
+    },
+}
+
+impl SimEvent {
+    /// Returns the update on which the event happened.
+    pub fn get_tick(&self) -> u16 {
+        match *self {
+            SimEvent::Boarded { tick, .. } => tick,
+            SimEvent::Moved { tick, .. } => tick,
+            SimEvent::Stowed { tick, .. } => tick,
+            SimEvent::Reassigned { tick, .. } => tick,
+        }
+    }
+}
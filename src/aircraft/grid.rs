@@ -0,0 +1,128 @@
+//! Holds utilities for searching the grid of tiles making up an aircraft.
+//!
+//! These work on a layout as returned by `Aircraft::get_layout`, indexed
+//! `[x][y]`, so can be used to build analyses of a layout beyond those
+//! `Aircraft` provides itself.
+
+use std::collections::{HashMap, VecDeque};
+
+use super::tile::Tile;
+
+/// Finds how many steps north, south, east, or west each tile is from `start`,
+/// moving only onto tiles for which `is_passable` returns true.
+///
+/// The start tile is always included, at a distance of 0, whether or not it is
+/// passable itself. Tiles that can't be reached are left out.
+///
+/// # Examples
+///
+/// ```
+/// let distances = grid::bfs(aircraft.get_layout(), (3, 0), |tile| {
+///     tile.get_variant() == Variant::Aisle
+/// });
+/// ```
+pub fn bfs<F>(layout: &[Vec<Tile>], start: (usize, usize), is_passable: F)
+    -> HashMap<(usize, usize), u32>
+    where F: Fn(&Tile) -> bool {
+    let distances = bfs_grid(layout, start, |_, (i, j)| {
+        is_passable(&layout[i][j])
+    });
+
+    let mut reached = HashMap::new();
+    for (x, column) in distances.iter().enumerate() {
+        for (y, distance) in column.iter().enumerate() {
+            if let Some(distance) = distance {
+                reached.insert((x, y), *distance);
+            }
+        }
+    }
+    reached
+}
+
+/// As `bfs`, but returns the distances as a grid indexed `[x][y]` like the
+/// layout, with `None` for tiles that can't be reached, and decides whether
+/// each step can be taken with `can_step`, given the tile stepped from and
+/// the tile stepped onto.
+///
+/// # Examples
+///
+/// ```
+/// let layout = aircraft.get_layout();
+/// let distances = grid::bfs_grid(layout, (3, 0), |_, (x, y)| {
+///     layout[x][y].get_variant() == Variant::Aisle
+/// });
+/// ```
+pub fn bfs_grid<F>(layout: &[Vec<Tile>], start: (usize, usize), can_step: F)
+    -> Vec<Vec<Option<u32>>>
+    where F: Fn((usize, usize), (usize, usize)) -> bool {
+    let mut distances: Vec<Vec<Option<u32>>> = layout.iter()
+        .map(|column| vec![None; column.len()])
+        .collect();
+    let mut queue = VecDeque::new();
+    distances[start.0][start.1] = Some(0);
+    queue.push_back(start);
+
+    while let Some((x, y)) = queue.pop_front() {
+        let distance = distances[x][y].unwrap_or(0);
+        for (dx, dy) in &[(0, -1), (0, 1), (1, 0), (-1, 0)] {
+            let (i, j) = (x as i32 + dx, y as i32 + dy);
+            if i < 0 || j < 0 || i as usize >= layout.len()
+            || j as usize >= layout[i as usize].len() {
+                continue;
+            }
+            let (i, j) = (i as usize, j as usize);
+            if distances[i][j].is_none() && can_step((x, y), (i, j)) {
+                distances[i][j] = Some(distance + 1);
+                queue.push_back((i, j));
+            }
+        }
+    }
+
+    distances
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::tile::Variant;
+
+    #[test]
+    fn routes_around_obstacles() {
+        let mut layout: Vec<Vec<Tile>> = (0..3)
+            .map(|_| (0..3).map(|_| Tile::aisle()).collect())
+            .collect();
+        layout[1][0] = Tile::none();
+        layout[1][1] = Tile::none();
+
+        let distances = bfs(&layout, (0, 0), |tile| {
+            tile.get_variant() != Variant::None
+        });
+
+        assert_eq!(distances[&(0, 0)], 0);
+        assert_eq!(distances[&(1, 2)], 3);
+        assert_eq!(distances[&(2, 0)], 6);
+        assert!(!distances.contains_key(&(1, 1)));
+        assert_eq!(distances.len(), 7);
+    }
+
+    #[test]
+    fn grid_follows_steps() {
+        let layout: Vec<Vec<Tile>> = (0..3)
+            .map(|_| (0..3).map(|_| Tile::aisle()).collect())
+            .collect();
+
+        // Only steps along a row, or down the first column, are allowed.
+        let distances = bfs_grid(&layout, (0, 0), |(x, y), (i, j)| {
+            y == j || (x == 0 && i == 0)
+        });
+
+        assert_eq!(distances[0][0], Some(0));
+        assert_eq!(distances[2][2], Some(4));
+        assert_eq!(distances[1][1], Some(2));
+        assert!(distances.iter().flatten().all(|d| d.is_some()));
+
+        let distances = bfs_grid(&layout, (1, 1), |(x, _), (i, _)| x == i);
+        assert_eq!(distances[1][2], Some(1));
+        assert_eq!(distances[0][1], None);
+    }
+}
@@ -6,21 +6,228 @@
 
 pub mod tile;
 pub mod person;
+pub mod stats;
+pub mod trace;
+pub mod events;
+pub mod realtime;
+pub mod grid;
+
+use std::cmp::Reverse;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::io;
 
 use simple_logger::SimpleLogger;
 
-use tile::{Tile, Variant};
-use person::{Person, Behaviour};
+use tile::{Tile, Variant, SimpleTile, Neighbourhood, PASS_WAIT};
+use person::{Person, Behaviour, Mobility};
+use stats::{BoardingStats, PassengerStats, StepReport};
+use trace::{MoveDecision, TickDecisions, TrajectoryRow};
+use events::SimEvent;
 
 const MAX_ITERATIONS: u16 = 1000;
+const GATE_CHECK_WAIT: u8 = 2; // The number of steps spent at the door having a
+                               // bag checked
+const REDUCED_MOBILITY_WAIT: u8 = 1; // The number of steps spent waiting after
+                                     // each move by a passenger with reduced
+                                     // mobility
+const WHEELCHAIR_SEATING_WAIT: u8 = 4; // The number of steps spent in the aisle
+                                       // getting a wheelchair user into its
+                                       // seat
+const REASSIGNED_SEAT_WAIT: u8 = 1; // The number of steps spent checking the
+                                    // seat number by a reassigned passenger
+
+/// The order in which waiting passengers are let through the entrance.
+///
+/// `AsAdded` boards passengers in the order they were passed to
+/// `add_passenger`, `Reversed` boards the most recently added passenger first,
+/// and `Custom` boards passengers by their ids (their position in the order
+/// they were added). Any passengers left out of a `Custom` order board
+/// afterwards in the order they were added. Whatever the order, passengers
+/// flagged with `must_board_first` board ahead of everyone else.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BoardingOrder {
+    AsAdded,
+    Reversed,
+    Custom(Vec<usize>),
+}
+
+/// How passengers standing in the aisle make way for those behind them.
+///
+/// Under `None`, a passenger blocked by another in the aisle waits for it to
+/// move. Under `FurtherSeatFirst`, a passenger lets anyone whose seat is
+/// further away than its own squeeze past; under `CloserSeatFirst`, it lets
+/// anyone whose seat is closer squeeze past. Squeezing past takes as long as
+/// getting past a seated passenger.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum YieldPolicy {
+    None,
+    CloserSeatFirst,
+    FurtherSeatFirst,
+}
+
+/// Criteria for ordering the seats of an aircraft.
+///
+/// Back and front refer to rows with the highest and lowest `y` respectively;
+/// window and aisle refer to how far a seat is from the nearest aisle on its
+/// row.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SeatSort {
+    BackToFront,
+    FrontToBack,
+    WindowToAisle,
+    AisleToWindow,
+}
+
+/// The reasons a run of the simulation can fail.
+///
+/// `Timeout` is returned when the passengers still aren't all seated after
+/// `MAX_ITERATIONS` updates, carrying the statistics gathered up to that point
+/// and the ids of the passengers not yet in their seats. `Stalled` is returned
+/// by `Aircraft::run_until_stalled` when nobody has been seated for too long,
+/// carrying the ids of the passengers not yet in their seats along with where
+/// each is onboard, or `None` if it hasn't boarded yet.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RunError {
+    Timeout {
+        stats: Box<BoardingStats>,
+        unseated: Vec<usize>,
+    },
+    Stalled {
+        stats: Box<BoardingStats>,
+        stalled: Vec<(usize, Option<(u16, u16)>)>,
+    },
+}
+
+/// A passenger found somewhere other than its assigned seat by
+/// `Aircraft::audit_seating`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SeatMismatch {
+    pub id: Option<usize>,
+    pub name: String,
+    pub expected: Option<(u16, u16)>,
+    pub actual: (u16, u16),
+}
+
+/// Why an aircraft's boarding isn't complete, as found by
+/// `Aircraft::diagnose_incomplete`, with the ids of the passengers responsible.
+///
+/// `SeatConflict` lists each seat assigned to more than one passenger, with
+/// the passengers assigned it. `UnreachableSeats` lists passengers whose seats
+/// can't be reached from any entrance even on an empty aircraft. `Deadlock`
+/// lists the passengers onboard when each of them either had no legal move on
+/// the last update or can't reach its seat past the others as they stand
+/// (bar any that would make way for it under the aircraft's `YieldPolicy`),
+/// so none ever will. `Stalled` lists the passengers not yet in their seats
+/// when none of these is the cause, e.g. because some are still making their
+/// way and boarding was given up too soon.
+#[derive(Debug, Clone, PartialEq)]
+pub enum IncompleteReason {
+    Complete,
+    SeatConflict(Vec<((u16, u16), Vec<usize>)>),
+    UnreachableSeats(Vec<usize>),
+    Deadlock(Vec<usize>),
+    Stalled(Vec<usize>),
+}
+
+/// A copy of everything about an aircraft part way through boarding, taken
+/// with `Aircraft::snapshot` and carried on from with `Aircraft::resume`.
+pub struct Snapshot {
+    aircraft: Aircraft,
+}
+
+impl Clone for Snapshot {
+    fn clone(&self) -> Snapshot {
+        Snapshot { aircraft: self.aircraft.copy_state() }
+    }
+}
+
+/// What a move filter set with `Aircraft::set_move_filter` is told about the
+/// passenger whose move it is filtering.
+///
+/// `passing` is set when the passenger is squeezing past another on its tile.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PassengerView {
+    pub id: Option<usize>,
+    pub name: String,
+    pub position: (u16, u16),
+    pub seat: Option<(u16, u16)>,
+    pub baggage: bool,
+    pub passing: bool,
+    pub tick: u16,
+}
+
+/// A function that can override the move chosen for a passenger, given the
+/// passenger and the move.
+pub type MoveFilter = Box<dyn FnMut(&PassengerView, Behaviour) -> Behaviour
+                          + Send>;
+
+/// A function called with the update on which the last passenger waiting to
+/// board came onboard.
+pub type LastBoardedHook = Box<dyn FnMut(u16) + Send>;
+
+/// The characters `Aircraft::render_ascii` draws each tile with, set with
+/// `Aircraft::set_glyphs`.
+///
+/// An occupied tile is drawn as `occupied` whatever its variant.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GlyphSet {
+    pub occupied: char,
+    pub aisle: char,
+    pub seat: char,
+    pub entrance: char,
+    pub none: char,
+}
+
+impl Default for GlyphSet {
+    fn default() -> GlyphSet {
+        GlyphSet {
+            occupied: '@',
+            aisle: '*',
+            seat: '#',
+            entrance: '*',
+            none: '?',
+        }
+    }
+}
+
+impl std::fmt::Display for RunError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            RunError::Timeout { unseated, .. } => {
+                write!(f, "Passengers could not all be seated ({} unseated).",
+                       unseated.len())
+            },
+            RunError::Stalled { stats, stalled } => {
+                write!(f, "Boarding stalled on update {} ({} unseated).",
+                       stats.ticks, stalled.len())
+            },
+        }
+    }
+}
+
+/// When an entrance lets passengers board, as set with
+/// `Aircraft::set_door_window` and `Aircraft::set_door_interval`.
+///
+/// The door is open from update `open_tick` until `close_tick`, if any, and
+/// admits a passenger at most every `interval` updates; `last_admitted` is
+/// the update on which it last did.
+#[derive(Clone)]
+struct DoorSchedule {
+    door: (u16, u16),
+    open_tick: u16,
+    close_tick: Option<u16>,
+    interval: u16,
+    last_admitted: Option<u16>,
+}
 
 /// A struct representing a simulated aircraft.
 ///
 /// This object simulates an aircraft, consisting of `layout`, a 2d vector of
 /// `Tile` objects representing each space onboard and its occupant(s) (or lack
 /// thereof), a list of passengers waiting to board, the aircraft's size as a
-/// convenient tuple, and a list of seats which should eventually be filled when
-/// the aircraft has been fully boarded.
+/// convenient tuple, a list of seats which should eventually be filled when
+/// the aircraft has been fully boarded, and the statistics gathered so far.
 ///
 /// # Examples
 ///
@@ -34,6 +241,35 @@ pub struct Aircraft {
     layout: Vec<Vec<Tile>>,
     passengers: Vec<Person>,
     targeted_seats: Vec<(u16,u16)>,
+    stats: BoardingStats,
+    boarding_order: BoardingOrder,
+    boarding_cursor: usize,
+    yield_policy: YieldPolicy,
+    forbid_backtracking: bool,
+    tracing: bool,
+    decision_trace: Vec<TickDecisions>,
+    legroom: Vec<f32>,
+    bin_capacity: Option<u16>,
+    bins: Vec<u16>,
+    bin_search_range: u16,
+    doors: Vec<DoorSchedule>,
+    jet_bridge: Vec<Option<Person>>,
+    row_boarded: Vec<Option<u16>>,
+    logging: bool,
+    events: Vec<SimEvent>,
+    transits: Vec<((u16, u16), Person)>,
+    seated: usize,
+    panic_on_timeout: bool,
+    beam_boarding: bool,
+    peak_occupancy: Vec<Vec<u8>>,
+    move_filter: Option<MoveFilter>,
+    glyphs: GlyphSet,
+    seat_after_stow: bool,
+    on_last_boarded: Option<LastBoardedHook>,
+    seat_deadline: Option<u16>,
+    attendant_interval: Option<u16>,
+    vestibule_pooling: bool,
+    boxed_in: Vec<usize>,
 }
 
 impl Aircraft {
@@ -46,6 +282,38 @@ impl Aircraft {
             layout: Vec::<Vec<Tile>>::new(),
             passengers: Vec::<Person>::new(),
             targeted_seats: Vec::<(u16,u16)>::new(),
+            stats: BoardingStats {
+                row_dwell: vec![0; y as usize],
+                ..BoardingStats::new()
+            },
+            boarding_order: BoardingOrder::Reversed,
+            boarding_cursor: 0,
+            yield_policy: YieldPolicy::None,
+            forbid_backtracking: false,
+            tracing: false,
+            decision_trace: Vec::<TickDecisions>::new(),
+            legroom: vec![1.0; y as usize],
+            bin_capacity: None,
+            bins: vec![0; y as usize],
+            bin_search_range: 0,
+            doors: Vec::<DoorSchedule>::new(),
+            jet_bridge: Vec::<Option<Person>>::new(),
+            row_boarded: vec![None; y as usize],
+            logging: false,
+            events: Vec::<SimEvent>::new(),
+            transits: Vec::<((u16, u16), Person)>::new(),
+            seated: 0,
+            panic_on_timeout: false,
+            beam_boarding: false,
+            peak_occupancy: vec![vec![0; y as usize]; x as usize],
+            move_filter: None,
+            glyphs: GlyphSet::default(),
+            seat_after_stow: false,
+            on_last_boarded: None,
+            seat_deadline: None,
+            attendant_interval: None,
+            vestibule_pooling: false,
+            boxed_in: Vec::<usize>::new(),
         };
         aircraft.clear();
         return aircraft;
@@ -63,38 +331,450 @@ impl Aircraft {
     }
     
     /// Adds a passenger to the aircraft
-    pub fn add_passenger(&mut self, p: Person) {
+    ///
+    /// The passenger is given an id matching its position in
+    /// `BoardingStats::passengers`.
+    ///
+    /// A passenger may target an entrance tile as its seat, in which case it
+    /// stays there once reached and no more passengers board through that
+    /// door. If it is the only door, anyone yet to board never will, and
+    /// `run_to_completion` times out.
+    ///
+    /// Passengers can also be added part way through a boarding, e.g. between
+    /// calls to `step`, joining the back of the queue. An aircraft that was
+    /// already complete is no longer so until the new passenger is seated.
+    ///
+    /// The passenger's seat isn't checked; use `try_add_passenger` for
+    /// passengers that may have been given a seat that can't be sat in.
+    pub fn add_passenger(&mut self, mut p: Person) {
         let seat = p.get_seat();
         if seat.is_some() {
             self.targeted_seats.push(seat.unwrap());
+            self.stats.complete_tick = None;
         }
+        p.set_id(self.stats.passengers.len());
+        self.stats.passengers.push(PassengerStats {
+            zone: p.get_zone(),
+            seat: p.get_seat(),
+            seat_deadline: p.get_seat_deadline(),
+            ..PassengerStats::new(&p.get_name())
+        });
         self.passengers.push(p);
     }
 
+    /// As `add_passenger`, but first checks that the passenger's seat can be
+    /// reached and sat in, as it may not be when read from a file.
+    ///
+    /// Returns an error, without adding the passenger, if it has no seat, its
+    /// seat is off the aircraft, or its seat is an aisle or `None` tile, where
+    /// it would never count as seated. Entrance seats are allowed, as for
+    /// `add_passenger`.
+    pub fn try_add_passenger(&mut self, p: Person)
+        -> Result<(), &'static str> {
+        let (x, y) = p.get_seat().ok_or("Passenger has no seat")?;
+        if x >= self.size.0 || y >= self.size.1 {
+            return Err("Passenger's seat is off the aircraft");
+        }
+        match self.get_tile_variant(x, y) {
+            Variant::Seat | Variant::Entrance => (),
+            Variant::Aisle => return Err("Passenger's seat is an aisle"),
+            Variant::None => return Err("Passenger's seat is not a tile"),
+        }
+        self.add_passenger(p);
+        Ok(())
+    }
+
+    /// Adds someone passing through the cabin during boarding, such as a member
+    /// of crew, who comes onboard through the entrance at `from_door` and
+    /// leaves as soon as it reaches the one at `to_door`.
+    ///
+    /// Transit actors board ahead of any waiting passengers, have no seat, and
+    /// don't count towards the aircraft being complete or its statistics,
+    /// besides getting in passengers' way. They aren't recorded in the event
+    /// log. Returns an error if either door isn't an entrance.
+    pub fn add_transit(&mut self, from_door: (u16, u16), to_door: (u16, u16))
+        -> Result<(), &'static str> {
+        for door in &[from_door, to_door] {
+            if door.0 >= self.size.0 || door.1 >= self.size.1
+            || self.get_tile_variant(door.0, door.1) != Variant::Entrance {
+                return Err("No entrance at transit door");
+            }
+        }
+
+        let mut person = Person::new("TRANSIT");
+        person.target_seat(to_door.0, to_door.1);
+        person.set_transit(true);
+        self.transits.push((from_door, person));
+        Ok(())
+    }
+
+    /// Sets the order in which waiting passengers board.
+    ///
+    /// Defaults to `BoardingOrder::Reversed`. Passengers flagged with
+    /// `must_board_first` still head the queue.
+    pub fn set_boarding_order(&mut self, order: BoardingOrder) {
+        self.boarding_order = order;
+        self.boarding_cursor = 0;
+    }
+
+    /// Sets how passengers in the aisle make way for those behind them.
+    ///
+    /// Defaults to `YieldPolicy::None`.
+    pub fn set_yield_policy(&mut self, policy: YieldPolicy) {
+        self.yield_policy = policy;
+    }
+
+    /// Sets whether passengers are stopped from walking back along the aisle
+    /// towards the door they boarded through, as real passengers rarely do.
+    ///
+    /// Moves into and out of seats are unaffected. A passenger that passes its
+    /// row while this is enabled, including one looking further forward for
+    /// bin space, waits where it is rather than turning back. Defaults to
+    /// false.
+    pub fn set_forbid_backtracking(&mut self, forbid: bool) {
+        self.forbid_backtracking = forbid;
+    }
+
+    /// Sets whether every waiting passenger is placed straight onto the aisle
+    /// on the first update, instead of boarding through the door.
+    ///
+    /// Each passenger is placed on the free aisle or entrance tile nearest to
+    /// where it would stand beside its seat, as though beamed aboard, and
+    /// moves as normal from then on. Those that can't be placed once the aisle
+    /// is full board through the door as normal. This gives a rough upper
+    /// bound on how quickly any boarding method could be. Defaults to false.
+    pub fn set_beam_boarding(&mut self, beam: bool) {
+        self.beam_boarding = beam;
+    }
+
+    /// Places every waiting passenger on the free aisle tile nearest its seat,
+    /// for `set_beam_boarding`.
+    fn beam_passengers(&mut self, tick: u16) -> bool {
+        let mut placed_any = false;
+        for mut person in std::mem::take(&mut self.passengers) {
+            let tile = person.get_seat()
+                .and_then(|(x, y)| self.beam_tile(x, y));
+            let (x, y) = match tile {
+                Some((x, y)) => (x as usize, y as usize),
+                None => {
+                    self.passengers.push(person);
+                    continue;
+                },
+            };
+            self.record_boarding(&person, x, y);
+            person.record_position(x as u16, y as u16);
+            if self.logging {
+                self.events.push(SimEvent::Boarded {
+                    tick,
+                    id: person.get_id(),
+                    position: (x as u16, y as u16),
+                    gate_checked: false,
+                });
+            }
+            self.layout[x][y].occupy(person);
+            self.layout[x][y].set_updated(true);
+            placed_any = true;
+        }
+        placed_any
+    }
+
+    /// Finds the free aisle or entrance tile nearest, along the aisles, to
+    /// where a passenger would stand to enter the seat at `x`,`y`.
+    fn beam_tile(&self, x: u16, y: u16) -> Option<(u16, u16)> {
+        let goal = match self.access_tile(x, y) {
+            Some(tile) => tile,
+            None => (self.nearest_aisle(x, y)?, y),
+        };
+        let distances = self.aisle_distances(goal);
+        let mut nearest: Option<(u32, (u16, u16))> = None;
+        for (i, column) in distances.iter().enumerate() {
+            for (j, distance) in column.iter().enumerate() {
+                if let Some(d) = *distance {
+                    if !self.layout[i][j].is_occupied()
+                    && nearest.filter(|(best, _)| *best <= d).is_none() {
+                        nearest = Some((d, (i as u16, j as u16)));
+                    }
+                }
+            }
+        }
+        nearest.map(|(_, tile)| tile)
+    }
+
+    /// Sets a function to be consulted on every move chosen for a passenger,
+    /// which may override it, e.g. so that an external controller can drive
+    /// the passengers. Defaults to none, leaving every move as chosen.
+    ///
+    /// A move off the aircraft or onto a `None` tile is turned into a `Wait`,
+    /// as is a `Stow` by a passenger with no baggage, or a `Settle` by one not
+    /// in its seat with its baggage stowed.
+    pub fn set_move_filter(&mut self, filter: Option<MoveFilter>) {
+        self.move_filter = filter;
+    }
+
+    /// Sets whether a passenger takes its next step on the same update as it
+    /// finishes stowing its baggage, rather than on the update after.
+    ///
+    /// With no stow delay the passenger stows and steps together; otherwise it
+    /// steps on the last update of its delay. Defaults to false.
+    pub fn set_seat_after_stow(&mut self, enabled: bool) {
+        self.seat_after_stow = enabled;
+    }
+
+    /// Sets a function to be called on the update the last passenger waiting
+    /// to board comes onboard, leaving the jet bridge empty, e.g. so that the
+    /// door can be closed. Defaults to none.
+    ///
+    /// This is also recorded in `BoardingStats::last_boarded_tick`. Passengers
+    /// added after that board as normal, and the function is called again
+    /// once they have all come onboard.
+    pub fn set_on_last_boarded(&mut self, hook: Option<LastBoardedHook>) {
+        self.on_last_boarded = hook;
+    }
+
+    /// Sets how many updates a passenger has, from boarding, to reach its
+    /// seat before it is moved to another. Defaults to none, where passengers
+    /// keep their seats however long they take.
+    ///
+    /// A passenger still not in its seat by the deadline, e.g. because it is
+    /// taken, is given the vacant seat nearest where it stands that nobody
+    /// else is assigned, as a gate agent would. This is recorded in its
+    /// `PassengerStats` and the event log, and it has as long again to reach
+    /// the new seat. A passenger is left as it is if there is no such seat.
+    /// Passengers given their own deadline with `Person::set_seat_deadline`
+    /// keep to that instead.
+    pub fn set_seat_deadline(&mut self, deadline: Option<u16>) {
+        self.seat_deadline = deadline;
+    }
+
+    /// Sets the characters tiles are drawn with by `render_ascii`. Defaults to
+    /// `GlyphSet::default()`.
+    pub fn set_glyphs(&mut self, glyphs: GlyphSet) {
+        self.glyphs = glyphs;
+    }
+
+    /// Passes the move chosen for the occupier of the tile at `x`,`y`, or the
+    /// passenger passing through it if `passing` is set, through the move
+    /// filter, if there is one.
+    fn filter_move(&mut self,
+                   x: usize,
+                   y: usize,
+                   passing: bool,
+                   chosen: (Behaviour, f32)) -> (Behaviour, f32) {
+        let filter = match self.move_filter.as_mut() {
+            Some(filter) => filter,
+            None => return chosen,
+        };
+        let person = if passing {
+            self.layout[x][y].get_passer()
+        } else {
+            self.layout[x][y].get_occupier()
+        }.unwrap();
+        let view = PassengerView {
+            id: person.get_id(),
+            name: person.get_name(),
+            position: (x as u16, y as u16),
+            seat: person.get_seat(),
+            baggage: person.has_baggage(),
+            passing,
+            tick: self.stats.ticks + 1,
+        };
+
+        let behaviour = filter(&view, chosen.0);
+        let (dx, dy) = match behaviour {
+            Behaviour::Move_North => (0, -1),
+            Behaviour::Move_South => (0, 1),
+            Behaviour::Move_East => (1, 0),
+            Behaviour::Move_West => (-1, 0),
+            Behaviour::Stow if !view.baggage => return (Behaviour::Wait, 0.0),
+            Behaviour::Settle if view.baggage
+                || view.seat != Some(view.position) => {
+                return (Behaviour::Wait, 0.0);
+            },
+            _ => (0, 0),
+        };
+        if self.neighbourhood(x as u16, y as u16).get(dx, dy)
+            .filter(|tile| tile.get_variant() != Variant::None)
+            .is_none() {
+            return (Behaviour::Wait, chosen.1);
+        }
+        (behaviour, chosen.1)
+    }
+
+    /// Checks whether a move by a passenger on the tile at `x`,`y` would take
+    /// it back along the aisle towards `door`.
+    fn is_backtracking(&self,
+                       x: usize,
+                       y: usize,
+                       door: Option<(u16, u16)>,
+                       behaviour: Behaviour) -> bool {
+        let door = match door {
+            Some(door) => door,
+            None => return false,
+        };
+        if self.layout[x][y].get_variant() == Variant::Seat {
+            return false;
+        }
+        match behaviour {
+            Behaviour::Move_North => (door.1 as usize) < y,
+            Behaviour::Move_South => (door.1 as usize) > y,
+            _ => false,
+        }
+    }
+
+    /// Checks whether the passenger standing on the aisle tile at `x`,`y` lets
+    /// a passenger heading for `target` squeeze past it.
+    fn yields_to(&self, x: usize, y: usize, target: (u16, u16)) -> bool {
+        let other_target = match self.layout[x][y].get_occupier() {
+            Some(p) => match p.get_seat() {
+                Some(seat) => seat,
+                None => return false,
+            },
+            None => return false,
+        };
+        let distance = |seat: (u16, u16)| {
+            (seat.0 as i32 - x as i32).abs() + (seat.1 as i32 - y as i32).abs()
+        };
+
+        match self.yield_policy {
+            YieldPolicy::None => false,
+            YieldPolicy::CloserSeatFirst => {
+                distance(target) < distance(other_target)
+            },
+            YieldPolicy::FurtherSeatFirst => {
+                distance(target) > distance(other_target)
+            },
+        }
+    }
+
+    /// Takes the next passenger to board from the waiting passengers, according
+    /// to the aircraft's `BoardingOrder`.
+    fn next_passenger(&mut self) -> Option<Person> {
+        let (position, cursor) = self.next_waiting()?;
+        self.boarding_cursor = cursor;
+        Some(self.passengers.remove(position))
+    }
+
+    /// Finds where among the waiting passengers the next to board is, along
+    /// with where a `Custom` order carries on from once it has.
+    fn next_waiting(&self) -> Option<(usize, usize)> {
+        if self.passengers.is_empty() {
+            return None;
+        }
+
+        // Passengers with tight connections are let through ahead of the
+        // order, which carries on from where it was once they have boarded.
+        let mut flagged = self.passengers.iter()
+            .enumerate()
+            .filter(|(_, p)| p.must_board_first())
+            .map(|(i, _)| i);
+        let first = match self.boarding_order {
+            BoardingOrder::Reversed => flagged.next_back(),
+            _ => flagged.next(),
+        };
+        if let Some(position) = first {
+            return Some((position, self.boarding_cursor));
+        }
+
+        let mut cursor = self.boarding_cursor;
+        let position = match &self.boarding_order {
+            BoardingOrder::AsAdded => 0,
+            BoardingOrder::Reversed => self.passengers.len() - 1,
+            BoardingOrder::Custom(order) => loop {
+                if cursor >= order.len() {
+                    break 0;
+                }
+                let id = order[cursor];
+                cursor += 1;
+                let position = self.passengers.iter()
+                    .position(|p| p.get_id() == Some(id));
+                if let Some(position) = position {
+                    break position;
+                }
+            },
+        };
+
+        // A group waiting to board lets whichever of them sits furthest along
+        // their row go first, so nobody has to get up for the others. The one
+        // stood aside keeps their place in a `Custom` order.
+        let member = self.furthest_in_group(position);
+        if member != position {
+            return Some((member, self.boarding_cursor));
+        }
+        Some((position, cursor))
+    }
+
+    /// Finds the waiting passenger in the same group as the one at `position`
+    /// whose seat is furthest from the aisle, or `position` itself if nobody
+    /// in the group sits further along their row.
+    fn furthest_in_group(&self, position: usize) -> usize {
+        let distance = |person: &Person| match person.get_seat() {
+            Some((x, y)) => self.aisle_distance(x, y),
+            None => 0,
+        };
+        let group = match self.passengers[position].get_group() {
+            Some(group) => group,
+            None => return position,
+        };
+
+        let mut furthest = position;
+        for (i, person) in self.passengers.iter().enumerate() {
+            if person.get_group() == Some(group)
+            && distance(person) != u16::MAX
+            && distance(person) > distance(&self.passengers[furthest]) {
+                furthest = i;
+            }
+        }
+        furthest
+    }
+
+    /// Returns the passengers still waiting to board, in the order they were
+    /// added rather than the order they will board in.
+    pub fn waiting_queue(&self) -> &[Person] {
+        &self.passengers
+    }
+
+    /// Returns the passenger that will board next, according to the aircraft's
+    /// `BoardingOrder`, or `None` if nobody is waiting.
+    pub fn next_to_board(&self) -> Option<&Person> {
+        self.next_waiting().map(|(position, _)| &self.passengers[position])
+    }
+
     /// For debug purposes: prints the aircraft's layout to stdout using ASCII
     /// characters to represent passengers and different types of aisle.
     pub fn ascii_render(&self) {
-        print!("   ");
+        self.render_to(&mut io::stdout()).unwrap();
+    }
+
+    /// Writes the aircraft's layout, as drawn by `render_ascii`, to `w`.
+    pub fn render_to(&self, w: &mut impl io::Write) -> io::Result<()> {
+        w.write_all(self.render_ascii().as_bytes())
+    }
+
+    /// Draws the aircraft's layout as it is printed by `ascii_render`, one line
+    /// per row after a header numbering the columns.
+    pub fn render_ascii(&self) -> String {
+        let mut frame = String::from("   ");
         for i in 0..self.size.0 {
-            print!("{}", i);
+            frame.push_str(&i.to_string());
         }
-        print!("\n");
+        frame.push('\n');
         for j in 0..self.size.1 as usize {
-            print!("{:>3}", j);
+            frame.push_str(&format!("{:>3}", j));
             for i in 0..self.size.0 as usize {
                 if self.layout[i][j].is_occupied() {
-                    print!("@");
+                    frame.push(self.glyphs.occupied);
                 } else {
-                    print!("{}", match self.layout[i][j].get_variant() {
-                        Variant::Aisle => "*",
-                        Variant::Seat => "#",
-                        Variant::Entrance => "*",
-                        Variant::None => "?",
+                    frame.push(match self.layout[i][j].get_variant() {
+                        Variant::Aisle => self.glyphs.aisle,
+                        Variant::Seat => self.glyphs.seat,
+                        Variant::Entrance => self.glyphs.entrance,
+                        Variant::None => self.glyphs.none,
                     });
                 }
             }
-            print!("\n");
+            frame.push('\n');
         }
+        frame
     }
     
     /// Determines the best move for a passenger.
@@ -102,74 +782,203 @@ impl Aircraft {
     /// Takes into account the passenger's current location, their target
     /// location, whether they are currently holding baggage, and their
     /// surroundings to determine the best possible move at any given time. This
-    /// is returned as a `Behaviour` enum.
+    /// is returned as a `Behaviour` enum, alongside the resulting distance from
+    /// the passenger's goal.
+    ///
+    /// Returns `None` if the passenger is boxed in with no legal move at all,
+    /// as opposed to choosing to `Wait` because no legal move gets it closer.
     fn determine_move(&self,
                       i: usize,
                       j: usize,
                       target_x: u16,
                       target_y: u16,
-                      baggage: bool) -> (Behaviour, f32) {
-        let mut current_move = (Behaviour::Wait, 1000.0);
+                      baggage: bool) -> Option<(Behaviour, f32)> {
+        self.determine_move_traced(i, j, target_x, target_y, baggage, false,
+                                   None)
+    }
+
+    /// As `determine_move`, but also records every candidate move scored, and
+    /// its distance, into `candidates` when given. A passenger with
+    /// `reserved_bin` set always stows its baggage on its own row.
+    #[allow(clippy::too_many_arguments)]
+    fn determine_move_traced(&self,
+                             i: usize,
+                             j: usize,
+                             target_x: u16,
+                             target_y: u16,
+                             baggage: bool,
+                             reserved_bin: bool,
+                             mut candidates: Option<&mut Vec<(Behaviour, f32)>>)
+        -> Option<(Behaviour, f32)> {
+        let mut current_move = (Behaviour::Wait, f32::INFINITY);
+        let mut can_move = false;
         let target_seat = (target_x, target_y);
+
+        // A passenger already in its seat with nothing left to stow has nowhere
+        // to go, so there is no need to evaluate any moves.
+        if target_seat == (i as u16, j as u16) && !baggage {
+            return Some((Behaviour::Settle, 0.0));
+        }
         // let (i, j) = (pos_x as usize, pos_y as usize);
         
+        let neighbourhood = self.neighbourhood(i as u16, j as u16);
+
         if self.layout[i][j].get_variant() == Variant::Aisle
         || self.layout[i][j].get_variant() == Variant::Entrance {
-            if target_seat.1 == j as u16 && baggage {
+            // A seat should be approached from the aisle nearest to it, so a
+            // passenger in any other aisle heads for that one first, crossing
+            // over to it wherever aisle tiles allow (e.g. along a cross-aisle
+            // at the door) before moving along the cabin.
+            let approach = match self.layout[target_x as usize]
+                [target_y as usize].get_variant() {
+                Variant::Seat => self.nearest_aisle(target_x, target_y),
+                _ => None,
+            };
+
+            // A seat that can't be stepped into from an aisle along its row is
+            // entered from an access tile next to it instead, which passengers
+            // find their way to along the aisle tiles.
+            let access = match self.layout[target_x as usize]
+                [target_y as usize].get_variant() {
+                Variant::Seat => self.access_tile(target_x, target_y),
+                _ => None,
+            };
+            let (mut goal, mut lateral_weight) = match approach {
+                Some(aisle) if aisle != i as u16 && access.is_none() => {
+                    ((aisle, target_y), self.size.1 as f32 + 1.0)
+                },
+                _ => (target_seat, 1.0),
+            };
+
+            // A passenger whose row's bins are full carries on forward looking
+            // for space, then comes back to its row once its bag is stowed.
+            let search_row = if baggage && !reserved_bin && access.is_none()
+                && self.bins_full(target_y) {
+                self.bin_search_row(target_y)
+            } else {
+                None
+            };
+            if let (Some(row), Some(aisle)) = (search_row, approach) {
+                goal = (aisle, row);
+                if aisle != i as u16 {
+                    lateral_weight = self.size.1 as f32 + 1.0;
+                }
+            }
+
+            let access_distances = match access {
+                Some(tile) if tile != (i as u16, j as u16) => {
+                    Some(self.aisle_distances(tile))
+                },
+                _ => None,
+            };
+            // A seat block is only entered from the aisle nearest the seat,
+            // so a passenger in another aisle makes its way round to that one
+            // along the aisle tiles instead of climbing over the seats, where
+            // it can.
+            let access_distances = match (access_distances, approach) {
+                (None, Some(aisle)) if aisle != i as u16
+                    && access.is_none() && search_row.is_none() => {
+                    let distances = self.aisle_distances((aisle, target_y));
+                    if distances[i][j].is_some() {
+                        Some(distances)
+                    } else {
+                        None
+                    }
+                },
+                (distances, _) => distances,
+            };
+            let stow_here = match (access, search_row) {
+                (Some(tile), _) => tile == (i as u16, j as u16),
+                (None, Some(_)) => goal == (i as u16, j as u16),
+                (None, None) => {
+                    target_seat.1 == j as u16 && goal == target_seat
+                },
+            };
+
+            if stow_here && baggage {
                 current_move = (Behaviour::Stow, 0.0);
+                can_move = true;
             } else {
                 // Decide movement based on other tile
                 for potential_move in &[
-                    (Behaviour::Wait, (0.0, 0.0)),
-                    (Behaviour::Move_North, (0.0, -1.0)),
-                    (Behaviour::Move_South, (0.0, 1.0)),
-                    (Behaviour::Move_East, (1.0, 0.0)),
-                    (Behaviour::Move_West, (-1.0, 0.0)),
+                    (Behaviour::Wait, (0, 0)),
+                    (Behaviour::Move_North, (0, -1)),
+                    (Behaviour::Move_South, (0, 1)),
+                    (Behaviour::Move_East, (1, 0)),
+                    (Behaviour::Move_West, (-1, 0)),
                 ] {
-                    let (dest_x, dest_y)
-                        = (i as f32 + (potential_move.1).0,
-                           j as f32 + (potential_move.1).1);
-                    let (dest_x, dest_y)
-                        = (dest_x as usize,
-                           dest_y as usize);
+                    let (dx, dy) = potential_move.1;
+                    let dest = match neighbourhood.get(dx, dy) {
+                        Some(tile) => tile,
+                        None => continue,
+                    };
+                    let (dest_x, dest_y) = ((i as i32 + dx as i32) as usize,
+                                            (j as i32 + dy as i32) as usize);
+
+                    let dest_variant = dest.get_variant();
+                    if dest_variant == Variant::None {
+                        continue;
+                    }
 
                     // Calculates the distance from the potential tile to the
-                    // target tile using the pythagorean theorum.
-                    let new_distance
-                        = ((target_seat.0 as f32 - dest_x as f32).abs()
-                           + (target_seat.1 as f32 - dest_y as f32).abs());
+                    // target tile using the pythagorean theorum, or along the
+                    // aisles when making for an access tile.
+                    let new_distance = match &access_distances {
+                        Some(distances) => match distances[dest_x][dest_y] {
+                            Some(d) => d as f32 + 1.0,
+                            None => continue,
+                        },
+                        None => (goal.0 as f32 - dest_x as f32).abs()
+                            * lateral_weight
+                            + (goal.1 as f32 - dest_y as f32).abs()
+                            + (target_seat.0 as f32 - goal.0 as f32).abs(),
+                    };
+
+                    // Moving sideways is only allowed along an aisle, or onto
+                    // the passenger's own row. A passenger already in the
+                    // aisle its seat is approached from stays in it until it
+                    // reaches its row, rather than drifting off along a
+                    // cross-aisle into the path of others.
+                    let in_approach = approach == Some(i as u16)
+                        && access_distances.is_none();
+                    let sideways_allowed = dest_y == target_seat.1 as usize
+                        || (!in_approach
+                            && (dest_variant == Variant::Aisle
+                                || dest_variant == Variant::Entrance));
+
+                    if let Some(c) = candidates.as_mut() {
+                        c.push((potential_move.0, new_distance));
+                    }
+
+                    // Check whether the movement would place this passenger on
+                    // the wrong row and, if not, whether there is room for it
+                    // on the desired tile. Two passengers can temporarily share
+                    // an occupied space, albeit with a delay.
+                    let sideways = potential_move.0 == Behaviour::Move_East
+                        || potential_move.0 == Behaviour::Move_West;
+                    let legal = (!sideways || sideways_allowed)
+                        && (!dest.is_occupied()
+                            || (dest_x, dest_y) == (i, j)
+                            || (!dest.is_allowing()
+                                && (dest_variant == Variant::Seat
+                                    || (dest_variant == Variant::Aisle
+                                        && self.yields_to(dest_x, dest_y,
+                                                          target_seat)))));
+                    if legal && potential_move.0 != Behaviour::Wait {
+                        can_move = true;
+                    }
 
                     if new_distance < current_move.1 {
-                        // Check whether the movement would place this passenger
-                        // on the wrong row and, if so, wait instead.
-                        if !(potential_move.0 == Behaviour::Move_East
-                             && dest_y != target_seat.1 as usize)
-                            && !(potential_move.0 == Behaviour::Move_West
-                                 && dest_y != target_seat.1 as usize) {
-                            // Check whether desired seat is occupied
-                            if !self.layout[dest_x][dest_y].is_occupied()
-                                || (dest_x, dest_y) == (i, j) {
-                                current_move = (potential_move.0, new_distance);
-                                log::debug!("NEW MOVE: {:?} x {}",
-                                            current_move.0,
-                                            current_move.1);
-                            } else if !self.layout[dest_x][dest_y].is_allowing()
-                                && self.layout[dest_x][dest_y]
-                                    .get_variant() == Variant::Seat {
-                                // This movement is possible even though the
-                                // position is occupied, as two passengers can
-                                // temporarily share a space albeit with a
-                                // delay.
-                                current_move = (potential_move.0, new_distance);
-                                log::debug!("NEW MOVE: {:?} x {}",
-                                            current_move.0,
-                                            current_move.1);
-                            } else {
-                                log::debug!("No room to get past");
-                                log::debug!("REJECTED: {:?} x {}",
-                                            potential_move.0,
-                                            new_distance);
-                            }
+                        if legal {
+                            current_move = (potential_move.0, new_distance);
+                            log::debug!("NEW MOVE: {:?} x {}",
+                                        current_move.0,
+                                        current_move.1);
+                        } else if !sideways || sideways_allowed {
+                            log::debug!("No room to get past");
+                            log::debug!("REJECTED: {:?} x {}",
+                                        potential_move.0,
+                                        new_distance);
                         }
                     } else {
                         log::debug!("REJECTED: {:?} x {}",
@@ -193,6 +1002,20 @@ impl Aircraft {
                                     + (target_seat.1 as f32 - dest_y as f32)
                                         .abs());
 
+                if let Some(c) = candidates.as_mut() {
+                    c.push((potential_move.0, new_distance));
+                }
+
+                if potential_move.0 != Behaviour::Wait {
+                    let dx = (potential_move.1).0 as i8;
+                    if let Some(dest) = neighbourhood.get(dx, 0) {
+                        if dest.get_variant() != Variant::None
+                        && (!dest.is_occupied() || !dest.is_allowing()) {
+                            can_move = true;
+                        }
+                    }
+                }
+
                 if new_distance < current_move.1 {
                     current_move = (potential_move.0, new_distance);
                     log::debug!("NEW MOVE: {:?} x {}",
@@ -206,548 +1029,4795 @@ impl Aircraft {
             }
         }
 
-        return current_move;
+        if !can_move {
+            log::debug!("No legal move");
+            return None;
+        }
+        return Some(current_move);
     }
     
+    /// Determines the best move for the occupier of the tile at `x`,`y`, or
+    /// the passenger passing through it if `passing` is set, recording the
+    /// decision in the decision trace if enabled.
+    fn decide(&mut self,
+              x: usize,
+              y: usize,
+              target: (u16, u16),
+              baggage: bool,
+              passing: bool) -> (Behaviour, f32) {
+        // A delayed passenger spends this update waiting for its delay to run
+        // down instead of moving.
+        let person = if passing {
+            self.layout[x][y].get_passer_as_mut()
+        } else {
+            self.layout[x][y].get_occupier_as_mut()
+        }.unwrap();
+        let delayed = person.tick_delay();
+        let reserved_bin = person.has_reserved_bin();
+        let door = if self.forbid_backtracking {
+            person.get_door()
+        } else {
+            None
+        };
+
+        // Candidate moves are only gathered when tracing, or below for a
+        // passenger that ends up blocked, to keep the common case cheap.
+        let mut candidates = Vec::<(Behaviour, f32)>::new();
+        let chosen = if delayed {
+            Some((Behaviour::Wait, 0.0))
+        } else {
+            let sink = if self.tracing {
+                Some(&mut candidates)
+            } else {
+                None
+            };
+            self.determine_move_traced(x, y,
+                                       target.0, target.1,
+                                       baggage,
+                                       reserved_bin,
+                                       sink)
+        };
+        let boxed_in = chosen.is_none();
+        let current_move = self.record_boxed_in(x, y, passing, chosen);
+        let current_move = if self.is_backtracking(x, y, door, current_move.0) {
+            (Behaviour::Wait, current_move.1)
+        } else {
+            current_move
+        };
+        if current_move.0 == Behaviour::Wait && current_move.1 > 0.0 {
+            if !self.tracing {
+                self.determine_move_traced(x, y, target.0, target.1, baggage,
+                                           reserved_bin,
+                                           Some(&mut candidates));
+            }
+            self.record_blocker(x, y, passing, &candidates);
+        }
+        if !self.tracing {
+            return current_move;
+        }
+
+        let person = if passing {
+            self.layout[x][y].get_passer()
+        } else {
+            self.layout[x][y].get_occupier()
+        }.unwrap();
+        let decision = MoveDecision {
+            passenger: person.get_name(),
+            id: person.get_id(),
+            position: (x as u16, y as u16),
+            passing,
+            candidates,
+            chosen: current_move.0,
+            boxed_in,
+        };
+        if let Some(tick) = self.decision_trace.last_mut() {
+            tick.decisions.push(decision);
+        }
+
+        return current_move;
+    }
+
+    /// Notes the passenger at `x`,`y` as boxed in if it has no legal move, in
+    /// which case it waits where it is.
+    fn record_boxed_in(&mut self,
+                       x: usize,
+                       y: usize,
+                       passing: bool,
+                       chosen: Option<(Behaviour, f32)>) -> (Behaviour, f32) {
+        if let Some(chosen) = chosen {
+            return chosen;
+        }
+        let person = if passing {
+            self.layout[x][y].get_passer()
+        } else {
+            self.layout[x][y].get_occupier()
+        }.unwrap();
+        if let Some(id) = person.get_id() {
+            self.boxed_in.push(id);
+        }
+        (Behaviour::Wait, f32::INFINITY)
+    }
+
+    /// Returns the ids of the passengers that had no legal move at all on the
+    /// last update, rather than choosing to wait.
+    pub fn boxed_in(&self) -> &[usize] {
+        &self.boxed_in
+    }
+
+    /// Records which passenger is in the way of a passenger at `x`,`y` that
+    /// has had to wait despite not being in its seat.
+    ///
+    /// The blocker is whoever is standing on the tile the waiting passenger
+    /// would most like to move to, out of the `candidates` it scored.
+    fn record_blocker(&mut self,
+                      x: usize,
+                      y: usize,
+                      passing: bool,
+                      candidates: &[(Behaviour, f32)]) {
+        let waiting = if passing {
+            self.layout[x][y].get_passer()
+        } else {
+            self.layout[x][y].get_occupier()
+        }.unwrap().get_id();
+
+        let wanted = candidates.iter()
+            .filter(|(behaviour, _)| *behaviour != Behaviour::Wait)
+            .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+        let (dest_x, dest_y) = match wanted {
+            Some((Behaviour::Move_North, _)) => (x, y.wrapping_sub(1)),
+            Some((Behaviour::Move_South, _)) => (x, y + 1),
+            Some((Behaviour::Move_East, _)) => (x + 1, y),
+            Some((Behaviour::Move_West, _)) => (x.wrapping_sub(1), y),
+            _ => return,
+        };
+        if dest_x >= self.size.0 as usize || dest_y >= self.size.1 as usize {
+            return;
+        }
+
+        let blocker = self.layout[dest_x][dest_y].get_occupier()
+            .and_then(|p| p.get_id());
+        if let (Some(waiting), Some(blocker)) = (waiting, blocker) {
+            self.stats.passengers[waiting].blocked_by = Some(blocker);
+        }
+    }
+
+    /// Enables or disables collection of the decision trace.
+    ///
+    /// While enabled, every update appends a `TickDecisions` recording the move
+    /// chosen by each passenger, to be collected with `take_decision_trace`.
+    pub fn set_decision_trace(&mut self, enabled: bool) {
+        self.tracing = enabled;
+    }
+
+    /// Removes and returns the decision trace collected so far.
+    pub fn take_decision_trace(&mut self) -> Vec<TickDecisions> {
+        std::mem::take(&mut self.decision_trace)
+    }
+
     /// Updates every tile in the aircraft based on its current best move.
     ///
     /// Runs `determine_move` on every occupied tile in the aircraft, and, where
     /// possible, executes this move.
     pub fn update(&mut self) {
+        self.step();
+    }
+
+    /// Updates the aircraft once, as `update`, and reports what happened.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let report = aircraft.step();
+    ///
+    /// if report.entered {
+    ///     println!("A passenger boarded on tick {}", report.tick);
+    /// }
+    /// ```
+    pub fn step(&mut self) -> StepReport {
+        let mut report = StepReport::new(self.stats.ticks + 1);
+        let seated_before = self.seated_count();
+        self.boxed_in.clear();
+
+        if self.tracing {
+            self.decision_trace.push(TickDecisions {
+                tick: self.stats.ticks + 1,
+                decisions: Vec::<MoveDecision>::new(),
+            });
+        }
+
+        // Beamed passengers stay where they are placed until the next update.
+        if self.beam_boarding && self.stats.ticks == 0 {
+            report.entered = self.beam_passengers(report.tick);
+        }
+
         for x in 0..self.size.0 as usize {
             for y in 0..self.size.1 as usize {
-                // Check current tile variant
-                    if !self.layout[x][y].has_updated() && (
-                    self.layout[x][y].get_variant() == Variant::Entrance ||
-                    self.layout[x][y].get_variant() == Variant::Aisle ||
-                    self.layout[x][y].get_variant() == Variant::Seat ) {
-                        // Check whether current tile is occupied
-                        if self.layout[x][y].get_occupier().is_some() {
-                            log::trace!("DEBUG: {:?}",
-                                        self.layout[x][y]
-                                            .get_occupier()
-                                            .unwrap()
-                                            .has_baggage());
-
-                            // Choose movement
-                            let target = self.layout[x][y].get_occupier()
-                                .unwrap().get_seat().unwrap();
-                            let baggage = self.layout[x][y].get_occupier()
-                                .unwrap().has_baggage();
-                            let current_move =
-                                self.determine_move(x, y,
-                                                    target.0, target.1,
-                                                    baggage);
-
-                            if current_move.0 != Behaviour::Wait
-                            && current_move.0 != Behaviour::Stow {
-                                log::debug!("Passenger moved: {:?}",
-                                            current_move.0);
-
-                                let coords = match current_move.0 {
-                                    Behaviour::Move_North => (x, y - 1),
-                                    Behaviour::Move_South => (x, y + 1),
-                                    Behaviour::Move_East => (x + 1, y),
-                                    Behaviour::Move_West => (x - 1, y),
-                                    _ => {
-                                        // Invalid move; wait instead
-                                        log::warn!("Invalid move selected");
-                                        (x, y) // May be slightly buggy, however
-                                               // this branch shouldn't ever be
-                                               // called
-                                    },
-                                };
-
-                                if !self.layout[coords.0][coords.1]
-                                  .is_occupied() {
-                                    let person = self.layout[x][y].free();
-                                    self.layout[coords.0][coords.1]
-                                      .occupy(person.unwrap());
-                                } else if !self.layout[coords.0][coords.1]
-                                  .is_allowing() {
-                                    let person = self.layout[x][y].free();
-                                    self.layout[coords.0][coords.1]
-                                      .pass_in(person.unwrap());
-                                } else {
-                                    log::debug!("Passenger Waited");
-                                }
-                            } else if current_move.0 == Behaviour::Stow {
-                                log::debug!("Passenger stowed");
+                // A tile's variant can't change during an update, so it is
+                // only read once per tile.
+                let variant = self.layout[x][y].variant();
+                if !self.layout[x][y].has_updated()
+                && variant != Variant::None {
+                    // Check whether current tile is occupied by someone
+                    // still to settle; a settled passenger never moves
+                    // again, so isn't evaluated at all.
+                    if self.layout[x][y].get_occupier()
+                        .filter(|p| !p.is_settled()).is_some() {
+                        log::trace!("DEBUG: {:?}",
+                                    self.layout[x][y]
+                                        .get_occupier()
+                                        .unwrap()
+                                        .has_baggage());
+
+                        // Choose movement
+                        let target = self.layout[x][y].get_occupier()
+                            .unwrap().get_seat().unwrap();
+                        let baggage = self.layout[x][y].get_occupier()
+                            .unwrap().has_baggage();
+                        let delayed = self.layout[x][y].get_occupier()
+                            .unwrap().is_delayed();
+                        let current_move =
+                            self.decide(x, y, target, baggage, false);
+                        let current_move =
+                            self.filter_move(x, y, false, current_move);
+                        let current_move = self.stow_then_step(
+                            x, y, false, current_move, &mut report);
+
+                        if current_move.0 != Behaviour::Wait
+                        && current_move.0 != Behaviour::Stow
+                        && current_move.0 != Behaviour::Settle {
+                            log::debug!("Passenger moved: {:?}",
+                                        current_move.0);
+
+                            let coords = match current_move.0 {
+                                Behaviour::Move_North => (x, y - 1),
+                                Behaviour::Move_South => (x, y + 1),
+                                Behaviour::Move_East => (x + 1, y),
+                                Behaviour::Move_West => (x - 1, y),
+                                _ => {
+                                    // Invalid move; wait instead
+                                    log::warn!("Invalid move selected");
+                                    (x, y) // May be slightly buggy, however
+                                           // this branch shouldn't ever be
+                                           // called
+                                },
+                            };
+
+                            let id = self.layout[x][y].get_occupier()
+                                .unwrap().get_id();
+                            if self.layout[x][y].get_occupier().unwrap()
+                              .is_oscillating(coords.0 as u16,
+                                              coords.1 as u16) {
+                                // Standing still for an update breaks the
+                                // pattern, letting it try again next time.
+                                log::warn!("Passenger moving back and \
+                                            forth; waiting instead");
                                 self.layout[x][y].get_occupier_as_mut()
-                                    .unwrap().remove_baggage();
+                                  .unwrap()
+                                  .record_position(x as u16, y as u16);
+                                self.stats.waits += 1;
+                            } else if !self.layout[coords.0][coords.1]
+                              .is_occupied() {
+                                let mut person =
+                                  self.layout[x][y].free().unwrap();
+                                self.arrive(&mut person, coords);
+                                self.layout[coords.0][coords.1]
+                                  .occupy(person);
+                                report.moved += 1;
+                                self.log_move(id, (x, y), coords, false);
+                            } else if !self.layout[coords.0][coords.1]
+                              .is_allowing() {
+                                let mut person =
+                                  self.layout[x][y].free().unwrap();
+                                self.arrive(&mut person, coords);
+                                self.layout[coords.0][coords.1]
+                                  .pass_in(person);
+                                report.moved += 1;
+                                self.record_interference(coords);
+                                self.log_move(id, (x, y), coords, false);
                             } else {
-                                log::debug!("Passenger waited");
+                                log::debug!("Passenger Waited");
+                                self.stats.waits += 1;
+                            }
+                        } else if current_move.0 == Behaviour::Stow
+                            && self.loses_bin_contest(x, y, false) {
+                            log::debug!("Passenger gave up bin space");
+                            self.stats.waits += 1;
+                        } else if current_move.0 == Behaviour::Stow {
+                            log::debug!("Passenger stowed");
+                            self.stow_bag(x, y, false, &mut report);
+                        } else if current_move.0 == Behaviour::Settle
+                            && !delayed {
+                            if self.layout[x][y].get_occupier().unwrap()
+                              .is_transit() {
+                                self.layout[x][y].free();
+                                log::info!("Transit left");
+                            } else {
+                                self.layout[x][y].get_occupier_as_mut()
+                                    .unwrap().settle();
                             }
+                        } else {
+                            log::debug!("Passenger waited");
+                            self.stats.waits += 1;
                         }
-                        
-                        if self.layout[x][y].is_allowing()
-                        && self.layout[x][y].pass_count() {
-                            // Choose movement
-                            let target = self.layout[x][y]
-                                .get_passer().unwrap().get_seat().unwrap();
-                            let baggage = self.layout[x][y]
-                                .get_passer().unwrap().has_baggage();
-                            let current_move = 
-                                self.determine_move(x, y,
-                                                    target.0, target.1,
-                                                    baggage);
-                            
-
-                            if current_move.0 != Behaviour::Wait
-                            && current_move.0 != Behaviour::Stow {
-                                log::debug!("Passenger moved: {:?}",
-                                            current_move.0);
-                                    
-                                let coords = match current_move.0 {
-                                    Behaviour::Move_North => (x, y - 1),
-                                    Behaviour::Move_South => (x, y + 1),
-                                    Behaviour::Move_East => (x + 1, y),
-                                    Behaviour::Move_West => (x - 1, y),
-                                    _ => {
-                                        log::warn!("Impossible movement");
-                                        (x, y)
-                                    },
-                                };
-                                
-                                if !self.layout[coords.0][coords.1]
-                                  .is_occupied() {
-                                    let person = self.layout[x][y]
-                                      .pass_out();
-                                    self.layout[coords.0][coords.1]
-                                      .occupy(person);
-                                } else if !self.layout[coords.0][coords.1]
-                                  .is_allowing() {
-                                    let person =
-                                      self.layout[x][y].pass_out();
-                                    self.layout[coords.0][coords.1]
-                                      .pass_in(person);
-                                } else {
-                                    log::debug!("Passenger waited");
-                                }
-                                
-                            } else if current_move.0 == Behaviour::Stow {
-                                log::debug!("Stowed");
-                                self.layout[x][y]
-                                    .get_passer_as_mut()
-                                    .unwrap()
-                                    .remove_baggage();
-                                println!("DEBUG: STOWING");
+                    }
+
+                    // A passenger pooled at the door holds there behind
+                    // the one ahead of it rather than overtaking it.
+                    let pooled = self.vestibule_pooling
+                        && variant == Variant::Entrance
+                        && self.layout[x][y].is_allowing();
+                    let pass_wait = self.pass_wait(y);
+                    if pooled && !self.layout[x][y].is_occupied() {
+                        self.step_up_pooled(x, y);
+                    } else if pooled {
+                        log::debug!("Passenger held at the door");
+                        self.stats.waits += 1;
+                    } else if self.layout[x][y].is_allowing()
+                    && self.layout[x][y].pass_count(pass_wait) {
+                        // Choose movement
+                        let target = self.layout[x][y]
+                            .get_passer().unwrap().get_seat().unwrap();
+                        let baggage = self.layout[x][y]
+                            .get_passer().unwrap().has_baggage();
+                        let current_move =
+                            self.decide(x, y, target, baggage, true);
+                        let current_move =
+                            self.filter_move(x, y, true, current_move);
+                        let current_move = self.stow_then_step(
+                            x, y, true, current_move, &mut report);
+
+                        if current_move.0 != Behaviour::Wait
+                        && current_move.0 != Behaviour::Stow
+                        && current_move.0 != Behaviour::Settle {
+                            log::debug!("Passenger moved: {:?}",
+                                        current_move.0);
+
+                            let coords = match current_move.0 {
+                                Behaviour::Move_North => (x, y - 1),
+                                Behaviour::Move_South => (x, y + 1),
+                                Behaviour::Move_East => (x + 1, y),
+                                Behaviour::Move_West => (x - 1, y),
+                                _ => {
+                                    log::warn!("Impossible movement");
+                                    (x, y)
+                                },
+                            };
+
+                            let id = self.layout[x][y].get_passer()
+                                .unwrap().get_id();
+                            if self.layout[x][y].get_passer().unwrap()
+                              .is_oscillating(coords.0 as u16,
+                                              coords.1 as u16) {
+                                // Standing still for an update breaks the
+                                // pattern, letting it try again next time.
+                                log::warn!("Passenger moving back and \
+                                            forth; waiting instead");
+                                self.layout[x][y].get_passer_as_mut()
+                                  .unwrap()
+                                  .record_position(x as u16, y as u16);
+                                self.stats.waits += 1;
+                            } else if !self.layout[coords.0][coords.1]
+                              .is_occupied() {
+                                let mut person = self.layout[x][y]
+                                  .pass_out();
+                                self.arrive(&mut person, coords);
+                                self.layout[coords.0][coords.1]
+                                  .occupy(person);
+                                report.moved += 1;
+                                self.log_move(id, (x, y), coords, true);
+                            } else if !self.layout[coords.0][coords.1]
+                              .is_allowing() {
+                                let mut person =
+                                  self.layout[x][y].pass_out();
+                                self.arrive(&mut person, coords);
+                                self.layout[coords.0][coords.1]
+                                  .pass_in(person);
+                                report.moved += 1;
+                                self.record_interference(coords);
+                                self.log_move(id, (x, y), coords, true);
                             } else {
                                 log::debug!("Passenger waited");
+                                self.stats.waits += 1;
                             }
+
+                        } else if current_move.0 == Behaviour::Stow
+                            && self.loses_bin_contest(x, y, true) {
+                            log::debug!("Passenger gave up bin space");
+                            self.stats.waits += 1;
+                        } else if current_move.0 == Behaviour::Stow {
+                            log::debug!("Passenger stowed");
+                            self.stow_bag(x, y, true, &mut report);
+                        } else if current_move.0 == Behaviour::Settle
+                            && self.layout[x][y].get_occupier().unwrap()
+                              .get_seat() != Some((x as u16, y as u16)) {
+                            // The seat is the passer's, so it takes it
+                            // over and the occupier carries on past it.
+                            log::debug!("Passenger took its seat back");
+                            self.layout[x][y].swap_places();
+                            self.layout[x][y].get_occupier_as_mut()
+                                .unwrap().settle();
+                        } else {
+                            log::debug!("Passenger waited");
+                            self.stats.waits += 1;
                         }
-                        
-                        if self.layout[x][y].get_variant() == Variant::Entrance
-                        && self.passengers.len() > 0
-                        && !self.layout[x][y].is_occupied() {
-                            self.layout[x][y]
-                                .occupy(self.passengers.pop().unwrap());
+                    }
+
+                    // The occupier of the door, if it is still there, has
+                    // had its turn and is held up by someone ahead.
+                    let held = self.vestibule_pooling && !pooled
+                        && !self.layout[x][y].is_allowing()
+                        && self.layout[x][y].get_occupier()
+                            .filter(|p| !p.is_delayed()).is_some();
+                    if variant == Variant::Entrance
+                    && (!self.layout[x][y].is_occupied() || held)
+                    && self.door_open(x as u16, y as u16, report.tick) {
+                        // Only passengers pool at the door.
+                        let transit = self.transits.iter()
+                            .position(|(door, _)| {
+                                !held && *door == (x as u16, y as u16)
+                            });
+                        if let Some(index) = transit {
+                            let (_, mut person) =
+                                self.transits.remove(index);
+                            person.record_position(x as u16, y as u16);
+                            self.layout[x][y].occupy(person);
+                            log::info!("Added transit");
+                        } else if let Some(mut person) =
+                            self.next_through_bridge() {
+                            self.record_boarding(&person, x, y);
+                            person.set_door(x as u16, y as u16);
+                            let baggage = person.has_baggage();
+                            self.check_bag_at_door(&mut person);
+                            person.record_position(x as u16, y as u16);
+                            if self.logging {
+                                self.events.push(SimEvent::Boarded {
+                                    tick: report.tick,
+                                    id: person.get_id(),
+                                    position: (x as u16, y as u16),
+                                    gate_checked: baggage
+                                        && !person.has_baggage(),
+                                });
+                            }
+                            if held {
+                                self.layout[x][y].pass_in(person);
+                            } else {
+                                self.layout[x][y].occupy(person);
+                            }
                             log::info!("Added passenger");
+                            report.entered = true;
                         }
+                    }
                 }
             }
         }
 
+        self.advance_jet_bridge();
+        if report.entered && self.passengers.is_empty()
+        && self.jet_bridge.iter().all(|p| p.is_none()) {
+            self.stats.last_boarded_tick = Some(report.tick);
+            if let Some(hook) = self.on_last_boarded.as_mut() {
+                hook(report.tick);
+            }
+        }
+        self.reassign_overdue(report.tick);
+        self.redistribute_bins(report.tick);
+        debug_assert_eq!(self.passenger_census(), self.stats.passengers.len(),
+                         "Passengers were lost or duplicated");
+        self.record_tick();
+
         // Sets every tile's `updated` value to false, to ensure no inaccurate
         // behaviours on the next update.
         self.reset();
+
+        report.seated_delta = self.seated_count() as i32 - seated_before as i32;
+        report.complete = self.is_complete();
+        return report;
     }
 
-    /// Repeatedly updates an aircraft until either all passengers are seated or
-    /// an arbitrary limit (`MAX_ITERATIONS`) is reached.
-    ///
-    /// Primarily intended for use when running in parallel; this method does
-    /// not allow any monitoring of the aircraft while it is running and is
-    /// therefore unsuitable for use in interactive mode.
-    pub fn run_to_completion(&mut self) -> Result<u16, &'static str> {
-        let mut iterations = 0;
-        while !self.is_complete() && iterations < MAX_ITERATIONS {
-            self.update();
-            iterations += 1;
-        }
-        if self.is_complete() {
-            return Ok(iterations);
+    /// Stows the baggage of the occupier of the tile at `x`,`y`, or the
+    /// passenger passing through it if `passing` is set.
+    fn stow_bag(&mut self,
+                x: usize,
+                y: usize,
+                passing: bool,
+                report: &mut StepReport) {
+        let person = if passing {
+            self.layout[x][y].get_passer_as_mut()
         } else {
-            return Err(&"Passengers could not all be seated.");
+            self.layout[x][y].get_occupier_as_mut()
+        }.unwrap();
+        person.stow();
+        let id = person.get_id();
+        report.stowed += 1;
+        self.stats.stows += 1;
+        self.store_bag(y);
+        self.log_stow(id, (x, y), passing);
+    }
+
+    /// With `seat_after_stow` set, carries out a passenger's chosen `Stow`
+    /// straight away and returns the move it makes on the same update instead,
+    /// or `Wait` if it has a stow delay to serve first. Any other move is
+    /// returned as it is.
+    fn stow_then_step(&mut self,
+                      x: usize,
+                      y: usize,
+                      passing: bool,
+                      chosen: (Behaviour, f32),
+                      report: &mut StepReport) -> (Behaviour, f32) {
+        if !self.seat_after_stow || chosen.0 != Behaviour::Stow
+        || self.loses_bin_contest(x, y, passing) {
+            return chosen;
+        }
+        log::debug!("Passenger stowed");
+        self.stow_bag(x, y, passing, report);
+
+        let person = if passing {
+            self.layout[x][y].get_passer_as_mut()
+        } else {
+            self.layout[x][y].get_occupier_as_mut()
+        }.unwrap();
+        // The last update of the stow delay is spent stepping on instead.
+        if person.tick_delay() {
+            return (Behaviour::Wait, 0.0);
+        }
+        let target = person.get_seat().unwrap();
+        let next = self.decide(x, y, target, false, passing);
+        self.filter_move(x, y, passing, next)
+    }
+
+    /// Enables or disables collection of the event log.
+    ///
+    /// While enabled, every passenger boarding, moving, or stowing its baggage
+    /// is recorded as a `SimEvent`, to be collected with `take_events`.
+    pub fn set_event_log(&mut self, enabled: bool) {
+        self.logging = enabled;
+    }
+
+    /// Removes and returns the events collected so far.
+    pub fn take_events(&mut self) -> Vec<SimEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// Records a passenger moving from one tile to another in the event log.
+    fn log_move(&mut self,
+                id: Option<usize>,
+                from: (usize, usize),
+                to: (usize, usize),
+                passing: bool) {
+        // Only passengers have ids, so transit actors are left out.
+        if self.logging && id.is_some() {
+            self.events.push(SimEvent::Moved {
+                tick: self.stats.ticks + 1,
+                id,
+                from: (from.0 as u16, from.1 as u16),
+                to: (to.0 as u16, to.1 as u16),
+                passing,
+            });
+        }
+    }
+
+    /// Records a passenger stowing its baggage in the event log.
+    fn log_stow(&mut self,
+                id: Option<usize>,
+                position: (usize, usize),
+                passing: bool) {
+        if self.logging {
+            self.events.push(SimEvent::Stowed {
+                tick: self.stats.ticks + 1,
+                id,
+                position: (position.0 as u16, position.1 as u16),
+                passing,
+            });
+        }
+    }
+
+    /// Reconstructs a boarding from its event log, without running the
+    /// simulation.
+    ///
+    /// The aircraft should have the same layout and waiting passengers as the
+    /// one the events were collected from. Each event is applied in turn
+    /// exactly as it happened, and the statistics recorded at the end of each
+    /// update as they would be by `update`, so every passenger ends up where
+    /// it was in the original on the tick of the last event. Passengers board
+    /// along the jet bridge, if there is one. Returns an error if an event
+    /// can't be applied, e.g. a boarding passenger isn't next through the door
+    /// or the passenger named isn't on the tile it moves from.
+    pub fn replay(&mut self, events: &[SimEvent])
+        -> Result<(), &'static str> {
+        let mut entered = false;
+        for event in events {
+            while self.stats.ticks + 1 < event.get_tick() {
+                self.finish_replayed_tick(entered);
+                entered = false;
+            }
+            match *event {
+                SimEvent::Boarded { id, position, gate_checked, .. } => {
+                    let (x, y) = (position.0 as usize, position.1 as usize);
+                    let index = match self.jet_bridge.last() {
+                        Some(end) => {
+                            if end.as_ref().map(|p| p.get_id()) != Some(id) {
+                                return Err("Replayed passenger is not at the \
+                                            end of the jet bridge");
+                            }
+                            None
+                        },
+                        None => Some(self.passengers.iter()
+                            .position(|p| p.get_id() == id)
+                            .ok_or("Replayed passenger is not waiting to \
+                                    board")?),
+                    };
+                    self.step_up_pooled(x, y);
+                    let pooling = self.vestibule_pooling
+                        && !self.layout[x][y].is_allowing();
+                    if self.layout[x][y].is_occupied() && !pooling {
+                        return Err("Replayed passenger boarded onto an \
+                                    occupied tile");
+                    }
+                    let mut person = match index {
+                        Some(index) => self.passengers.remove(index),
+                        None => self.jet_bridge.last_mut().unwrap().take()
+                            .unwrap(),
+                    };
+                    self.record_boarding(&person, x, y);
+                    person.set_door(x as u16, y as u16);
+                    if gate_checked {
+                        person.set_baggage(false);
+                        self.stats.gate_checked += 1;
+                        if let Some(id) = id {
+                            self.stats.passengers[id].gate_checked = true;
+                        }
+                    }
+                    if self.layout[x][y].is_occupied() {
+                        self.layout[x][y].pass_in(person);
+                    } else {
+                        self.layout[x][y].occupy(person);
+                    }
+                    entered = true;
+                },
+                SimEvent::Moved { id, from, to, passing, .. } => {
+                    let (x, y) = (from.0 as usize, from.1 as usize);
+                    if !passing {
+                        self.step_up_pooled(x, y);
+                    }
+                    let mover = if passing {
+                        self.layout[x][y].get_passer()
+                    } else {
+                        self.layout[x][y].get_occupier()
+                    };
+                    if mover.map(|p| p.get_id()) != Some(id) {
+                        return Err("Replayed passenger is not on its tile");
+                    }
+                    let person = if passing {
+                        self.layout[x][y].pass_out()
+                    } else {
+                        self.layout[x][y].free().unwrap()
+                    };
+
+                    let (x, y) = (to.0 as usize, to.1 as usize);
+                    if !self.layout[x][y].is_occupied() {
+                        self.layout[x][y].occupy(person);
+                    } else if !self.layout[x][y].is_allowing() {
+                        self.layout[x][y].pass_in(person);
+                        self.record_interference((x, y));
+                    } else {
+                        return Err("Replayed passenger moved onto a full tile");
+                    }
+                },
+                SimEvent::Stowed { position, passing, .. } => {
+                    let (x, y) = (position.0 as usize, position.1 as usize);
+                    let person = if passing {
+                        self.layout[x][y].get_passer_as_mut()
+                    } else {
+                        self.layout[x][y].get_occupier_as_mut()
+                    };
+                    person.ok_or("Replayed passenger is not on its tile")?
+                        .remove_baggage();
+                    self.stats.stows += 1;
+                    self.store_bag(y);
+                },
+                SimEvent::Reassigned { tick, id, position, to, .. } => {
+                    let id = id.ok_or("Replayed passenger has no id")?;
+                    if !self.reassign(id, position, to, tick) {
+                        return Err("Replayed passenger is not on its tile");
+                    }
+                },
+            }
+        }
+
+        if let Some(last) = events.last() {
+            while self.stats.ticks < last.get_tick() {
+                self.finish_replayed_tick(entered);
+                entered = false;
+            }
+        }
+        Ok(())
+    }
+
+    /// Ends an update being replayed as `update` would, moving the jet bridge
+    /// along and recording the update's statistics. `entered` is whether a
+    /// passenger boarded on it.
+    fn finish_replayed_tick(&mut self, entered: bool) {
+        self.advance_jet_bridge();
+        if entered && self.passengers.is_empty()
+        && self.jet_bridge.iter().all(|p| p.is_none()) {
+            self.stats.last_boarded_tick = Some(self.stats.ticks + 1);
+        }
+        self.record_tick();
+        self.reset();
+    }
+
+    /// Builds a table of where every passenger was on every update of a
+    /// boarding, from the boarding's event log and decision trace, with one
+    /// row per passenger for each update it was onboard, ordered by update
+    /// then id.
+    ///
+    /// Meant to be called on the aircraft the events and decisions were
+    /// collected from once it has finished updating. Without a decision trace
+    /// every row's `behaviour` is `None`.
+    pub fn trajectory_table(&self, events: &[SimEvent], trace: &[TickDecisions])
+        -> Vec<TrajectoryRow> {
+        let mut positions = vec![None; self.stats.passengers.len()];
+        let mut events = events.iter().peekable();
+        let mut rows = Vec::<TrajectoryRow>::new();
+
+        for tick in 1..=self.stats.ticks {
+            while let Some(event) = events.next_if(|e| e.get_tick() <= tick) {
+                match *event {
+                    SimEvent::Boarded { id: Some(id), position, .. } => {
+                        positions[id] = Some(position);
+                    },
+                    SimEvent::Moved { id: Some(id), to, .. } => {
+                        positions[id] = Some(to);
+                    },
+                    _ => {},
+                }
+            }
+
+            let decisions = trace.iter().find(|t| t.tick == tick);
+            for (id, position) in positions.iter().enumerate() {
+                let (x, y) = match position {
+                    Some(position) => *position,
+                    None => continue,
+                };
+                let behaviour = decisions
+                    .and_then(|t| t.decisions.iter()
+                                  .find(|d| d.id == Some(id)))
+                    .map(|d| d.chosen);
+                rows.push(TrajectoryRow {
+                    tick,
+                    id,
+                    passenger: self.stats.passengers[id].name.clone(),
+                    x,
+                    y,
+                    variant: self.layout[x as usize][y as usize].get_variant(),
+                    behaviour,
+                });
+            }
+        }
+        rows
+    }
+
+    /// Moves a passenger pooled at the door at `x`,`y` up to take the place of
+    /// the one that boarded ahead of it, once that one has moved on.
+    ///
+    /// This isn't logged, so is made when replaying as soon as the next event
+    /// at the door needs it.
+    fn step_up_pooled(&mut self, x: usize, y: usize) {
+        if self.vestibule_pooling && !self.layout[x][y].is_occupied()
+        && self.layout[x][y].is_allowing() {
+            let person = self.layout[x][y].pass_out();
+            self.layout[x][y].occupy(person);
+        }
+    }
+
+    /// Moves every passenger onboard that has missed its seat deadline to the
+    /// nearest vacant seat nobody else is assigned, logging each move.
+    ///
+    /// A passenger standing in a seat can only move along its row, so only
+    /// seats on that row, including the one it is in, are considered for it.
+    fn reassign_overdue(&mut self, tick: u16) {
+        if self.seat_deadline.is_none() && self.stats.passengers.iter()
+            .all(|p| p.seat_deadline.is_none()) {
+            return;
+        }
+
+        let positions = self.passenger_positions();
+        // Which seats are free and reachable only changes as passengers are
+        // reassigned, which `targeted_seats` accounts for, so these are found
+        // once for every passenger overdue.
+        let mut free = None;
+        for id in self.unseated() {
+            let stats = &self.stats.passengers[id];
+            let deadline = match stats.seat_deadline.or(self.seat_deadline) {
+                Some(deadline) => deadline,
+                None => continue,
+            };
+            let since = stats.reassigned_tick.or(stats.boarded_tick);
+            let (position, seat, since) = match (positions[id], stats.seat,
+                                                 since) {
+                (Some(position), Some(seat), Some(since)) => {
+                    (position, seat, since)
+                },
+                _ => continue,
+            };
+            if tick - since < deadline {
+                continue;
+            }
+
+            let tile = &self.layout[position.0 as usize][position.1 as usize];
+            let in_seat = tile.get_variant() == Variant::Seat;
+            let (reached, vacancies) = free.get_or_insert_with(|| {
+                (self.entrance_reach(),
+                 (0..self.size.1).map(|row| self.row_vacancies(row))
+                     .collect::<Vec<Vec<(u16, u16)>>>())
+            });
+            let standing_in = match tile.get_occupier() {
+                Some(p) if in_seat && p.get_id() == Some(id) => Some(position),
+                _ => None,
+            };
+            let vacancy = (0..self.size.1)
+                .filter(|&row| !in_seat || row == position.1)
+                .flat_map(|row| vacancies[row as usize].iter().copied())
+                .chain(standing_in)
+                .filter(|s| !self.targeted_seats.contains(s))
+                .filter(|s| self.seat_reachable(reached, *s))
+                .min_by_key(|&(x, y)| {
+                    ((x as i32 - position.0 as i32).abs()
+                     + (y as i32 - position.1 as i32).abs(), y, x)
+                });
+            let vacancy = match vacancy {
+                Some(vacancy) => vacancy,
+                None => {
+                    log::debug!("No vacant seat to reassign passenger to");
+                    continue;
+                },
+            };
+
+            log::warn!("Passenger {} reassigned from seat {:?} to {:?}",
+                       id, seat, vacancy);
+            self.reassign(id, position, vacancy, tick);
+            if self.logging {
+                self.events.push(SimEvent::Reassigned {
+                    tick,
+                    id: Some(id),
+                    position,
+                    from: seat,
+                    to: vacancy,
+                });
+            }
+        }
+    }
+
+    /// Gives the passenger with the given id, standing at `position`, the seat
+    /// `to` in place of its own. Returns false if it isn't there.
+    fn reassign(&mut self,
+                id: usize,
+                position: (u16, u16),
+                to: (u16, u16),
+                tick: u16) -> bool {
+        let tile = &mut self.layout[position.0 as usize][position.1 as usize];
+        let person = if tile.get_occupier().and_then(|p| p.get_id())
+            == Some(id) {
+            tile.get_occupier_as_mut()
+        } else if tile.get_passer().and_then(|p| p.get_id()) == Some(id) {
+            tile.get_passer_as_mut()
+        } else {
+            None
+        };
+        let person = match person {
+            Some(person) => person,
+            None => return false,
+        };
+
+        let from = person.get_seat();
+        person.target_seat(to.0, to.1);
+        person.set_reassigned(true);
+        if let Some(index) = self.targeted_seats.iter()
+            .position(|s| Some(*s) == from) {
+            self.targeted_seats[index] = to;
+        }
+        let stats = &mut self.stats.passengers[id];
+        stats.seat = Some(to);
+        stats.reassigned_from = from;
+        stats.reassigned_tick = Some(tick);
+        true
+    }
+
+    /// Sets the length of the jet bridge, in tiles, that passengers walk along
+    /// from the gate before reaching the door.
+    ///
+    /// Passengers move along the bridge one tile per update, and only onto an
+    /// empty tile, so they queue up in it whenever they can't get onboard. With
+    /// a length of 0 (the default) passengers step straight from the queue to
+    /// the door. Returns an error if there are passengers on the bridge.
+    pub fn set_jet_bridge_len(&mut self, len: u16)
+        -> Result<(), &'static str> {
+        if self.bridge_occupancy() > 0 {
+            return Err("Jet bridge is in use");
+        }
+        self.jet_bridge = (0..len).map(|_| None).collect();
+        Ok(())
+    }
+
+    /// Counts every passenger, whether waiting to board, on the jet bridge, or
+    /// onboard.
+    ///
+    /// Passengers are never removed from the aircraft, so this should always
+    /// equal the number added; this is checked on every update in debug
+    /// builds. Transit actors aren't passengers, so aren't counted.
+    pub fn passenger_census(&self) -> usize {
+        let onboard = self.layout.iter()
+            .flatten()
+            .flat_map(|tile| tile.get_occupier().into_iter()
+                      .chain(tile.get_passer()))
+            .filter(|p| p.get_id().is_some())
+            .count();
+        self.passengers.len() + self.bridge_occupancy() + onboard
+    }
+
+    /// Counts the passengers on the jet bridge.
+    pub fn bridge_occupancy(&self) -> usize {
+        self.jet_bridge.iter().filter(|slot| slot.is_some()).count()
+    }
+
+    /// Takes the next passenger to step through a door: the one at the end of
+    /// the jet bridge if there is one, or else the next waiting passenger.
+    fn next_through_bridge(&mut self) -> Option<Person> {
+        match self.jet_bridge.last_mut() {
+            Some(end) => end.take(),
+            None => self.next_passenger(),
+        }
+    }
+
+    /// Moves every passenger on the jet bridge one tile along it where there is
+    /// room, then lets the next waiting passenger onto it.
+    fn advance_jet_bridge(&mut self) {
+        if self.jet_bridge.is_empty() {
+            return;
+        }
+        for i in (1..self.jet_bridge.len()).rev() {
+            if self.jet_bridge[i].is_none() {
+                self.jet_bridge[i] = self.jet_bridge[i - 1].take();
+            }
+        }
+        if self.jet_bridge[0].is_none() {
+            self.jet_bridge[0] = self.next_passenger();
+        }
+    }
+
+    /// Measures the longest queue at any door: the passengers standing still
+    /// on the unbroken run of occupied aisle tiles leading from an entrance,
+    /// plus those on the jet bridge behind them.
+    ///
+    /// A tile's `updated` flag is set when a passenger arrives on it, so this
+    /// must be called before the flags are reset at the end of an update.
+    fn door_queue(&self) -> u16 {
+        let waiting = |tile: &Tile| tile.is_occupied() && !tile.has_updated();
+        let mut longest = 0;
+        let mut queued =
+            vec![vec![false; self.size.1 as usize]; self.size.0 as usize];
+        for x in 0..self.size.0 as usize {
+            for y in 0..self.size.1 as usize {
+                let tile = &self.layout[x][y];
+                if tile.get_variant() != Variant::Entrance
+                || !waiting(tile) || queued[x][y] {
+                    continue;
+                }
+
+                let queue = grid::bfs(&self.layout, (x, y), |tile| {
+                    matches!(tile.get_variant(),
+                             Variant::Aisle | Variant::Entrance)
+                        && waiting(tile)
+                });
+                let mut length = 0;
+                for (i, j) in queue.keys() {
+                    queued[*i][*j] = true;
+                    length += 1 + self.layout[*i][*j].is_allowing() as u16;
+                }
+                longest = longest.max(length + self.bridge_occupancy() as u16);
+            }
+        }
+        longest
+    }
+
+    /// Sets the updates during which the entrance at `x`,`y` lets passengers
+    /// board.
+    ///
+    /// The door opens on update `open_tick` and, if given, closes again on
+    /// update `close_tick`, after which no one boards through it. Doors without
+    /// a window are open throughout. Returns an error if there is no entrance
+    /// at `x`,`y`.
+    pub fn set_door_window(&mut self,
+                           x: u16,
+                           y: u16,
+                           open_tick: u16,
+                           close_tick: Option<u16>)
+        -> Result<(), &'static str> {
+        if x >= self.size.0 || y >= self.size.1
+        || self.get_tile_variant(x, y) != Variant::Entrance {
+            return Err("No entrance at the given position");
+        }
+        let schedule = self.door_schedule(x, y);
+        schedule.open_tick = open_tick;
+        schedule.close_tick = close_tick;
+        Ok(())
+    }
+
+    /// Sets how many updates apart the entrance at `x`,`y` lets passengers
+    /// board, e.g. so that a narrow overwing exit admits passengers more
+    /// slowly than a wide forward door.
+    ///
+    /// An interval of 1 (the default) lets a passenger board on every update
+    /// the door is free; an interval of 3 lets one board at most every third
+    /// update. Returns an error if there is no entrance at `x`,`y` or the
+    /// interval is 0.
+    pub fn set_door_interval(&mut self, x: u16, y: u16, interval: u16)
+        -> Result<(), &'static str> {
+        if x >= self.size.0 || y >= self.size.1
+        || self.get_tile_variant(x, y) != Variant::Entrance {
+            return Err("No entrance at the given position");
+        }
+        if interval == 0 {
+            return Err("Door interval must be at least 1");
+        }
+        self.door_schedule(x, y).interval = interval;
+        Ok(())
+    }
+
+    /// Finds the schedule of the entrance at `x`,`y`, adding one that is
+    /// always open if it has none yet.
+    fn door_schedule(&mut self, x: u16, y: u16) -> &mut DoorSchedule {
+        let index = match self.doors.iter()
+            .position(|schedule| schedule.door == (x, y)) {
+            Some(index) => index,
+            None => {
+                self.doors.push(DoorSchedule {
+                    door: (x, y),
+                    open_tick: 0,
+                    close_tick: None,
+                    interval: 1,
+                    last_admitted: None,
+                });
+                self.doors.len() - 1
+            },
+        };
+        &mut self.doors[index]
+    }
+
+    /// Sets whether passengers wait at the door, rather than further back
+    /// down the jet bridge, while the aisle ahead of them is blocked. Defaults
+    /// to false.
+    ///
+    /// While enabled, an entrance whose occupier is held up by someone ahead
+    /// lets the next passenger board alongside it. The second passenger keeps
+    /// its place in the queue, stepping up once the first has moved on.
+    pub fn set_vestibule_pooling(&mut self, enabled: bool) {
+        self.vestibule_pooling = enabled;
+    }
+
+    /// Checks whether the entrance at `x`,`y` lets passengers board on the
+    /// given update.
+    fn door_open(&self, x: u16, y: u16, tick: u16) -> bool {
+        let schedule = match self.doors.iter()
+            .find(|schedule| schedule.door == (x, y)) {
+            Some(schedule) => schedule,
+            None => return true,
+        };
+        let admitting = match schedule.last_admitted {
+            Some(last) => tick.saturating_sub(last) >= schedule.interval,
+            None => true,
+        };
+        admitting && tick >= schedule.open_tick && match schedule.close_tick {
+            Some(close) => tick < close,
+            None => true,
+        }
+    }
+
+    /// Records a passenger moving to `x`,`y`, holding it in place there for as
+    /// long as its mobility requires.
+    ///
+    /// Passengers with reduced mobility wait after every move, and wheelchair
+    /// users wait longer on reaching the aisle beside their seat while they
+    /// are helped into it. Reassigned passengers wait on reaching their seat
+    /// while they check it really is theirs.
+    fn arrive(&self, p: &mut Person, (x, y): (usize, usize)) {
+        p.record_position(x as u16, y as u16);
+        if p.is_reassigned() && p.get_seat() == Some((x as u16, y as u16)) {
+            p.delay(REASSIGNED_SEAT_WAIT);
+        }
+        match p.get_mobility() {
+            Mobility::Normal => (),
+            Mobility::Reduced => p.delay(REDUCED_MOBILITY_WAIT),
+            Mobility::Wheelchair => {
+                p.delay(REDUCED_MOBILITY_WAIT);
+                if let Some((seat_x, seat_y)) = p.get_seat() {
+                    if seat_y as usize == y
+                    && self.layout[x][y].get_variant() != Variant::Seat
+                    && self.get_tile_variant(seat_x, seat_y) == Variant::Seat {
+                        p.delay(WHEELCHAIR_SEATING_WAIT);
+                    }
+                }
+            },
+        }
+    }
+
+    /// Counts the targeted seats that are currently occupied.
+    fn seated_count(&self) -> usize {
+        self.targeted_seats.iter()
+            .filter(|(x, y)| self.layout[*x as usize][*y as usize]
+                    .is_occupied())
+            .count()
+    }
+
+    /// Sets how many bags the overhead bins on each row can hold.
+    ///
+    /// With no capacity (the default) the bins never fill. Otherwise, a
+    /// passenger boarding with baggage when the bins on its seat's row are
+    /// already full has its bag checked at the door instead, which holds it
+    /// there for `GATE_CHECK_WAIT` updates before it carries on bag-free.
+    /// Passengers with a reserved bin stow on their own row regardless.
+    pub fn set_bin_capacity(&mut self, capacity: Option<u16>) {
+        self.bin_capacity = capacity;
+    }
+
+    /// Returns the number of bags stowed in the overhead bins on a row.
+    pub fn get_bin_usage(&self, row: u16) -> u16 {
+        self.bins[row as usize]
+    }
+
+    /// Checks whether the overhead bins on a row have no space left.
+    pub fn bins_full(&self, row: u16) -> bool {
+        match self.bin_capacity {
+            Some(capacity) => self.bins[row as usize] >= capacity,
+            None => false,
+        }
+    }
+
+    /// Lists the seats on a row that nobody is sitting in, from left to right.
+    ///
+    /// Seat reassignment picks free seats from this. Bin search and shuffling
+    /// past seated passengers don't depend on which seats are free, so they
+    /// don't call it.
+    pub fn row_vacancies(&self, row: u16) -> Vec<(u16, u16)> {
+        (0..self.size.0)
+            .filter(|&x| {
+                let tile = &self.layout[x as usize][row as usize];
+                tile.get_variant() == Variant::Seat && !tile.is_occupied()
+            })
+            .map(|x| (x, row))
+            .collect()
+    }
+
+    /// Sets how many rows forward of its own a passenger will search for bin
+    /// space when the bins on its row are full.
+    ///
+    /// With a range of 0 (the default), a passenger stows its bag on its own
+    /// row regardless. Otherwise, it walks forward to the nearest row within
+    /// range with space, stows its bag there, and walks back to its seat; its
+    /// bag is only checked at the door if there is no space within range.
+    pub fn set_bin_search_range(&mut self, range: u16) {
+        self.bin_search_range = range;
+    }
+
+    /// Sets how often, in updates, flight attendants move a bag out of full
+    /// overhead bins to make room for passengers still to board. Defaults to
+    /// none, where bags stay wherever they were stowed.
+    ///
+    /// Every `interval` updates, a bag is moved from the rearmost row whose
+    /// bins are full to the nearest row forward of it with space, if there is
+    /// one.
+    pub fn set_attendant_redistribution(&mut self, interval: Option<u16>) {
+        self.attendant_interval = interval;
+    }
+
+    /// Moves a bag forward out of the rearmost full bins, if flight attendants
+    /// are redistributing bags on this update.
+    fn redistribute_bins(&mut self, tick: u16) {
+        let due = self.attendant_interval
+            .and_then(|interval| tick.checked_rem(interval));
+        if due != Some(0) {
+            return;
+        }
+
+        let full = (0..self.size.1).rev().find(|&row| self.bins_full(row));
+        if let Some(row) = full {
+            if let Some(forward) = (0..row).rev()
+                .find(|&r| !self.bins_full(r)) {
+                log::debug!("Attendant moved a bag from row {} to row {}",
+                            row, forward);
+                self.bins[row as usize] -= 1;
+                self.bins[forward as usize] += 1;
+            }
+        }
+    }
+
+    /// Finds the nearest row forward of `row`, within the bin search range,
+    /// whose bins have space.
+    fn bin_search_row(&self, row: u16) -> Option<u16> {
+        (1..=self.bin_search_range)
+            .take_while(|k| *k <= row)
+            .map(|k| row - k)
+            .find(|r| !self.bins_full(*r))
+    }
+
+    /// Checks whether the passenger at `x`,`y` about to stow its bag should
+    /// leave the last space in the bins on its row to another.
+    ///
+    /// When two passengers want the last space on the same update, the one
+    /// with the higher `bin_priority` takes it; on a tie, the one updated
+    /// first (the furthest left, or the occupier of a shared tile) does. The
+    /// other finds the bins full on its next update and looks elsewhere.
+    /// Passengers with a reserved bin never give up their space.
+    fn loses_bin_contest(&self, x: usize, y: usize, passing: bool) -> bool {
+        match self.bin_capacity {
+            Some(capacity) if self.bins[y] + 1 == capacity => (),
+            _ => return false,
+        }
+        let tile = &self.layout[x][y];
+        let person = if passing {
+            tile.get_passer()
+        } else {
+            tile.get_occupier()
+        }.unwrap();
+        if person.has_reserved_bin() {
+            return false;
+        }
+
+        // Only passengers not yet updated can still want the space.
+        let mut rivals = Vec::<(usize, &Person)>::new();
+        if !passing {
+            rivals.extend(tile.get_passer().map(|p| (x, p)));
+        }
+        for i in x + 1..self.size.0 as usize {
+            let tile = &self.layout[i][y];
+            rivals.extend(tile.get_occupier().map(|p| (i, p)));
+            rivals.extend(tile.get_passer().map(|p| (i, p)));
+        }
+
+        rivals.iter().any(|(i, rival)| {
+            let seat = match rival.get_seat() {
+                Some(seat) => seat,
+                None => return false,
+            };
+            rival.get_bin_priority() > person.get_bin_priority()
+                && rival.has_baggage()
+                && !rival.is_delayed()
+                && matches!(self.determine_move_traced(*i, y, seat.0, seat.1,
+                                                       true,
+                                                       rival.has_reserved_bin(),
+                                                       None),
+                            Some((Behaviour::Stow, _)))
+        })
+    }
+
+    /// Places a bag in the overhead bins on a row.
+    fn store_bag(&mut self, row: usize) {
+        self.bins[row] += 1;
+        if let Some(capacity) = self.bin_capacity {
+            if self.bins[row] > capacity {
+                log::warn!("Overhead bins on row {} overfilled", row);
+            }
+        }
+    }
+
+    /// Checks a boarding passenger's bag at the door if the bins on its seat's
+    /// row are already full.
+    fn check_bag_at_door(&mut self, p: &mut Person) {
+        let row = match p.get_seat() {
+            Some(seat) => seat.1,
+            None => return,
+        };
+        if p.has_baggage() && !p.has_reserved_bin() && self.bins_full(row)
+        && self.bin_search_row(row).is_none() {
+            log::info!("Bins on row {} full; checking bag at door", row);
+            p.set_baggage(false);
+            p.delay(GATE_CHECK_WAIT);
+            self.stats.gate_checked += 1;
+            if let Some(id) = p.get_id() {
+                self.stats.passengers[id].gate_checked = true;
+            }
+        }
+    }
+
+    /// Records a passenger coming onboard through the entrance at `x`,`y`,
+    /// including when that entrance last admitted someone.
+    ///
+    /// Without any congestion, the nth passenger to board enters on the nth
+    /// update, then needs one update per tile between the entrance and its
+    /// seat and one more to stow any baggage; the latest of these across all
+    /// passengers is the theoretical minimum number of updates for a boarding.
+    fn record_boarding(&mut self, p: &Person, x: usize, y: usize) {
+        self.stats.boarded += 1;
+        let tick = self.stats.ticks + 1;
+        for schedule in self.doors.iter_mut() {
+            if schedule.door == (x as u16, y as u16) {
+                schedule.last_admitted = Some(tick);
+            }
+        }
+        if let Some(id) = p.get_id() {
+            self.stats.passengers[id].boarded_tick = Some(self.stats.ticks + 1);
+        }
+
+        if let Some((seat_x, seat_y)) = p.get_seat() {
+            let row = &mut self.row_boarded[seat_y as usize];
+            if row.is_none() {
+                *row = Some(self.stats.ticks + 1);
+            }
+
+            let distance = (seat_x as i32 - x as i32).abs()
+                + (seat_y as i32 - y as i32).abs();
+            let minimum = self.stats.boarded as u16
+                + distance as u16
+                + p.has_baggage() as u16;
+            if minimum > self.stats.theoretical_min_ticks {
+                self.stats.theoretical_min_ticks = minimum;
+            }
+            if let Some(id) = p.get_id() {
+                self.stats.passengers[id].ideal_seated_tick = Some(minimum);
+            }
+        }
+    }
+
+    /// Records a passenger squeezing past another onto the tile at `x`,`y`,
+    /// where it is now passing the tile's occupier.
+    fn record_interference(&mut self, (x, y): (usize, usize)) {
+        self.stats.interferences += 1;
+        let tile = &self.layout[x][y];
+        if tile.get_variant() == Variant::Seat {
+            self.stats.shuffles += 1;
+        }
+        let passer = tile.get_passer().and_then(|p| p.get_id());
+        let occupier = tile.get_occupier().and_then(|p| p.get_id());
+        if let (Some(passer), Some(occupier)) = (passer, occupier) {
+            *self.stats.pair_interferences.entry((passer, occupier))
+                .or_insert(0) += 1;
+        }
+    }
+
+    /// Records the statistics for a single update.
+    ///
+    /// Every passenger onboard has the variant of the tile it finished the
+    /// update on counted towards its `PassengerStats`. On the update the
+    /// aircraft is first complete, its boarding efficiency is calculated, and
+    /// on the update each row is first filled, its dwell time is.
+    fn record_tick(&mut self) {
+        self.stats.ticks += 1;
+        for row in 0..self.size.1 {
+            if let Some(boarded) = self.row_boarded[row as usize] {
+                let filled = self.targeted_seats.iter()
+                    .filter(|(_, y)| *y == row)
+                    .all(|(x, y)| self.check_if_occupied(*x, *y));
+                if filled {
+                    self.stats.row_dwell[row as usize]
+                        = self.stats.ticks - boarded;
+                    self.row_boarded[row as usize] = None;
+                }
+            }
+        }
+
+        for (x, column) in self.layout.iter().enumerate() {
+            for (y, tile) in column.iter().enumerate() {
+                let peak = &mut self.peak_occupancy[x][y];
+                *peak = (*peak).max(tile.occupants());
+                let variant = tile.get_variant();
+                for person in tile.get_occupier().iter()
+                    .chain(tile.get_passer().iter()) {
+                    let id = match person.get_id() {
+                        Some(x) => x,
+                        None => continue,
+                    };
+                    let passenger = &mut self.stats.passengers[id];
+                    match variant {
+                        Variant::Aisle | Variant::Entrance => {
+                            passenger.aisle_ticks += 1;
+                            self.stats.total_aisle_person_ticks += 1;
+                        },
+                        Variant::Seat => passenger.seated_ticks += 1,
+                        Variant::None => (),
+                    }
+                    if passenger.seated_tick.is_none()
+                    && person.get_seat() == Some((x as u16, y as u16)) {
+                        passenger.seated_tick = Some(self.stats.ticks);
+                        self.seated += 1;
+                    }
+                }
+            }
+        }
+
+        self.stats.max_door_queue =
+            self.stats.max_door_queue.max(self.door_queue());
+
+        if self.stats.complete_tick.is_none() && self.all_seated() {
+            self.stats.complete_tick = Some(self.stats.ticks);
+            self.stats.efficiency = if self.stats.passengers.is_empty() {
+                1.0
+            } else {
+                (self.stats.theoretical_min_ticks as f32
+                    / self.stats.ticks as f32).clamp(0.0, 1.0)
+            };
+        }
+
+        if self.stats.complete_tick == Some(self.stats.ticks) {
+            self.stats.critical_path = self.critical_path(self.last_seated());
+            self.stats.per_zone = self.stats.zone_stats();
+        }
+    }
+
+    /// Returns the id of the last passenger to be seated, if any.
+    fn last_seated(&self) -> Option<usize> {
+        let passengers = &self.stats.passengers;
+        (0..passengers.len())
+            .filter(|id| passengers[*id].seated_tick.is_some())
+            .max_by_key(|id| passengers[*id].seated_tick)
+    }
+
+    /// Traces the chain of passengers that held up the passenger `last`, from
+    /// the first in the chain to `last` itself.
+    ///
+    /// Each passenger in the chain is the one that most recently blocked the
+    /// next from moving.
+    fn critical_path(&self, last: Option<usize>) -> Vec<String> {
+        let passengers = &self.stats.passengers;
+        let mut path = Vec::<usize>::new();
+        let mut current = last;
+        while let Some(id) = current {
+            if path.contains(&id) {
+                break;
+            }
+            path.push(id);
+            current = passengers[id].blocked_by;
+        }
+
+        path.iter().rev().map(|id| passengers[*id].name.clone()).collect()
+    }
+
+    /// Returns a copy of the statistics of a boarding that didn't complete,
+    /// with the critical path traced back from the first passenger still
+    /// unseated.
+    fn unfinished_stats(&self) -> Box<BoardingStats> {
+        let mut stats = self.stats.clone();
+        stats.critical_path = self.critical_path(
+            self.unseated().first().copied());
+        Box::new(stats)
+    }
+
+    /// Takes a copy of the aircraft as it stands, with every passenger where
+    /// they are and the statistics gathered so far, to be carried on from
+    /// later with `resume`. The move filter and last boarded hook, if any,
+    /// aren't copied.
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot { aircraft: self.copy_state() }
+    }
+
+    /// Replaces the aircraft's state with that of a snapshot, so that boarding
+    /// carries on from where the snapshot was taken. The aircraft keeps its own
+    /// move filter and last boarded hook.
+    ///
+    /// Returns an error, leaving the aircraft unchanged, if the snapshot is of
+    /// a different size of aircraft, a passenger is missing from it or in two
+    /// places, or its passengers don't agree with the seats targeted and the
+    /// number seated.
+    pub fn resume(&mut self, snapshot: &Snapshot) -> Result<(), &'static str> {
+        if snapshot.aircraft.size != self.size {
+            return Err("Snapshot is of a different size of aircraft");
+        }
+        snapshot.aircraft.check_state()?;
+
+        let filter = self.move_filter.take();
+        let hook = self.on_last_boarded.take();
+        *self = snapshot.aircraft.copy_state();
+        self.move_filter = filter;
+        self.on_last_boarded = hook;
+        Ok(())
+    }
+
+    /// Copies every part of the aircraft but its move filter and last boarded
+    /// hook.
+    fn copy_state(&self) -> Aircraft {
+        Aircraft {
+            size: self.size,
+            layout: self.layout.clone(),
+            passengers: self.passengers.clone(),
+            targeted_seats: self.targeted_seats.clone(),
+            stats: self.stats.clone(),
+            boarding_order: self.boarding_order.clone(),
+            boarding_cursor: self.boarding_cursor,
+            yield_policy: self.yield_policy,
+            forbid_backtracking: self.forbid_backtracking,
+            tracing: self.tracing,
+            decision_trace: self.decision_trace.clone(),
+            legroom: self.legroom.clone(),
+            bin_capacity: self.bin_capacity,
+            bins: self.bins.clone(),
+            bin_search_range: self.bin_search_range,
+            doors: self.doors.clone(),
+            jet_bridge: self.jet_bridge.clone(),
+            row_boarded: self.row_boarded.clone(),
+            logging: self.logging,
+            events: self.events.clone(),
+            transits: self.transits.clone(),
+            seated: self.seated,
+            panic_on_timeout: self.panic_on_timeout,
+            beam_boarding: self.beam_boarding,
+            peak_occupancy: self.peak_occupancy.clone(),
+            move_filter: None,
+            glyphs: self.glyphs.clone(),
+            seat_after_stow: self.seat_after_stow,
+            on_last_boarded: None,
+            seat_deadline: self.seat_deadline,
+            attendant_interval: self.attendant_interval,
+            vestibule_pooling: self.vestibule_pooling,
+            boxed_in: self.boxed_in.clone(),
+        }
+    }
+
+    /// Checks that the passengers onboard and waiting agree with the seats
+    /// targeted and the count of those seated, as they always should.
+    fn check_state(&self) -> Result<(), &'static str> {
+        let mut assigned: Vec<(u16, u16)> = self.stats.passengers.iter()
+            .filter_map(|p| p.seat)
+            .collect();
+        let mut targeted = self.targeted_seats.clone();
+        assigned.sort_unstable();
+        targeted.sort_unstable();
+        if assigned != targeted {
+            return Err("Targeted seats don't match the passengers' seats");
+        }
+
+        let mut found = vec![false; self.stats.passengers.len()];
+        let onboard = self.layout.iter().flatten()
+            .flat_map(|tile| tile.get_occupier().into_iter()
+                      .chain(tile.get_passer()));
+        let bridge = self.jet_bridge.iter().flatten();
+        for person in onboard.chain(bridge).chain(self.passengers.iter()) {
+            if let Some(id) = person.get_id() {
+                if id >= found.len() || found[id] {
+                    return Err("Passenger is in two places");
+                }
+                found[id] = true;
+            }
+        }
+        // Transit actors have no ids, so those that have left aren't missed.
+        if found.contains(&false) {
+            return Err("Passenger is missing");
+        }
+
+        let seated = self.stats.passengers.iter()
+            .filter(|p| p.seated_tick.is_some())
+            .count();
+        if seated != self.seated {
+            return Err("Seated count doesn't match the passengers seated");
+        }
+        Ok(())
+    }
+
+    /// Updates an aircraft up to `ticks` times, stopping early if all
+    /// passengers are seated, and returns whether they are.
+    pub fn run_for(&mut self, ticks: u16) -> bool {
+        for _ in 0..ticks {
+            if self.is_complete() {
+                break;
+            }
+            self.update();
+        }
+        self.is_complete()
+    }
+
+    /// Repeatedly updates an aircraft until either all passengers are seated or
+    /// an arbitrary limit (`MAX_ITERATIONS`) is reached, returning the
+    /// statistics of the boarding, whose `ticks` is the total number of
+    /// updates. This stops on the very update the last passenger reaches its
+    /// seat.
+    ///
+    /// Updates already made to the aircraft (e.g. by `run_for`) count towards
+    /// both the total and the limit, so a run can be resumed part way through.
+    ///
+    /// Primarily intended for use when running in parallel; this method does
+    /// not allow any monitoring of the aircraft while it is running and is
+    /// therefore unsuitable for use in interactive mode.
+    pub fn run_to_completion(&mut self) -> Result<BoardingStats, RunError> {
+        while !self.all_seated() && self.stats.ticks < MAX_ITERATIONS {
+            self.update();
+        }
+        if self.all_seated() {
+            return Ok(self.stats.clone());
+        } else if self.panic_on_timeout {
+            let unseated: Vec<String> = self.unseated().iter()
+                .map(|&id| format!("{} ({})", id,
+                                   self.stats.passengers[id].name))
+                .collect();
+            panic!("Passengers still unseated after {} updates: {}",
+                   MAX_ITERATIONS, unseated.join(", "));
+        } else {
+            return Err(RunError::Timeout {
+                stats: self.unfinished_stats(),
+                unseated: self.unseated(),
+            });
+        }
+    }
+
+    /// Sets whether `run_to_completion` panics, naming the passengers still
+    /// unseated, instead of returning `RunError::Timeout` when it reaches
+    /// `MAX_ITERATIONS`.
+    ///
+    /// Intended for tests, where a scenario that never completes should fail
+    /// loudly. Defaults to false.
+    pub fn set_panic_on_timeout(&mut self, panic: bool) {
+        self.panic_on_timeout = panic;
+    }
+
+    /// Updates an aircraft like `run_to_completion`, but gives up early if no
+    /// passenger has reached its seat in the last `stall_ticks` updates,
+    /// returning the passengers still unseated and where they are.
+    pub fn run_until_stalled(&mut self, stall_ticks: u16)
+        -> Result<BoardingStats, RunError> {
+        let mut last_progress = self.stats.ticks;
+        let mut seated = self.seated;
+        while !self.all_seated() && self.stats.ticks < MAX_ITERATIONS {
+            self.update();
+            if self.seated > seated {
+                seated = self.seated;
+                last_progress = self.stats.ticks;
+            } else if self.stats.ticks - last_progress >= stall_ticks {
+                let positions = self.passenger_positions();
+                return Err(RunError::Stalled {
+                    stats: self.unfinished_stats(),
+                    stalled: self.unseated().into_iter()
+                        .map(|id| (id, positions[id]))
+                        .collect(),
+                });
+            }
+        }
+        self.run_to_completion()
+    }
+
+    /// Finds where each passenger is onboard, indexed by id.
+    fn passenger_positions(&self) -> Vec<Option<(u16, u16)>> {
+        let mut positions = vec![None; self.stats.passengers.len()];
+        for (x, column) in self.layout.iter().enumerate() {
+            for (y, tile) in column.iter().enumerate() {
+                for person in tile.get_occupier().iter()
+                    .chain(tile.get_passer().iter()) {
+                    if let Some(id) = person.get_id() {
+                        positions[id] = Some((x as u16, y as u16));
+                    }
+                }
+            }
+        }
+        positions
+    }
+
+    /// Lists the ids of the passengers that aren't yet in their seats, whether
+    /// still waiting to board or somewhere onboard.
+    pub fn unseated(&self) -> Vec<usize> {
+        let mut seated = vec![false; self.stats.passengers.len()];
+        for (x, y) in &self.targeted_seats {
+            let occupier = self.layout[*x as usize][*y as usize]
+                .get_occupier();
+            if let Some(p) = occupier {
+                if let (Some(id), Some(seat)) = (p.get_id(), p.get_seat()) {
+                    if seat == (*x, *y) {
+                        seated[id] = true;
+                    }
+                }
+            }
+        }
+        (0..seated.len()).filter(|id| !seated[*id]).collect()
+    }
+    
+    /// Checks whether every passenger with a seat has reached it, as counted
+    /// on each update.
+    ///
+    /// Unlike `is_complete`, this isn't fooled by a targeted seat being taken
+    /// by a passenger on its way past to another seat.
+    fn all_seated(&self) -> bool {
+        self.seated >= self.targeted_seats.len()
+    }
+
+    /// Lists every passenger sitting in a seat that isn't its own, or that has
+    /// settled anywhere other than its own seat.
+    ///
+    /// Passengers pass through other seats on the way to their own, so this is
+    /// meant to be called once boarding is complete, when anything it finds is
+    /// a bug in the movement rules.
+    pub fn audit_seating(&self) -> Vec<SeatMismatch> {
+        let mut mismatches = Vec::<SeatMismatch>::new();
+        for (x, column) in self.layout.iter().enumerate() {
+            for (y, tile) in column.iter().enumerate() {
+                let position = (x as u16, y as u16);
+                for person in tile.get_occupier().iter()
+                    .chain(tile.get_passer().iter()) {
+                    let seated = tile.get_variant() == Variant::Seat
+                        || person.is_settled();
+                    if seated && person.get_seat() != Some(position) {
+                        mismatches.push(SeatMismatch {
+                            id: person.get_id(),
+                            name: person.get_name(),
+                            expected: person.get_seat(),
+                            actual: position,
+                        });
+                    }
+                }
+            }
+        }
+        mismatches
+    }
+
+    /// Works out why boarding hasn't completed, checking for causes in the
+    /// order they appear in `IncompleteReason`.
+    ///
+    /// Meant to be called once `run_to_completion` or `run_until_stalled` has
+    /// given up, with the passengers where it left them.
+    pub fn diagnose_incomplete(&self) -> IncompleteReason {
+        let unseated: Vec<usize> = self.unseated().into_iter()
+            .filter(|&id| self.stats.passengers[id].seat.is_some())
+            .collect();
+        if unseated.is_empty() {
+            return IncompleteReason::Complete;
+        }
+
+        let mut assigned = Vec::<((u16, u16), Vec<usize>)>::new();
+        for (id, passenger) in self.stats.passengers.iter().enumerate() {
+            if let Some(seat) = passenger.seat {
+                match assigned.iter_mut().find(|(s, _)| *s == seat) {
+                    Some((_, ids)) => ids.push(id),
+                    None => assigned.push((seat, vec![id])),
+                }
+            }
+        }
+        assigned.retain(|(_, ids)| ids.len() > 1);
+        if !assigned.is_empty() {
+            return IncompleteReason::SeatConflict(assigned);
+        }
+
+        let reached = self.entrance_reach();
+        let unreachable: Vec<usize> = unseated.iter().copied()
+            .filter(|&id| !self.seat_reachable(
+                &reached, self.stats.passengers[id].seat.unwrap()))
+            .collect();
+        if !unreachable.is_empty() {
+            return IncompleteReason::UnreachableSeats(unreachable);
+        }
+
+        let positions = self.passenger_positions();
+        let onboard: Vec<usize> = unseated.iter().copied()
+            .filter(|&id| positions[id].is_some())
+            .collect();
+        let stuck = onboard.iter().all(|&id| {
+            let seat = self.stats.passengers[id].seat.unwrap();
+            self.boxed_in.contains(&id)
+                || !self.reachable_past(positions[id].unwrap(), seat, true)
+        });
+        if !onboard.is_empty() && stuck {
+            return IncompleteReason::Deadlock(onboard);
+        }
+        IncompleteReason::Stalled(unseated)
+    }
+
+    /// Finds which tiles could be reached along the aisle and entrance tiles
+    /// from any entrance on an empty aircraft, for `seat_reachable`.
+    fn entrance_reach(&self) -> Vec<Vec<bool>> {
+        let mut reached =
+            vec![vec![false; self.size.1 as usize]; self.size.0 as usize];
+        for x in 0..self.size.0 {
+            for y in 0..self.size.1 {
+                if self.get_tile_variant(x, y) != Variant::Entrance {
+                    continue;
+                }
+                for (i, column) in self.aisle_distances((x, y)).iter()
+                    .enumerate() {
+                    for (j, distance) in column.iter().enumerate() {
+                        reached[i][j] |= distance.is_some();
+                    }
+                }
+            }
+        }
+        reached
+    }
+
+    /// Checks whether a seat could be reached from any entrance on an empty
+    /// aircraft, along its row from an aisle or from its access tile, given
+    /// the tiles `reached` as found by `entrance_reach`.
+    fn seat_reachable(&self, reached: &[Vec<bool>], seat: (u16, u16)) -> bool {
+        let (x, y) = seat;
+        if reached[x as usize][y as usize] {
+            return true;
+        }
+        for direction in &[-1, 1] {
+            let mut i = x as i32 + direction;
+            while i >= 0 && i < self.size.0 as i32 {
+                match self.get_tile_variant(i as u16, y) {
+                    Variant::Seat => i += direction,
+                    Variant::Aisle | Variant::Entrance => {
+                        if reached[i as usize][y as usize] {
+                            return true;
+                        }
+                        break;
+                    },
+                    Variant::None => break,
+                }
+            }
+        }
+        matches!(self.access_tile(x, y),
+                 Some((i, j)) if reached[i as usize][j as usize])
+    }
+
+    /// Checks whether all passengers are seated in their desired seats.
+    pub fn is_complete(&self) -> bool {
+        let mut complete: bool = true;
+        for targeted_seat in &self.targeted_seats {
+            let (x, y) = targeted_seat;
+            if !self.layout[*x as usize][*y as usize].is_occupied() {
+                complete = false;
+            }
+        }
+        return complete;
+    }
+
+    /// Sets all tiles as not being updated.
+    ///
+    /// This is called at the end of `update()` to ensure no incorrect
+    /// behaviours are observed on the next iteration.
+    fn reset(&mut self) {
+        for i in 0..self.size.0 as usize {
+            for j in 0..self.size.1 as usize {
+                self.layout[i][j].set_updated(false);
+            }
+        }
+    }
+
+    /// Sets the `variant` of a tile.
+    ///
+    /// Changes the `variant` member of a tile to another `Variant` enum, which
+    /// affects the way passengers will move when on that tile.
+    pub fn set_tile(&mut self, x: u16, y: u16, var: Variant) {
+        self.layout[x as usize][y as usize] = match var {
+            Variant::Aisle => Tile::aisle(),
+            Variant::Seat => Tile::seat(),
+            Variant::Entrance => Tile::entrance(),
+            Variant::None => Tile::none(),
+        };
+    }
+
+    /// Sets how much legroom a row has relative to a normal row.
+    ///
+    /// Passing a seated passenger takes `PASS_WAIT` additional updates on a
+    /// normal row (a factor of 1.0); this is divided by the row's factor, so
+    /// e.g. an exit row with a factor of 2.0 is passed twice as quickly.
+    pub fn set_row_legroom(&mut self, row: u16, factor: f32)
+        -> Result<(), &'static str> {
+        if row >= self.size.1 {
+            return Err("Row is outside the aircraft");
+        }
+        if factor.is_nan() || factor <= 0.0 {
+            return Err("Legroom factor must be positive");
+        }
+
+        self.legroom[row as usize] = factor;
+        Ok(())
+    }
+
+    /// Finds the number of additional updates taken to pass a passenger on the
+    /// given row.
+    fn pass_wait(&self, row: usize) -> u8 {
+        let wait = (PASS_WAIT as f32 / self.legroom[row]).round();
+        if wait > u8::MAX as f32 {
+            u8::MAX
+        } else {
+            wait as u8
+        }
+    }
+
+    pub fn get_size(&self) -> (u16, u16) {
+        (self.size.0, self.size.1)
+    }
+
+    /// Lists the coordinates of every seat, ordered by the given criteria.
+    ///
+    /// Seats that tie are listed front to back, then left to right.
+    pub fn seats_sorted_by(&self, criteria: SeatSort) -> Vec<(u16,u16)> {
+        let mut seats = Vec::<(u16,u16)>::new();
+        for y in 0..self.size.1 {
+            for x in 0..self.size.0 {
+                if self.get_tile_variant(x, y) == Variant::Seat {
+                    seats.push((x, y));
+                }
+            }
+        }
+
+        match criteria {
+            SeatSort::BackToFront => seats.sort_by_key(|s| Reverse(s.1)),
+            SeatSort::FrontToBack => seats.sort_by_key(|s| s.1),
+            SeatSort::WindowToAisle => seats.sort_by_key(|s| {
+                Reverse(self.aisle_distance(s.0, s.1))
+            }),
+            SeatSort::AisleToWindow => seats.sort_by_key(|s| {
+                self.aisle_distance(s.0, s.1)
+            }),
+        }
+
+        return seats;
+    }
+
+    /// Counts the seats approached via each aisle column, listed left to right.
+    ///
+    /// A seat is approached via the aisle or entrance nearest to it on its row;
+    /// seats on rows with no aisle at all aren't counted.
+    pub fn aisle_load(&self) -> Vec<(u16, usize)> {
+        let mut load = Vec::<(u16, usize)>::new();
+        for (x, y) in self.seats_sorted_by(SeatSort::FrontToBack) {
+            if let Some(aisle) = self.nearest_aisle(x, y) {
+                match load.iter_mut().find(|(column, _)| *column == aisle) {
+                    Some((_, count)) => *count += 1,
+                    None => load.push((aisle, 1)),
+                }
+            }
+        }
+        load.sort_by_key(|(column, _)| *column);
+        load
+    }
+
+    /// Finds how many tiles a position is from the nearest aisle or entrance on
+    /// the same row, or `u16::MAX` if the row has no aisle.
+    pub(crate) fn aisle_distance(&self, x: u16, y: u16) -> u16 {
+        match self.nearest_aisle(x, y) {
+            Some(i) => (i as i32 - x as i32).unsigned_abs() as u16,
+            None => u16::MAX,
+        }
+    }
+
+    /// Finds the tile a seat must be entered from, if it can't be stepped into
+    /// from an aisle along its row.
+    ///
+    /// This is the first aisle or entrance tile found directly north, south,
+    /// east, or west of the seat, or `None` if the seat can be entered along
+    /// its row as normal (or can't be entered at all).
+    fn access_tile(&self, x: u16, y: u16) -> Option<(u16, u16)> {
+        for direction in &[-1, 1] {
+            let mut i = x as i32 + direction;
+            while i >= 0 && i < self.size.0 as i32 {
+                match self.get_tile_variant(i as u16, y) {
+                    Variant::Seat => i += direction,
+                    Variant::Aisle | Variant::Entrance => return None,
+                    Variant::None => break,
+                }
+            }
+        }
+
+        for (dx, dy) in &[(0, -1), (0, 1), (1, 0), (-1, 0)] {
+            let (i, j) = (x as i32 + dx, y as i32 + dy);
+            if i < 0 || j < 0 || i >= self.size.0 as i32
+            || j >= self.size.1 as i32 {
+                continue;
+            }
+            match self.get_tile_variant(i as u16, j as u16) {
+                Variant::Aisle | Variant::Entrance => {
+                    return Some((i as u16, j as u16));
+                },
+                _ => (),
+            }
+        }
+        None
+    }
+
+    /// Checks whether a passenger at `from` could make its way to `to` past
+    /// the passengers onboard as they stand right now.
+    ///
+    /// Aisle and entrance tiles can only be walked through while empty, and
+    /// seat tiles while nobody is already squeezing past their occupier. As in
+    /// the simulation, seats are moved into and out of along their row, unless
+    /// `to` is a seat entered from an access tile. Unlike the layout itself,
+    /// this changes from update to update as passengers move.
+    pub fn is_reachable_now(&self, from: (u16, u16), to: (u16, u16)) -> bool {
+        self.reachable_past(from, to, false)
+    }
+
+    /// As `is_reachable_now`, but if `yielding` is set, an aisle tile can also
+    /// be walked through when its occupier would make way for a passenger
+    /// headed for `to` under the aircraft's `YieldPolicy`.
+    fn reachable_past(&self,
+                      from: (u16, u16),
+                      to: (u16, u16),
+                      yielding: bool) -> bool {
+        let access = self.access_tile(to.0, to.1)
+            .map(|(x, y)| (x as usize, y as usize));
+        let target = (to.0 as usize, to.1 as usize);
+        let start = (from.0 as usize, from.1 as usize);
+        let reached = grid::bfs_grid(&self.layout, start, |(x, y), (i, j)| {
+            let on_seat = self.layout[x][y].get_variant() == Variant::Seat;
+            let tile = &self.layout[i][j];
+            let along_row = y == j;
+            match tile.get_variant() {
+                Variant::Aisle | Variant::Entrance => {
+                    let yields = yielding && !tile.is_allowing()
+                        && tile.get_variant() == Variant::Aisle
+                        && self.yields_to(i, j, to);
+                    (!tile.is_occupied() || yields)
+                        && (along_row || !on_seat)
+                },
+                Variant::Seat => !tile.is_allowing()
+                    && (along_row
+                        || ((i, j) == target && access == Some((x, y)))),
+                Variant::None => false,
+            }
+        });
+        reached[target.0][target.1].is_some()
+    }
+
+    /// Finds how many steps along aisle and entrance tiles each tile is from
+    /// the given tile, or `None` for tiles that can't be reached that way.
+    fn aisle_distances(&self, from: (u16, u16)) -> Vec<Vec<Option<u32>>> {
+        let start = (from.0 as usize, from.1 as usize);
+        grid::bfs_grid(&self.layout, start, |_, (x, y)| {
+            matches!(self.layout[x][y].get_variant(),
+                     Variant::Aisle | Variant::Entrance)
+        })
+    }
+
+    /// Finds the column of the aisle or entrance nearest to a position on the
+    /// same row, preferring the leftmost if two are equally near.
+    fn nearest_aisle(&self, x: u16, y: u16) -> Option<u16> {
+        let mut nearest: Option<u16> = None;
+        for i in 0..self.size.0 {
+            match self.get_tile_variant(i, y) {
+                Variant::Aisle | Variant::Entrance => {
+                    let d = (i as i32 - x as i32).abs();
+                    if nearest.is_none()
+                    || d < (nearest.unwrap() as i32 - x as i32).abs() {
+                        nearest = Some(i);
+                    }
+                },
+                _ => (),
+            }
+        }
+        return nearest;
+    }
+
+    pub fn get_stats(&self) -> &BoardingStats {
+        &self.stats
+    }
+
+    /// Hashes which passenger is on every tile, and which is passing through
+    /// it, so that the states of two aircraft can be compared cheaply.
+    ///
+    /// The hash is the same from one run of the program to the next.
+    pub fn fingerprint(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        for column in &self.layout {
+            for tile in column {
+                tile.get_occupier().map(|p| p.get_id()).hash(&mut hasher);
+                tile.get_passer().map(|p| p.get_id()).hash(&mut hasher);
+            }
+        }
+        hasher.finish()
+    }
+
+    /// Takes a snapshot of the tiles surrounding the tile at `x`,`y`.
+    pub fn neighbourhood(&self, x: u16, y: u16) -> Neighbourhood {
+        let mut cells = [None; 9];
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                let (i, j) = (x as i32 + dx, y as i32 + dy);
+                if i >= 0 && j >= 0
+                && i < self.size.0 as i32 && j < self.size.1 as i32 {
+                    cells[((dy + 1) * 3 + dx + 1) as usize] = Some(
+                        SimpleTile::new(&self.layout[i as usize][j as usize]));
+                }
+            }
+        }
+        Neighbourhood::new(cells)
+    }
+
+    /// Returns the most passengers each tile has held at the end of any update
+    /// so far, indexed `[x][y]`.
+    ///
+    /// Peaks are only sampled at the end of each update, so someone moving
+    /// through a tile part way through one isn't counted. No tile can hold
+    /// more than its `Tile::capacity`, as `Tile::occupy` and `Tile::pass_in`
+    /// refuse to displace anyone in debug builds.
+    pub fn peak_occupancy(&self) -> Vec<Vec<u8>> {
+        self.peak_occupancy.clone()
+    }
+
+    /// Returns the update on which each seat was filled by the passenger
+    /// assigned it, indexed `[x][y]`, or `None` for seats not yet filled and
+    /// tiles that aren't seats.
+    pub fn seat_fill_ticks(&self) -> Vec<Vec<Option<u16>>> {
+        let mut ticks = vec![vec![None; self.size.1 as usize];
+                             self.size.0 as usize];
+        for passenger in &self.stats.passengers {
+            if let (Some((x, y)), Some(tick)) = (passenger.seat,
+                                                 passenger.seated_tick) {
+                ticks[x as usize][y as usize] = Some(tick);
+            }
+        }
+        ticks
+    }
+
+    /// Returns how many updates later each seat was filled than it would have
+    /// been had nobody been in its passenger's way, indexed `[x][y]`, e.g. to
+    /// overlay on the layout to show where congestion held boarding up.
+    ///
+    /// Seats not yet filled and tiles that aren't seats are 0.
+    pub fn delay_heatmap(&self) -> Vec<Vec<i32>> {
+        let fill_ticks = self.seat_fill_ticks();
+        let mut delays = vec![vec![0; self.size.1 as usize];
+                              self.size.0 as usize];
+        for passenger in &self.stats.passengers {
+            if let (Some((x, y)), Some(ideal)) = (passenger.seat,
+                                                  passenger.ideal_seated_tick) {
+                let (x, y) = (x as usize, y as usize);
+                if let Some(tick) = fill_ticks[x][y] {
+                    delays[x][y] = tick as i32 - ideal as i32;
+                }
+            }
+        }
+        delays
+    }
+
+    /// Returns the aircraft's tiles, indexed `[x][y]`, e.g. for searching
+    /// with `grid::bfs`.
+    pub fn get_layout(&self) -> &[Vec<Tile>] {
+        &self.layout
+    }
+
+    pub fn get_tile_variant(&self, x: u16, y: u16) -> Variant {
+        self.layout[x as usize][y as usize].get_variant()
+    }
+
+    pub fn check_if_occupied(&self, x: u16, y: u16) -> bool {
+        self.layout[x as usize][y as usize].is_occupied()
+    }
+
+    pub fn check_if_allowing(&self, x: u16, y: u16) -> bool {
+        self.layout[x as usize][y as usize].is_allowing()
+    }
+}
+
+/// Updates two aircraft once each, in lockstep, and draws them side by side
+/// for comparison.
+///
+/// Each is drawn as by `Aircraft::render_ascii`, beneath a line giving its
+/// update count and how many of its targeted seats are filled. An aircraft
+/// that is already complete isn't updated again, so the one that finishes
+/// first can be seen waiting for the other.
+pub fn step_side_by_side(left: &mut Aircraft, right: &mut Aircraft) -> String {
+    let mut frames = Vec::<Vec<String>>::new();
+    for aircraft in [left, right].iter_mut() {
+        if !aircraft.is_complete() {
+            aircraft.step();
+        }
+        let mut lines = vec![format!("tick {} seated {}/{}",
+                                     aircraft.stats.ticks,
+                                     aircraft.seated_count(),
+                                     aircraft.targeted_seats.len())];
+        lines.extend(aircraft.render_ascii().lines().map(String::from));
+        frames.push(lines);
+    }
+
+    let width = frames[0].iter().map(|l| l.len()).max().unwrap_or(0);
+    let rows = frames[0].len().max(frames[1].len());
+    let mut combined = String::new();
+    for row in 0..rows {
+        let left = frames[0].get(row).map_or("", |l| l.as_str());
+        let right = frames[1].get(row).map_or("", |l| l.as_str());
+        combined.push_str(&format!("{:<width$}    {}\n", left, right,
+                                   width = width));
+    }
+    combined
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clear() {
+        let mut aircraft = Aircraft {
+            size: (5, 5),
+            layout: Vec::<Vec<Tile>>::new(),
+            passengers: Vec::<Person>::new(),
+            targeted_seats: Vec::<(u16,u16)>::new(),
+            stats: BoardingStats::new(),
+            boarding_order: BoardingOrder::Reversed,
+            boarding_cursor: 0,
+            yield_policy: YieldPolicy::None,
+            forbid_backtracking: false,
+            tracing: false,
+            decision_trace: Vec::<TickDecisions>::new(),
+            legroom: vec![1.0; 5],
+            bin_capacity: None,
+            bins: vec![0; 5],
+            bin_search_range: 0,
+            doors: Vec::<DoorSchedule>::new(),
+            jet_bridge: Vec::<Option<Person>>::new(),
+            row_boarded: vec![None; 5],
+            logging: false,
+            events: Vec::<SimEvent>::new(),
+            transits: Vec::<((u16, u16), Person)>::new(),
+            seated: 0,
+            panic_on_timeout: false,
+            beam_boarding: false,
+            peak_occupancy: vec![vec![0; 5]; 5],
+            move_filter: None,
+            glyphs: GlyphSet::default(),
+            seat_after_stow: false,
+            on_last_boarded: None,
+            seat_deadline: None,
+            attendant_interval: None,
+            vestibule_pooling: false,
+            boxed_in: Vec::<usize>::new(),
+        };
+
+        aircraft.clear();
+        for i in 0..aircraft.size.0 {
+            for j in 0..aircraft.size.1 {
+                assert_eq!(aircraft.layout[i as usize][j as usize]
+                               .get_variant(),
+                           Variant::Aisle);
+            }
+        }
+    }
+
+    #[test]
+    fn constructor() {
+        let aircraft = Aircraft::new(10, 10);
+        assert_eq!(aircraft.layout.len(), 10);
+        assert_eq!(aircraft.size, (10,10));
+        for i in 0..aircraft.size.0 {
+            for j in 0..aircraft.size.1 {
+                println!("Testing aircraft[{}][{}]", i, j);
+                assert_eq!(aircraft.layout[i as usize][j as usize]
+                               .get_variant(),
+                           Variant::Aisle);
+            }
+        }
+    }
+
+    #[test]
+    fn add_passenger() {
+        println!("Testing add_passenger()");
+        let mut aircraft = Aircraft::new(6, 9);
+        let passenger = Person::new("Dave");
+        aircraft.add_passenger(passenger);
+        assert_eq!(aircraft.passengers.get(0).unwrap().get_name(), "Dave");
+        assert_eq!(aircraft.passengers.get(0).unwrap().get_seat(), None);
+    }
+
+    #[test]
+    fn get_size() {
+        let aircraft = Aircraft::new(5, 6);
+        assert_eq!(aircraft.get_size(), (5, 6));
+    }
+
+    #[test]
+    fn update() {
+        let mut aircraft = Aircraft::new(10, 10);
+        let mut passenger = Person::new("Dave");
+        passenger.target_seat(1,1);
+
+        println!("Adding passenger");
+        aircraft.add_passenger(passenger);
+        assert_eq!(aircraft.passengers.len(), 1,
+                   "Unwanted passenger at initialisation");
+
+        println!("Adding entrance and updating");
+        aircraft.layout[2][2] = Tile::entrance();
+        // aircraft.layout[2][3] = Tile::aisle();
+        aircraft.ascii_render();
+        aircraft.update();
+        aircraft.ascii_render();
+        assert_eq!(aircraft.passengers.len(), 0,
+                   "Passenger was not removed from passengers array");
+        assert!(aircraft.layout[2][2].is_occupied(),
+                "Passenger was not added to entrance tile");
+
+        println!("Updating");
+        aircraft.update();
+        aircraft.ascii_render();
+        println!("Updating");
+        aircraft.update();
+        aircraft.ascii_render();
+        aircraft.update();
+        aircraft.ascii_render();
+        aircraft.update();
+        aircraft.ascii_render();
+        assert_eq!(aircraft.layout[2][2].is_occupied(), false,
+                   "Passenger did not move from entrance");
+        assert!(aircraft.layout[1][1].is_occupied(),
+                "Passenger did not make it to target seat");
+
+        println!("Updating once too much");
+        aircraft.update();
+        aircraft.ascii_render();
+        assert!(aircraft.layout[1][1].is_occupied(),
+                "Passenger shouldn't have moved from their seat");
+    }
+
+    #[test]
+    fn impassable_terrain() {
+        let mut aircraft = Aircraft::new(3,3);
+        let mut passenger = Person::new("Dave");
+        passenger.target_seat(2,2);
+        aircraft.layout[0][0] = Tile::entrance();
+        for coords in &[(0, 1), (1, 1), (1, 0)] {
+            aircraft.layout[coords.0][coords.1] = Tile::none();
+        }
+        aircraft.add_passenger(passenger);
+
+        for i in 0..100 {
+            aircraft.update();
+        }
+
+        assert_eq!(aircraft.layout[2][2].is_occupied(), false,
+                   "Passenger made it to seat despite obstacles");
+    }
+
+    /// An aircraft in which only the passenger boarding first can be seated.
+    fn infeasible_aircraft() -> Aircraft {
+        let mut aircraft = Aircraft::new(3,3);
+        aircraft.layout[0][0] = Tile::entrance();
+        for coords in &[(0, 1), (1, 1), (1, 0)] {
+            aircraft.layout[coords.0][coords.1] = Tile::none();
+        }
+        // The passenger boarding first has a seat at the door, so is the only
+        // one that can board.
+        for seat in &[(2, 2), (0, 0)] {
+            let mut passenger = Person::new("Dave");
+            passenger.target_seat(seat.0, seat.1);
+            aircraft.add_passenger(passenger);
+        }
+        aircraft
+    }
+
+    /// A cabin `len` rows long with a pair of seats, (0, 0) and (1, 0), at the
+    /// back of an aisle down column 2, and the door at the front of it.
+    fn seat_pair_cabin(len: u16) -> Aircraft {
+        let mut aircraft = Aircraft::new(3, len);
+        let front = len as usize - 1;
+        for x in 0..2 {
+            aircraft.layout[x][0] = Tile::seat();
+            aircraft.layout[x][front] = Tile::none();
+        }
+        aircraft.layout[2][front] = Tile::entrance();
+        aircraft
+    }
+
+    /// A cabin `len` rows long with an aisle down column 1 between seats on
+    /// either side, and the door at the front of the aisle.
+    fn single_aisle_cabin(len: u16) -> Aircraft {
+        let mut aircraft = Aircraft::new(3, len);
+        let front = len as usize - 1;
+        for y in 0..front {
+            aircraft.layout[0][y] = Tile::seat();
+            aircraft.layout[2][y] = Tile::seat();
+        }
+        aircraft.layout[0][front] = Tile::none();
+        aircraft.layout[1][front] = Tile::entrance();
+        aircraft.layout[2][front] = Tile::none();
+        aircraft
+    }
+
+    #[test]
+    fn timeout() {
+        let mut aircraft = infeasible_aircraft();
+
+        match aircraft.run_to_completion() {
+            Err(RunError::Timeout { stats, unseated }) => {
+                assert_eq!(stats.ticks, MAX_ITERATIONS);
+                assert_eq!(stats.boarded, 1);
+                assert_eq!(unseated, vec![0]);
+            },
+            Err(e) => panic!("Unexpected error: {}", e),
+            Ok(_) => panic!("Passenger made it to seat despite obstacles"),
+        }
+    }
+
+    #[test]
+    fn aisle_ignoring() {
+        let mut aircraft = Aircraft::new(5,5);
+        let mut passenger = Person::new("Dave");
+        passenger.target_seat(4, 0);
+        aircraft.layout[2][4] = Tile::entrance();
+        for i in 0..5 {
+            aircraft.layout[0][i] = Tile::seat();
+            aircraft.layout[1][i] = Tile::seat();
+            aircraft.layout[3][i] = Tile::seat();
+            aircraft.layout[4][i] = Tile::seat();
+        }
+        aircraft.add_passenger(passenger);
+
+        for _ in 0..10 {
+            aircraft.ascii_render();
+            println!("========================");
+            aircraft.update();
+        }
+        assert!(aircraft.layout[4][0].is_occupied(),
+                "Passenger did not make it to seat");
+    }
+
+    #[test]
+    fn advanced_aisle_ignoring() {
+        let mut aircraft = Aircraft::new(3,3);
+        aircraft.layout[1][2] = Tile::entrance();
+        for i in 0..3 {
+            for j in &[0, 2] {
+                let mut passenger = Person::new("DEFAULT");
+                passenger.target_seat(*j, i);
+                aircraft.layout[*j as usize][i as usize] = Tile::seat();
+                aircraft.add_passenger(passenger);
+            }
+        }
+        // let mut passenger = Person::new("DAVE");
+        // passenger.target_seat(2, 0);
+        // aircraft.add_passenger(passenger);
+        
+        for _ in 0..10 {
+            aircraft.ascii_render();
+            println!("========================");
+            aircraft.update();
+        }
+
+        for i in 0..3 {
+            for j in &[0, 2] {
+                assert!(aircraft.layout[*j][i].is_occupied(),
+                        "Seat {},{} was not occupied", *j, i);
+            }
+        }
+    }
+
+    #[test]
+    fn aisle_shifting() {
+        let mut aircraft = Aircraft::new(5,5);
+
+        aircraft.layout[2][4] = Tile::entrance();
+        for i in 0..5 {
+            aircraft.layout[0][i] = Tile::seat();
+            aircraft.layout[1][i] = Tile::seat();
+            aircraft.layout[3][i] = Tile::seat();
+            aircraft.layout[4][i] = Tile::seat();
+        }
+
+        let mut passenger = Person::new("DEFAULT");
+        passenger.target_seat(0,0);
+        aircraft.add_passenger(passenger);
+
+        let mut passenger = Person::new("DEFAULT");
+        passenger.target_seat(1,0);
+        aircraft.add_passenger(passenger);
+
+        for _ in 0..10 {
+            println!("========================");
+            aircraft.update();
+            aircraft.ascii_render();
+        }
+
+        assert!(aircraft.layout[0][0].is_occupied());
+        assert!(aircraft.layout[1][0].is_occupied());
+    }
+
+    #[test]
+    fn bad_order() {
+        let mut aircraft = Aircraft::new(5,5);
+        
+        for i in 0..5 {
+            for j in &[0,1,3,4] {
+                aircraft.layout[*j][i] = Tile::seat();
+            }
+        }
+        aircraft.layout[2][4] = Tile::entrance();
+
+        for i in 0..3 {
+            for j in &[0,1,4,3] {
+                let mut passenger = Person::new("DEFAULT");
+                passenger.target_seat(*j, i);
+                aircraft.add_passenger(passenger);
+            }
+        }
+
+
+        for _ in 0..20 {
+            aircraft.ascii_render();
+            println!("==========");
+            aircraft.update();
+        }
+
+        // Check results
+        for i in 0..3 {
+            for j in &[0,1,4,3] {
+                assert!(aircraft.layout[*j][i].is_occupied());
+            }
+        }
+        assert!(aircraft.audit_seating().is_empty());
+    }
+
+    #[test]
+    fn baggage() {
+        let mut aircraft = Aircraft::new(5,5);
+        
+        for i in 0..5 {
+            for j in &[0,1,3,4] {
+                aircraft.layout[*j][i] = Tile::seat();
+            }
+        }
+        aircraft.layout[2][4] = Tile::entrance();
+        
+        let mut person = Person::new("DEFAULT");
+        person.target_seat(0,0);
+        person.set_baggage(true);
+        aircraft.add_passenger(person);
+
+        for _ in 0..7 {
+            aircraft.update();
+            println!("==========");
+            aircraft.ascii_render();
+        }
+
+        assert_eq!(aircraft.layout[0][0].is_occupied(), false);
+
+        aircraft.update();
+        println!("==========");
+        aircraft.ascii_render();
+
+        assert!(aircraft.layout[0][0].is_occupied());
+    }
+
+    #[test]
+    fn test_is_complete() {
+        let mut aircraft = Aircraft::new(5,5);
+        
+        for i in 0..5 {
+            for j in &[0,1,3,4] {
+                aircraft.layout[*j][i] = Tile::seat();
+            }
+        }
+        aircraft.layout[2][4] = Tile::entrance();
+
+        let mut passengers = Vec::<Person>::new();
+        for i in &[0,1,3,4] {
+            let mut passenger = Person::new("DEFAULT");
+            passenger.target_seat(*i, 0);
+            passengers.push(passenger);
+
+            let mut passenger = Person::new("DEFAULT");
+            passenger.target_seat(*i, 0);
+            aircraft.add_passenger(passenger);
+        }
+
+        for _ in 0..15 {
+            aircraft.ascii_render();
+            aircraft.update();
+            println!("Status is: {}", aircraft.is_complete());
+        }
+        
+        assert!(aircraft.is_complete(),
+                "Not all passengers reached their seats");
+    }
+
+    #[test]
+    fn advanced_stowing() {
+        let mut aircraft = Aircraft::new(5,5);
+        
+        for i in 0..5 {
+            for j in &[0,1,3,4] {
+                aircraft.layout[*j][i] = Tile::seat();
+            }
+        }
+        aircraft.layout[2][4] = Tile::entrance();
+
+        for i in &[(0,0), (4,4)] {
+            let mut passenger = Person::new("DEFAULT");
+            passenger.target_seat(i.0, i.1);
+            passenger.set_baggage(true);
+            aircraft.add_passenger(passenger);
+        }
+
+        for _ in 0..15 {
+            aircraft.ascii_render();
+            aircraft.update();
+            println!("Status is: {}", aircraft.is_complete());
+        }
+        
+        assert!(aircraft.is_complete(),
+                "Not all passengers reached their seats(?)");
+
+        for (x, column) in aircraft.layout.iter().enumerate() {
+            for (y, tile) in column.iter().enumerate() {
+                assert_eq!(tile.get_displaced(), 0,
+                           "A passenger was displaced from tile {},{}", x, y);
+            }
+        }
+        let peaks = aircraft.peak_occupancy();
+        assert_eq!(peaks[2][4], 1);
+        assert_eq!(peaks[1][1], 0);
+    }
+
+    #[test]
+    fn aisle_ticks() {
+        let mut aircraft = Aircraft::new(5,5);
+
+        for i in 0..5 {
+            for j in &[0,1,3,4] {
+                aircraft.layout[*j][i] = Tile::seat();
+            }
+        }
+        aircraft.layout[2][4] = Tile::entrance();
+
+        let mut bagged = Person::new("BAGGED");
+        bagged.target_seat(4,0);
+        bagged.set_baggage(true);
+        aircraft.add_passenger(bagged);
+
+        let mut unbagged = Person::new("UNBAGGED");
+        unbagged.target_seat(0,0);
+        aircraft.add_passenger(unbagged);
+
+        for _ in 0..15 {
+            aircraft.update();
+            aircraft.ascii_render();
+        }
+        assert!(aircraft.is_complete());
+
+        let stats = aircraft.get_stats();
+        assert_eq!(stats.ticks, 15);
+        assert_eq!(stats.passengers[0].name, "BAGGED");
+        assert_eq!(stats.passengers[1].name, "UNBAGGED");
+        assert!(stats.passengers[0].aisle_ticks
+                    > stats.passengers[1].aisle_ticks,
+                "Stowing did not increase time spent in the aisle");
+        assert!(stats.passengers[1].seated_ticks > 0);
+    }
+
+    #[test]
+    fn boarding_order() {
+        for (order, expected) in vec![
+            (BoardingOrder::AsAdded, ["A", "B", "C"]),
+            (BoardingOrder::Reversed, ["C", "B", "A"]),
+            (BoardingOrder::Custom(vec![1, 2]), ["B", "C", "A"]),
+        ] {
+            let mut aircraft = Aircraft::new(5,5);
+            aircraft.layout[2][4] = Tile::entrance();
+            for name in &["A", "B", "C"] {
+                let mut passenger = Person::new(name);
+                passenger.target_seat(2,0);
+                aircraft.add_passenger(passenger);
+            }
+            aircraft.set_boarding_order(order);
+
+            for name in &expected {
+                aircraft.update();
+                assert_eq!(aircraft.layout[2][4].get_occupier().unwrap()
+                               .get_name(),
+                           *name);
+            }
+        }
+    }
+
+    #[test]
+    fn must_board_first() {
+        let mut aircraft = Aircraft::new(5,6);
+        aircraft.layout[2][5] = Tile::entrance();
+        for y in 0..5 {
+            let mut passenger = Person::new(&y.to_string());
+            passenger.target_seat(0,y);
+            passenger.set_must_board_first(y == 0);
+            aircraft.add_passenger(passenger);
+        }
+        assert_eq!(aircraft.next_to_board().unwrap().get_name(), "0");
+
+        for name in &["0", "4", "3", "2", "1"] {
+            aircraft.update();
+            assert_eq!(aircraft.layout[2][5].get_occupier().unwrap()
+                           .get_name(),
+                       *name);
+        }
+    }
+
+    #[test]
+    fn window_after_aisle_and_middle() {
+        for baggage in &[false, true] {
+            let mut aircraft = Aircraft::new(7,5);
+            for i in 0..5 {
+                for j in &[0,1,2,4,5,6] {
+                    aircraft.layout[*j][i] = Tile::seat();
+                }
+            }
+            aircraft.layout[3][4] = Tile::entrance();
+            aircraft.set_boarding_order(BoardingOrder::AsAdded);
+
+            for seat in &[(2,0), (1,0), (0,0), (4,1), (5,1), (6,1)] {
+                let mut passenger = Person::new("DEFAULT");
+                passenger.target_seat(seat.0, seat.1);
+                passenger.set_baggage(*baggage);
+                aircraft.add_passenger(passenger);
+            }
+
+            for _ in 0..40 {
+                aircraft.update();
+                aircraft.ascii_render();
+            }
+
+            assert!(aircraft.is_complete(),
+                    "Window passengers did not reach their seats");
+            for seat in &[(0,0), (6,1)] {
+                assert_eq!(aircraft.layout[seat.0][seat.1].get_occupier()
+                               .unwrap().get_seat(),
+                           Some((seat.0 as u16, seat.1 as u16)),
+                           "Window seat taken by the wrong passenger");
+            }
+        }
+    }
+
+    #[test]
+    fn efficiency() {
+        let mut aircraft = Aircraft::new(5,5);
+        aircraft.layout[2][4] = Tile::entrance();
+        let mut passenger = Person::new("DEFAULT");
+        passenger.target_seat(0,1);
+        passenger.set_baggage(true);
+        aircraft.add_passenger(passenger);
+
+        for _ in 0..10 {
+            aircraft.update();
+        }
+
+        let stats = aircraft.get_stats();
+        assert_eq!(stats.theoretical_min_ticks, 7);
+        assert_eq!(stats.complete_tick, Some(7));
+        assert!((stats.efficiency - 1.0).abs() < 0.01);
+
+        let mut empty = Aircraft::new(5,5);
+        empty.update();
+        assert_eq!(empty.get_stats().efficiency, 1.0);
+    }
+
+    #[test]
+    fn settled_fast_path() {
+        let mut aircraft = Aircraft::new(3,3);
+        let mut passenger = Person::new("DEFAULT");
+        passenger.target_seat(0,0);
+        aircraft.layout[0][0] = Tile::seat();
+        aircraft.layout[0][0].occupy(passenger);
+        aircraft.reset();
+
+        assert_eq!(aircraft.determine_move(0, 0, 0, 0, false),
+                   Some((Behaviour::Settle, 0.0)));
+        // Still holding baggage, so moves are evaluated as normal.
+        assert_eq!(aircraft.determine_move(0, 0, 0, 0, true).unwrap().1, 0.0);
+        assert_eq!(aircraft.layout[0][0].get_occupier().unwrap().is_settled(),
+                   false);
+
+        aircraft.update();
+        assert!(aircraft.layout[0][0].get_occupier().unwrap().is_settled());
+
+        // Once settled, the passenger's moves aren't evaluated at all.
+        aircraft.set_decision_trace(true);
+        for _ in 0..3 {
+            aircraft.update();
+        }
+        let trace = aircraft.take_decision_trace();
+        assert_eq!(trace.len(), 3);
+        assert!(trace.iter().all(|tick| tick.decisions.is_empty()));
+        assert!(aircraft.layout[0][0].get_occupier().unwrap().is_settled());
+    }
+
+    #[test]
+    fn seats_sorted_by() {
+        let mut aircraft = Aircraft::new(5,3);
+        for i in 0..3 {
+            for j in &[0,1,3,4] {
+                aircraft.layout[*j][i] = Tile::seat();
+            }
+        }
+        aircraft.layout[2][2] = Tile::entrance();
+
+        let seats = aircraft.seats_sorted_by(SeatSort::BackToFront);
+        assert_eq!(seats.len(), 12);
+        assert_eq!(&seats[..4], &[(0,2), (1,2), (3,2), (4,2)]);
+        assert_eq!(seats[11], (4,0));
+
+        let seats = aircraft.seats_sorted_by(SeatSort::FrontToBack);
+        assert_eq!(&seats[..4], &[(0,0), (1,0), (3,0), (4,0)]);
+
+        let seats = aircraft.seats_sorted_by(SeatSort::WindowToAisle);
+        assert_eq!(&seats[..6], &[(0,0), (4,0), (0,1), (4,1), (0,2), (4,2)]);
+
+        let seats = aircraft.seats_sorted_by(SeatSort::AisleToWindow);
+        assert_eq!(&seats[..6], &[(1,0), (3,0), (1,1), (3,1), (1,2), (3,2)]);
+    }
+
+    #[test]
+    fn correct_aisle() {
+        // A 3-4-3 layout with a cross-aisle along the back row, boarding
+        // through the door at the end of the left aisle.
+        let mut aircraft = Aircraft::new(12,6);
+        for i in 0..5 {
+            for j in &[0,1,2,4,5,6,7,9,10,11] {
+                aircraft.layout[*j][i] = Tile::seat();
+            }
+        }
+        aircraft.layout[3][5] = Tile::entrance();
+
+        for seat in &[(6,1), (5,1), (10,2), (1,3)] {
+            let mut passenger = Person::new("DEFAULT");
+            passenger.target_seat(seat.0, seat.1);
+            passenger.set_baggage(true);
+            aircraft.add_passenger(passenger);
+        }
+
+        for _ in 0..30 {
+            aircraft.update();
+            aircraft.ascii_render();
+            for y in 0..5 {
+                for x in &[3, 8] {
+                    if let Some(p) = aircraft.layout[*x][y].get_occupier() {
+                        let seat = p.get_seat().unwrap();
+                        assert_eq!(seat.0 < 6, *x == 3,
+                                   "{:?} used the wrong aisle", seat);
+                    }
+                }
+            }
+        }
+
+        assert!(aircraft.is_complete());
+    }
+
+    #[test]
+    fn yield_policy() {
+        let mut ticks = Vec::<u16>::new();
+        for policy in &[YieldPolicy::None,
+                        YieldPolicy::FurtherSeatFirst,
+                        YieldPolicy::CloserSeatFirst] {
+            let mut aircraft = Aircraft::new(5,10);
+            for i in 0..10 {
+                for j in &[0,1,3,4] {
+                    aircraft.layout[*j][i] = Tile::seat();
+                }
+            }
+            aircraft.layout[2][9] = Tile::entrance();
+            aircraft.set_boarding_order(BoardingOrder::AsAdded);
+            aircraft.set_yield_policy(*policy);
+
+            // Rows nearest the door board first, so every stower blocks
+            // everyone heading further down the aisle.
+            for i in (0..10).rev() {
+                for j in &[0,1,3,4] {
+                    let mut passenger = Person::new("DEFAULT");
+                    passenger.target_seat(*j, i);
+                    passenger.set_baggage(true);
+                    aircraft.add_passenger(passenger);
+                }
+            }
+
+            ticks.push(aircraft.run_to_completion().unwrap().ticks);
+        }
+
+        println!("Completion ticks: {:?}", ticks);
+        assert!(ticks[1] < ticks[0],
+                "Letting further passengers past did not help");
+    }
+
+    #[test]
+    fn decision_trace() {
+        let mut aircraft = Aircraft::new(5,5);
+        for i in 0..5 {
+            for j in &[0,1,3,4] {
+                aircraft.layout[*j][i] = Tile::seat();
+            }
+        }
+        aircraft.layout[2][4] = Tile::entrance();
+        let mut passenger = Person::new("Dave");
+        passenger.target_seat(0,0);
+        aircraft.add_passenger(passenger);
+
+        aircraft.update();
+        assert!(aircraft.take_decision_trace().is_empty(),
+                "Trace collected without being enabled");
+
+        aircraft.set_decision_trace(true);
+        aircraft.update();
+        let trace = aircraft.take_decision_trace();
+        assert_eq!(trace.len(), 1);
+        assert_eq!(trace[0].tick, 2);
+        assert_eq!(trace[0].decisions.len(), 1);
+
+        let decision = &trace[0].decisions[0];
+        assert_eq!(decision.passenger, "Dave");
+        assert_eq!(decision.position, (2,4));
+        assert_eq!(decision.candidates, vec![
+            (Behaviour::Wait, 6.0),
+            (Behaviour::Move_North, 5.0),
+            (Behaviour::Move_East, 7.0),
+            (Behaviour::Move_West, 5.0),
+        ]);
+        assert_eq!(decision.chosen, Behaviour::Move_North);
+        assert!(aircraft.take_decision_trace().is_empty());
+    }
+
+    #[test]
+    fn row_legroom() {
+        let mut ticks = Vec::<u16>::new();
+        for factor in &[1.0, 2.0, 0.5] {
+            let mut aircraft = Aircraft::new(5,5);
+            for i in 0..5 {
+                for j in &[0,1,3,4] {
+                    aircraft.layout[*j][i] = Tile::seat();
+                }
+            }
+            aircraft.layout[2][4] = Tile::entrance();
+            aircraft.set_row_legroom(1, *factor).unwrap();
+
+            let mut seated = Person::new("SEATED");
+            seated.target_seat(1,1);
+            aircraft.layout[1][1].occupy(seated);
+
+            let mut passenger = Person::new("WINDOW");
+            passenger.target_seat(0,1);
+            aircraft.add_passenger(passenger);
+
+            ticks.push(aircraft.run_to_completion().unwrap().ticks);
+        }
+
+        println!("Completion ticks: {:?}", ticks);
+        assert!(ticks[1] < ticks[0], "Exit row was not passed faster");
+        assert!(ticks[2] > ticks[0], "Tight row was not passed slower");
+
+        let mut aircraft = Aircraft::new(5,5);
+        assert!(aircraft.set_row_legroom(5, 1.0).is_err());
+        assert!(aircraft.set_row_legroom(0, 0.0).is_err());
+    }
+
+    #[test]
+    fn step_report() {
+        let mut aircraft = Aircraft::new(5,5);
+        for i in 0..5 {
+            for j in &[0,1,3,4] {
+                aircraft.layout[*j][i] = Tile::seat();
+            }
+        }
+        aircraft.layout[2][4] = Tile::entrance();
+        let mut passenger = Person::new("DEFAULT");
+        passenger.target_seat(1,3);
+        passenger.set_baggage(true);
+        aircraft.add_passenger(passenger);
+
+        let report = aircraft.step();
+        assert_eq!(report.tick, 1);
+        assert!(report.entered);
+        assert_eq!(report.moved, 0);
+
+        let report = aircraft.step();
+        assert!(!report.entered);
+        assert_eq!(report.moved, 1);
+
+        let report = aircraft.step();
+        assert_eq!(report.stowed, 1);
+        assert!(!report.complete);
+
+        let report = aircraft.step();
+        assert_eq!(report.tick, 4);
+        assert_eq!(report.seated_delta, 1);
+        assert!(report.complete);
+    }
+
+    #[test]
+    fn gate_check() {
+        let mut aircraft = Aircraft::new(5,5);
+        for i in 0..5 {
+            for j in &[0,1,3,4] {
+                aircraft.layout[*j][i] = Tile::seat();
+            }
+        }
+        aircraft.layout[2][4] = Tile::entrance();
+        aircraft.set_bin_capacity(Some(1));
+        aircraft.set_boarding_order(BoardingOrder::AsAdded);
+
+        // The first passenger fills the bins on row 3 before the rest board.
+        let mut early = Person::new("EARLY");
+        early.target_seat(3,3);
+        early.set_baggage(true);
+        aircraft.add_passenger(early);
+        for _ in 0..3 {
+            aircraft.update();
+        }
+        assert!(aircraft.bins_full(3));
+
+        for seat in &[(4,3), (1,3), (0,3)] {
+            let mut late = Person::new("LATE");
+            late.target_seat(seat.0, seat.1);
+            late.set_baggage(true);
+            aircraft.add_passenger(late);
+        }
+
+        let mut stowed = 0;
+        for _ in 0..20 {
+            stowed += aircraft.step().stowed;
+        }
+
+        let stats = aircraft.get_stats();
+        assert!(aircraft.is_complete());
+        assert_eq!(stowed, 0, "A late passenger carried its bag to its seat");
+        assert_eq!(stats.gate_checked, 3);
+        assert!(!stats.passengers[0].gate_checked);
+        assert!(stats.passengers[1..].iter().all(|p| p.gate_checked));
+        assert_eq!(aircraft.get_bin_usage(3), 1);
+    }
+
+    #[test]
+    fn access_tile() {
+        // The seat at 0,1 is walled off from the aisle on its row, so must be
+        // entered from the access tiles below it.
+        let mut aircraft = Aircraft::new(3,4);
+        for (x, y) in &[(0,0), (1,0), (0,1), (0,3), (1,3)] {
+            aircraft.layout[*x][*y] = Tile::seat();
+        }
+        aircraft.layout[1][1] = Tile::none();
+        aircraft.layout[2][3] = Tile::entrance();
+
+        assert_eq!(aircraft.access_tile(0,1), Some((0,2)));
+        assert_eq!(aircraft.access_tile(0,0), None);
+
+        for seat in &[(0,0), (0,1)] {
+            let mut passenger = Person::new("DEFAULT");
+            passenger.target_seat(seat.0, seat.1);
+            passenger.set_baggage(true);
+            aircraft.add_passenger(passenger);
+        }
+
+        for _ in 0..15 {
+            aircraft.update();
+            aircraft.ascii_render();
+            assert!(!aircraft.layout[1][1].is_occupied());
+        }
+
+        assert!(aircraft.is_complete());
+        assert_eq!(aircraft.layout[0][1].get_occupier().unwrap().get_seat(),
+                   Some((0,1)));
+    }
+
+    #[test]
+    fn replay() {
+        fn build(bridge: u16) -> Aircraft {
+            let mut aircraft = Aircraft::new(3,6);
+            for y in 0..5 {
+                aircraft.layout[0][y] = Tile::seat();
+                aircraft.layout[2][y] = Tile::seat();
+            }
+            aircraft.layout[0][5] = Tile::none();
+            aircraft.layout[1][5] = Tile::entrance();
+            aircraft.layout[2][5] = Tile::none();
+            aircraft.set_bin_capacity(Some(1));
+            aircraft.set_jet_bridge_len(bridge).unwrap();
+            for y in 0..5 {
+                for x in &[0, 2] {
+                    let mut passenger = Person::new("DEFAULT");
+                    passenger.target_seat(*x, y);
+                    passenger.set_baggage(true);
+                    aircraft.add_passenger(passenger);
+                }
+            }
+            aircraft
+        }
+
+        for bridge in &[0, 3] {
+            let mut original = build(*bridge);
+            original.set_event_log(true);
+            let ticks = original.run_to_completion().unwrap().ticks;
+            let events = original.take_events();
+            assert!(original.take_events().is_empty());
+            assert_eq!(events.last().unwrap().get_tick(), ticks);
+
+            let mut replayed = build(*bridge);
+            replayed.replay(&events).unwrap();
+            assert!(replayed.is_complete());
+            assert!(replayed.all_seated());
+            let (stats, expected) = (replayed.get_stats(),
+                                     original.get_stats());
+            assert_eq!(stats.ticks, ticks);
+            assert_eq!(stats.complete_tick, expected.complete_tick);
+            assert_eq!(stats.efficiency, expected.efficiency);
+            assert_eq!(stats.last_boarded_tick, expected.last_boarded_tick);
+            assert_eq!(stats.gate_checked, expected.gate_checked);
+            assert_eq!(stats.interferences, expected.interferences);
+            assert_eq!(stats.total_aisle_person_ticks,
+                       expected.total_aisle_person_ticks);
+            for (a, b) in stats.passengers.iter()
+                .zip(expected.passengers.iter()) {
+                assert_eq!((a.boarded_tick, a.seated_tick),
+                           (b.boarded_tick, b.seated_tick));
+            }
+            for x in 0..3 {
+                for y in 0..6 {
+                    let occupier = |a: &Aircraft| a.layout[x][y]
+                        .get_occupier()
+                        .map(|p| (p.get_id(), p.has_baggage()));
+                    assert_eq!(occupier(&replayed), occupier(&original));
+                }
+            }
+            for y in 0..6 {
+                assert_eq!(replayed.get_bin_usage(y),
+                           original.get_bin_usage(y));
+            }
+        }
+
+        let mut original = build(0);
+        original.set_event_log(true);
+        original.run_to_completion().unwrap();
+        let events = original.take_events();
+
+        // A move naming a different passenger than the one on the tile fails.
+        let mut swapped = events.clone();
+        for event in swapped.iter_mut() {
+            if let SimEvent::Moved { id, .. } = event {
+                *id = id.map(|id| id + 1);
+                break;
+            }
+        }
+        assert!(build(0).replay(&swapped).is_err());
+
+        // Replaying onto an aircraft with nobody waiting fails.
+        let mut empty = Aircraft::new(3,6);
+        assert!(empty.replay(&events).is_err());
+    }
+
+    #[test]
+    fn no_legal_move() {
+        // A passenger in the aisle, behind a passenger blocking its way to its
+        // row, can't step sideways into the seats on its current row.
+        let mut aircraft = Aircraft::new(3,3);
+        for y in 0..2 {
+            aircraft.layout[0][y] = Tile::seat();
+            aircraft.layout[2][y] = Tile::seat();
+        }
+        let mut ahead = Person::new("DEFAULT");
+        ahead.target_seat(2,0);
+        aircraft.layout[1][0].occupy(ahead);
+
+        // With room to step back, the best move is to wait where it is.
+        assert_eq!(aircraft.determine_move(1, 1, 0, 0, false),
+                   Some((Behaviour::Wait, 2.0)));
+
+        // Once someone is standing behind it too, it is boxed in.
+        aircraft.layout[1][2].occupy(Person::new("DEFAULT"));
+        assert_eq!(aircraft.determine_move(1, 1, 0, 0, false), None);
+
+        // Which is recorded as such, rather than as an ordinary wait.
+        let mut boxed = Person::new("BOXED");
+        boxed.target_seat(0,0);
+        boxed.set_id(0);
+        aircraft.layout[1][1].occupy(boxed);
+        aircraft.set_decision_trace(true);
+        aircraft.decision_trace.push(TickDecisions {
+            tick: 1,
+            decisions: Vec::<MoveDecision>::new(),
+        });
+        assert_eq!(aircraft.decide(1, 1, (0,0), false, false).0,
+                   Behaviour::Wait);
+        assert_eq!(aircraft.boxed_in(), &[0]);
+        let trace = aircraft.take_decision_trace();
+        assert!(trace[0].decisions[0].boxed_in);
+    }
+
+    #[test]
+    fn bin_search() {
+        let mut aircraft = Aircraft::new(3,6);
+        for y in 0..5 {
+            aircraft.layout[0][y] = Tile::seat();
+            aircraft.layout[2][y] = Tile::seat();
+        }
+        aircraft.layout[0][5] = Tile::none();
+        aircraft.layout[1][5] = Tile::entrance();
+        aircraft.layout[2][5] = Tile::none();
+        aircraft.set_bin_capacity(Some(1));
+        aircraft.set_bin_search_range(2);
+        aircraft.set_event_log(true);
+        aircraft.store_bag(3);
+
+        let mut passenger = Person::new("DEFAULT");
+        passenger.target_seat(0,3);
+        passenger.set_baggage(true);
+        aircraft.add_passenger(passenger);
+        aircraft.run_to_completion().unwrap();
+
+        // The bag is stowed on the row in front, rather than checked.
+        assert_eq!(aircraft.get_stats().gate_checked, 0);
+        assert_eq!(aircraft.get_bin_usage(2), 1);
+        assert!(aircraft.take_events().iter().any(|e| {
+            matches!(e, SimEvent::Stowed { position: (1,2), .. })
+        }));
+        assert_eq!(aircraft.layout[0][3].get_occupier().unwrap().get_seat(),
+                   Some((0,3)));
+        assert!(!aircraft.layout[0][3].get_occupier().unwrap().has_baggage());
+    }
+
+    #[test]
+    fn aisle_load() {
+        // A 3-4-3 layout with a cross-aisle along the back row.
+        let mut aircraft = Aircraft::new(12,6);
+        for i in 0..5 {
+            for j in &[0,1,2,4,5,6,7,9,10,11] {
+                aircraft.layout[*j][i] = Tile::seat();
+            }
+        }
+        aircraft.layout[3][5] = Tile::entrance();
+
+        // Each aisle takes its three outer seats and the two nearest center
+        // seats on every row.
+        assert_eq!(aircraft.aisle_load(), vec![(3, 25), (8, 25)]);
+    }
+
+    #[test]
+    fn door_window() {
+        // Doors at the front and back of a single aisle.
+        let mut aircraft = Aircraft::new(3,8);
+        for y in 1..7 {
+            aircraft.layout[0][y] = Tile::seat();
+            aircraft.layout[2][y] = Tile::seat();
+        }
+        for y in &[0, 7] {
+            aircraft.layout[0][*y] = Tile::none();
+            aircraft.layout[1][*y] = Tile::entrance();
+            aircraft.layout[2][*y] = Tile::none();
+        }
+        assert!(aircraft.set_door_window(0, 0, 10, None).is_err());
+        aircraft.set_door_window(1, 7, 10, None).unwrap();
+        aircraft.set_door_window(1, 0, 0, Some(5)).unwrap();
+        aircraft.set_event_log(true);
+
+        for y in 1..7 {
+            for x in &[0, 2] {
+                let mut passenger = Person::new("DEFAULT");
+                passenger.target_seat(*x, y);
+                aircraft.add_passenger(passenger);
+            }
+        }
+        aircraft.run_to_completion().unwrap();
+
+        let boarded: Vec<(u16, (u16, u16))> = aircraft.take_events().iter()
+            .filter_map(|e| match e {
+                SimEvent::Boarded { tick, position, .. } => {
+                    Some((*tick, *position))
+                },
+                _ => None,
+            })
+            .collect();
+        assert!(boarded.iter().any(|(_, door)| *door == (1,7)));
+        for (tick, door) in boarded {
+            match door {
+                (1,7) => assert!(tick >= 10),
+                _ => assert!(tick < 5),
+            }
+        }
+    }
+
+    #[test]
+    fn mobility() {
+        let mut blocked = Vec::<u16>::new();
+        let mut ticks = Vec::<u16>::new();
+        for mobility in &[Mobility::Normal, Mobility::Reduced,
+                          Mobility::Wheelchair] {
+            let mut aircraft = Aircraft::new(3,6);
+            for y in 0..5 {
+                aircraft.layout[0][y] = Tile::seat();
+                aircraft.layout[2][y] = Tile::seat();
+            }
+            aircraft.layout[1][5] = Tile::entrance();
+            let mut passenger = Person::new("DEFAULT");
+            passenger.target_seat(0,1);
+            passenger.set_mobility(*mobility);
+            aircraft.add_passenger(passenger);
+
+            let mut count = 0;
+            while !aircraft.is_complete() {
+                aircraft.update();
+                if aircraft.layout[1][1].is_occupied() {
+                    count += 1;
+                }
+            }
+            blocked.push(count);
+            ticks.push(aircraft.get_stats().ticks);
+        }
+
+        assert!(ticks[0] < ticks[1] && ticks[1] < ticks[2]);
+        assert_eq!(blocked[0], 1);
+        assert!(blocked[2] > blocked[1]);
+    }
+
+    #[test]
+    fn critical_path() {
+        let mut aircraft = Aircraft::new(3,6);
+        for y in 0..5 {
+            aircraft.layout[0][y] = Tile::seat();
+            aircraft.layout[2][y] = Tile::seat();
+        }
+        aircraft.layout[0][5] = Tile::none();
+        aircraft.layout[1][5] = Tile::entrance();
+        aircraft.layout[2][5] = Tile::none();
+        aircraft.set_boarding_order(BoardingOrder::AsAdded);
+
+        // The first passenger stops to stow its bag just inside the door,
+        // holding up everyone behind it.
+        for (name, seat, baggage) in &[("Stower", (0,4), true),
+                                       ("Front", (2,0), false),
+                                       ("Middle", (0,2), false)] {
+            let mut passenger = Person::new(name);
+            passenger.target_seat(seat.0, seat.1);
+            passenger.set_baggage(*baggage);
+            aircraft.add_passenger(passenger);
+        }
+        aircraft.run_to_completion().unwrap();
+
+        let stats = aircraft.get_stats();
+        assert_eq!(stats.critical_path[0], "Stower");
+        assert_eq!(stats.critical_path.last().unwrap(), "Front");
+        assert_eq!(stats.passengers[0].blocked_by, None);
+        assert_eq!(stats.passengers[1].blocked_by, Some(0));
+    }
+
+    #[test]
+    fn resume_run() {
+        fn build() -> Aircraft {
+            let mut aircraft = Aircraft::new(3,10);
+            for y in 0..9 {
+                aircraft.layout[0][y] = Tile::seat();
+                aircraft.layout[2][y] = Tile::seat();
+            }
+            aircraft.layout[0][9] = Tile::none();
+            aircraft.layout[1][9] = Tile::entrance();
+            aircraft.layout[2][9] = Tile::none();
+            for y in 0..9 {
+                let mut passenger = Person::new("DEFAULT");
+                passenger.target_seat(0, y);
+                passenger.set_baggage(true);
+                aircraft.add_passenger(passenger);
+            }
+            aircraft
+        }
+
+        let total = build().run_to_completion().unwrap().ticks;
+        assert!(total > 10);
+
+        let mut aircraft = build();
+        assert!(!aircraft.run_for(10));
+        assert_eq!(aircraft.get_stats().ticks, 10);
+        assert_eq!(aircraft.run_to_completion().map(|s| s.ticks), Ok(total));
+        assert!(aircraft.run_for(10));
+        assert_eq!(aircraft.get_stats().ticks, total);
+    }
+
+    #[test]
+    fn snapshot_resume() {
+        let build = || {
+            let mut aircraft = Aircraft::new(3,10);
+            for y in 0..9 {
+                aircraft.layout[0][y] = Tile::seat();
+                aircraft.layout[2][y] = Tile::seat();
+            }
+            aircraft.layout[0][9] = Tile::none();
+            aircraft.layout[1][9] = Tile::entrance();
+            aircraft.layout[2][9] = Tile::none();
+            for y in 0..9 {
+                for x in &[0, 2] {
+                    let mut passenger = Person::new("DEFAULT");
+                    passenger.target_seat(*x, y);
+                    passenger.set_baggage(true);
+                    aircraft.add_passenger(passenger);
+                }
+            }
+            aircraft
+        };
+        let mut uninterrupted = build();
+        let total = uninterrupted.run_to_completion().unwrap().ticks;
+
+        let mut original = build();
+        original.run_for(total / 2);
+        let snapshot = original.snapshot();
+        assert!(original.get_stats().boarded > 0);
+        assert!(!original.unseated().is_empty());
+
+        let mut resumed = Aircraft::new(3, 10);
+        resumed.resume(&snapshot).unwrap();
+        assert_eq!(resumed.fingerprint(), original.fingerprint());
+        assert_eq!(resumed.run_to_completion().map(|s| s.ticks), Ok(total));
+        assert_eq!(resumed.get_stats(), uninterrupted.get_stats());
+        assert_eq!(resumed.fingerprint(), uninterrupted.fingerprint());
+
+        assert!(Aircraft::new(3, 9).resume(&snapshot).is_err());
+        let mut tampered = snapshot.clone();
+        tampered.aircraft.targeted_seats.pop();
+        assert!(resumed.resume(&tampered).is_err());
+        let mut lost = snapshot.clone();
+        let tile = lost.aircraft.layout.iter_mut().flatten()
+            .find(|t| t.get_occupier().and_then(|p| p.get_id()).is_some())
+            .unwrap();
+        tile.free();
+        assert_eq!(resumed.resume(&lost), Err("Passenger is missing"));
+        assert!(resumed.is_complete());
+    }
+
+    #[test]
+    fn seat_after_stow() {
+        let build = |stow_delay: u8| {
+            let mut aircraft = Aircraft::new(3, 5);
+            for y in 0..4 {
+                aircraft.layout[0][y] = Tile::seat();
+                aircraft.layout[2][y] = Tile::seat();
+            }
+            aircraft.layout[0][4] = Tile::none();
+            aircraft.layout[1][4] = Tile::entrance();
+            aircraft.layout[2][4] = Tile::none();
+            let mut passenger = Person::new("Dave");
+            passenger.target_seat(0, 1);
+            passenger.set_baggage(true);
+            passenger.set_stow_delay(stow_delay);
+            aircraft.add_passenger(passenger);
+            aircraft
+        };
+
+        for stow_delay in 0..3 {
+            let separate = build(stow_delay).run_to_completion().unwrap().ticks;
+            let mut aircraft = build(stow_delay);
+            aircraft.set_seat_after_stow(true);
+            let together = aircraft.run_to_completion().unwrap().ticks;
+
+            // Boarding and walking to row 1 takes 4 updates, then the stow
+            // delay, then 1 to stow and step into the seat together.
+            assert_eq!(together, 4 + stow_delay as u16 + 1);
+            assert_eq!(separate, together + 1);
+            assert_eq!(aircraft.get_stats().passengers[0].seated_tick,
+                       Some(together));
+        }
+    }
+
+    #[test]
+    fn oscillation_guard() {
+        let mut aircraft = Aircraft::new(3,3);
+        aircraft.layout[0][0] = Tile::none();
+        aircraft.layout[1][0] = Tile::seat();
+        aircraft.layout[2][0] = Tile::none();
+        let mut passenger = Person::new("DEFAULT");
+        passenger.target_seat(1,0);
+        // Having gone back and forth between its seat and the aisle behind it.
+        for _ in 0..2 {
+            passenger.record_position(1,0);
+            passenger.record_position(1,1);
+        }
+        aircraft.layout[1][1].occupy(passenger);
+        aircraft.reset();
+
+        // It waits rather than heading back to its seat yet again, then
+        // carries on.
+        aircraft.update();
+        assert!(aircraft.layout[1][1].is_occupied());
+        assert!(!aircraft.layout[1][0].is_occupied());
+        aircraft.update();
+        assert!(aircraft.layout[1][0].is_occupied());
+    }
+
+    #[test]
+    fn neighbourhood() {
+        let mut aircraft = Aircraft::new(3,3);
+        aircraft.layout[0][0] = Tile::seat();
+        aircraft.layout[1][0].occupy(Person::new("DEFAULT"));
+        aircraft.layout[2][1] = Tile::none();
+
+        let around = aircraft.neighbourhood(1,1);
+        assert_eq!(around.get(-1,-1).unwrap().get_variant(), Variant::Seat);
+        assert!(around.get(0,-1).unwrap().is_occupied());
+        assert!(!around.get(0,0).unwrap().is_occupied());
+        assert_eq!(around.get(1,0).unwrap().get_variant(), Variant::None);
+        assert_eq!(around.get(2,0), None);
+
+        // Tiles off the edge of the aircraft are left out.
+        let corner = aircraft.neighbourhood(0,0);
+        assert_eq!(corner.get(-1,0), None);
+        assert_eq!(corner.get(0,-1), None);
+        assert_eq!(corner.get(1,1).unwrap(),
+                   SimpleTile::new(&aircraft.layout[1][1]));
+    }
+
+    #[test]
+    fn reserved_bin() {
+        let mut aircraft = Aircraft::new(3,6);
+        for y in 0..5 {
+            aircraft.layout[0][y] = Tile::seat();
+            aircraft.layout[2][y] = Tile::seat();
+        }
+        aircraft.layout[0][5] = Tile::none();
+        aircraft.layout[1][5] = Tile::entrance();
+        aircraft.layout[2][5] = Tile::none();
+        aircraft.set_bin_capacity(Some(1));
+        aircraft.set_bin_search_range(2);
+        aircraft.set_event_log(true);
+        aircraft.store_bag(3);
+
+        for (seat, reserved) in &[((2,3), false), ((0,3), true)] {
+            let mut passenger = Person::new("DEFAULT");
+            passenger.target_seat(seat.0, seat.1);
+            passenger.set_baggage(true);
+            passenger.set_reserved_bin(*reserved);
+            aircraft.add_passenger(passenger);
+        }
+        aircraft.run_to_completion().unwrap();
+
+        let stowed: Vec<(Option<usize>, (u16,u16))> = aircraft.take_events()
+            .iter()
+            .filter_map(|e| match e {
+                SimEvent::Stowed { id, position, .. } => Some((*id, *position)),
+                _ => None,
+            })
+            .collect();
+        assert!(stowed.contains(&(Some(1), (1,3))));
+        assert!(stowed.contains(&(Some(0), (1,2))));
+        assert_eq!(aircraft.get_stats().gate_checked, 0);
+    }
+
+    #[test]
+    fn entrance_seat() {
+        let mut aircraft = Aircraft::new(3, 3);
+        aircraft.layout[1][2] = Tile::entrance();
+        aircraft.layout[0][1] = Tile::seat();
+        for coords in &[(0, 0), (2, 0), (0, 2), (2, 2), (2, 1)] {
+            aircraft.layout[coords.0][coords.1] = Tile::none();
+        }
+        // Passengers board in reverse, so the one seated at the door is last.
+        for seat in &[(1, 2), (0, 1)] {
+            let mut passenger = Person::new("Dave");
+            passenger.target_seat(seat.0, seat.1);
+            aircraft.add_passenger(passenger);
         }
+
+        assert!(aircraft.run_to_completion().is_ok());
+        assert!(aircraft.layout[0][1].is_occupied());
+        assert!(aircraft.layout[1][2].is_occupied());
     }
-    
-    /// Checks whether all passengers are seated in their desired seats.
-    pub fn is_complete(&self) -> bool {
-        let mut complete: bool = true;
-        for targeted_seat in &self.targeted_seats {
-            let (x, y) = targeted_seat;
-            if !self.layout[*x as usize][*y as usize].is_occupied() {
-                complete = false;
+
+    #[test]
+    fn full_cabin_outcome() {
+        // Pins down every move made boarding a full cabin, so that changes to
+        // the update loop meant to be behaviour-preserving can be checked. The
+        // values were recorded before the variant was cached in `step`.
+        let mut aircraft = Aircraft::new(7, 10);
+        for x in 0..7 {
+            for y in 0..10 {
+                if x != 3 {
+                    aircraft.layout[x][y] = Tile::seat();
+                }
             }
         }
-        return complete;
+        aircraft.layout[3][9] = Tile::entrance();
+        aircraft.set_event_log(true);
+        // A fixed, interleaved order so that passengers get in each other's
+        // way.
+        for &x in &[0, 6, 2, 4, 1, 5] {
+            for y in 0..10 {
+                let mut passenger = Person::new("Dave");
+                passenger.target_seat(x, (y * 3) % 10);
+                aircraft.add_passenger(passenger);
+            }
+        }
+
+        let ticks = aircraft.run_to_completion().unwrap().ticks;
+        let events = aircraft.take_events();
+        let moves: Vec<(u16, (u16, u16))> = events.iter()
+            .filter_map(|e| match *e {
+                SimEvent::Moved { tick, to, .. } => Some((tick, to)),
+                _ => None,
+            })
+            .collect();
+        let checksum: u32 = moves.iter()
+            .map(|(tick, to)| *tick as u32 * (to.0 as u32 + to.1 as u32 * 7))
+            .sum();
+        assert_eq!(ticks, 76);
+        assert_eq!(aircraft.stats.interferences, 40);
+        assert_eq!(moves.len(), 390);
+        assert_eq!(checksum, 524565);
     }
 
-    /// Sets all tiles as not being updated.
-    ///
-    /// This is called at the end of `update()` to ensure no incorrect
-    /// behaviours are observed on the next iteration.
-    fn reset(&mut self) {
-        for i in 0..self.size.0 as usize {
-            for j in 0..self.size.1 as usize {
-                self.layout[i][j].set_updated(false);
+    #[test]
+    fn transit() {
+        let mut aircraft = Aircraft::new(3, 6);
+        aircraft.layout[1][0] = Tile::entrance();
+        aircraft.layout[1][5] = Tile::entrance();
+        for coords in &[(0, 0), (2, 0), (0, 5), (2, 5)] {
+            aircraft.layout[coords.0][coords.1] = Tile::none();
+        }
+        for y in 1..5 {
+            aircraft.layout[0][y] = Tile::seat();
+            aircraft.layout[2][y] = Tile::seat();
+        }
+        assert!(aircraft.add_transit((1, 0), (0, 1)).is_err());
+        aircraft.add_transit((1, 0), (1, 5)).unwrap();
+        aircraft.set_door_window(1, 5, 8, None).unwrap();
+        let mut passenger = Person::new("Dave");
+        passenger.target_seat(2, 2);
+        aircraft.add_passenger(passenger);
+
+        // The transit actor crosses the whole cabin before the rear door
+        // opens, so the passenger boards at the front.
+        aircraft.step();
+        assert!(aircraft.layout[1][0].get_occupier().unwrap().is_transit());
+        assert!(!aircraft.is_complete());
+        for _ in 0..6 {
+            aircraft.step();
+        }
+        for y in 0..6 {
+            assert!(aircraft.layout[1][y].get_occupier()
+                    .filter(|p| p.is_transit())
+                    .is_none());
+        }
+
+        assert!(aircraft.run_to_completion().is_ok());
+        assert_eq!(aircraft.stats.boarded, 1);
+        assert!(aircraft.layout[2][2].is_occupied());
+    }
+
+    #[test]
+    fn exact_completion_tick() {
+        let mut aircraft = Aircraft::new(3, 5);
+        aircraft.layout[1][4] = Tile::entrance();
+        for y in 0..5 {
+            aircraft.layout[0][y] = Tile::seat();
+            aircraft.layout[2][y] = Tile::seat();
+        }
+        aircraft.layout[0][4] = Tile::none();
+        aircraft.layout[2][4] = Tile::none();
+        for seat in &[(0, 0), (2, 1), (0, 3)] {
+            let mut passenger = Person::new("Dave");
+            passenger.target_seat(seat.0, seat.1);
+            passenger.set_baggage(true);
+            aircraft.add_passenger(passenger);
+        }
+
+        let ticks = aircraft.run_to_completion().unwrap().ticks;
+        let last_seated = aircraft.stats.passengers.iter()
+            .map(|p| p.seated_tick.unwrap())
+            .max();
+        assert_eq!(Some(ticks), last_seated);
+        assert_eq!(Some(ticks), aircraft.stats.complete_tick);
+        assert_eq!(ticks, aircraft.stats.ticks);
+    }
+
+    /// Boards a single passenger, returning the tiles it moved to.
+    fn path_to_seat(mut aircraft: Aircraft, seat: (u16, u16))
+        -> Vec<(u16, u16)> {
+        let mut passenger = Person::new("Dave");
+        passenger.target_seat(seat.0, seat.1);
+        aircraft.add_passenger(passenger);
+        aircraft.set_event_log(true);
+        aircraft.run_to_completion().unwrap();
+        aircraft.take_events().iter()
+            .filter_map(|e| match *e {
+                SimEvent::Moved { to, .. } => Some(to),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn window_seat_entry() {
+        // A three-seat block beside the aisle.
+        let mut aircraft = Aircraft::new(4, 4);
+        for y in 0..3 {
+            for x in 0..3 {
+                aircraft.layout[x][y] = Tile::seat();
             }
         }
+        for x in 0..3 {
+            aircraft.layout[x][3] = Tile::none();
+        }
+        aircraft.layout[3][3] = Tile::entrance();
+
+        let path = path_to_seat(aircraft, (0, 1));
+        assert_eq!(path, vec![(3, 2), (3, 1), (2, 1), (1, 1), (0, 1)]);
     }
 
-    /// Sets the `variant` of a tile.
-    ///
-    /// Changes the `variant` member of a tile to another `Variant` enum, which
-    /// affects the way passengers will move when on that tile.
-    pub fn set_tile(&mut self, x: u16, y: u16, var: Variant) {
-        self.layout[x as usize][y as usize] = match var {
-            Variant::Aisle => Tile::aisle(),
-            Variant::Seat => Tile::seat(),
-            Variant::Entrance => Tile::entrance(),
-            Variant::None => Tile::none(),
-        };
+    #[test]
+    fn seat_block_entered_from_nearest_aisle() {
+        // A three-seat block between two aisles, joined by a cross-aisle at the
+        // back. The passenger boards at the front, on the far side of the block
+        // from its seat.
+        let mut aircraft = Aircraft::new(5, 5);
+        for y in 0..4 {
+            for x in 1..4 {
+                aircraft.layout[x][y] = Tile::seat();
+            }
+        }
+        aircraft.layout[4][0] = Tile::entrance();
+
+        // Going round by the cross-aisle rather than climbing over the seats.
+        let path = path_to_seat(aircraft, (1, 1));
+        assert_eq!(path, vec![(4, 1), (4, 2), (4, 3), (4, 4), (3, 4), (2, 4),
+                              (1, 4), (0, 4), (0, 3), (0, 2), (0, 1), (1, 1)]);
     }
 
-    pub fn get_size(&self) -> (u16, u16) {
-        (self.size.0, self.size.1)
+    #[test]
+    fn passenger_added_mid_run() {
+        let mut aircraft = Aircraft::new(3, 5);
+        aircraft.layout[1][4] = Tile::entrance();
+        for y in 0..4 {
+            aircraft.layout[0][y] = Tile::seat();
+            aircraft.layout[2][y] = Tile::seat();
+        }
+        aircraft.layout[0][4] = Tile::none();
+        aircraft.layout[2][4] = Tile::none();
+        aircraft.set_boarding_order(BoardingOrder::AsAdded);
+        let mut passenger = Person::new("Dave");
+        passenger.target_seat(2, 3);
+        aircraft.add_passenger(passenger);
+
+        for _ in 0..5 {
+            aircraft.step();
+        }
+        assert!(aircraft.is_complete());
+        assert!(aircraft.get_stats().complete_tick.is_some());
+
+        let mut late = Person::new("Late");
+        late.target_seat(0, 0);
+        aircraft.add_passenger(late);
+        assert!(!aircraft.is_complete());
+        assert_eq!(aircraft.get_stats().complete_tick, None);
+
+        let ticks = aircraft.run_to_completion().unwrap().ticks;
+        assert!(ticks > 5);
+        assert_eq!(aircraft.get_stats().complete_tick, Some(ticks));
+        assert_eq!(aircraft.layout[0][0].get_occupier().unwrap().get_name(),
+                   "Late");
     }
 
-    pub fn get_tile_variant(&self, x: u16, y: u16) -> Variant {
-        self.layout[x as usize][y as usize].get_variant()
+    #[test]
+    fn last_bin_space_contest() {
+        // Two passengers reach the same row up parallel aisles at once, with
+        // room in its bins for only one bag.
+        let contest = |left_priority: u8, right_priority: u8| {
+            let mut aircraft = Aircraft::new(5, 6);
+            for y in 0..5 {
+                for x in &[0, 2, 4] {
+                    aircraft.layout[*x][y] = Tile::seat();
+                }
+            }
+            for x in &[0, 2, 4] {
+                aircraft.layout[*x][5] = Tile::none();
+            }
+            aircraft.layout[1][5] = Tile::entrance();
+            aircraft.layout[3][5] = Tile::entrance();
+            aircraft.set_bin_capacity(Some(1));
+            aircraft.set_bin_search_range(2);
+            for (seat, priority) in &[((4, 2), right_priority),
+                                      ((0, 2), left_priority)] {
+                let mut passenger = Person::new("Dave");
+                passenger.target_seat(seat.0, seat.1);
+                passenger.set_baggage(true);
+                passenger.set_bin_priority(*priority);
+                aircraft.add_passenger(passenger);
+            }
+            aircraft.set_event_log(true);
+            aircraft.run_to_completion().unwrap();
+            aircraft.take_events().iter()
+                .filter_map(|e| match *e {
+                    SimEvent::Stowed { id, position, .. } => {
+                        Some((id, position))
+                    },
+                    _ => None,
+                })
+                .collect::<Vec<(Option<usize>, (u16, u16))>>()
+        };
+
+        // The left passenger is updated first, so wins a tie.
+        assert_eq!(contest(0, 0), vec![(Some(1), (1, 2)), (Some(0), (3, 1))]);
+        assert_eq!(contest(0, 1), vec![(Some(0), (3, 2)), (Some(1), (1, 1))]);
     }
 
-    pub fn check_if_occupied(&self, x: u16, y: u16) -> bool {
-        self.layout[x as usize][y as usize].is_occupied()
+    #[test]
+    fn audit_seating() {
+        let mut aircraft = Aircraft::new(3, 2);
+        aircraft.layout[0][0] = Tile::seat();
+        aircraft.layout[2][0] = Tile::seat();
+        let mut passenger = Person::new("Dave");
+        passenger.target_seat(2, 0);
+        passenger.set_id(0);
+        aircraft.layout[0][0].occupy(passenger);
+        let mut passenger = Person::new("Bob");
+        passenger.target_seat(1, 1);
+        aircraft.layout[1][1].occupy(passenger);
+
+        // Passengers in the aisle aren't checked until they settle.
+        assert_eq!(aircraft.audit_seating(), vec![SeatMismatch {
+            id: Some(0),
+            name: "Dave".to_string(),
+            expected: Some((2, 0)),
+            actual: (0, 0),
+        }]);
     }
 
-    pub fn check_if_allowing(&self, x: u16, y: u16) -> bool {
-        self.layout[x as usize][y as usize].is_allowing()
+    #[test]
+    fn side_by_side() {
+        let build = |seat: (u16, u16)| {
+            let mut aircraft = Aircraft::new(3, 3);
+            aircraft.layout[0][0] = Tile::seat();
+            aircraft.layout[0][1] = Tile::seat();
+            aircraft.layout[1][2] = Tile::entrance();
+            aircraft.layout[0][2] = Tile::none();
+            aircraft.layout[2][2] = Tile::none();
+            let mut passenger = Person::new("Dave");
+            passenger.target_seat(seat.0, seat.1);
+            aircraft.add_passenger(passenger);
+            aircraft
+        };
+        let (mut near, mut far) = (build((0, 1)), build((0, 0)));
+
+        let frame = step_side_by_side(&mut near, &mut far);
+        let lines: Vec<&str> = frame.lines().collect();
+        assert_eq!(lines.len(), 5);
+        assert_eq!(lines[0], "tick 1 seated 0/1    tick 1 seated 0/1");
+        assert_eq!(lines[1], "   012                  012");
+        assert_eq!(lines[4], "  2?@?                 2?@?");
+
+        for _ in 0..3 {
+            step_side_by_side(&mut near, &mut far);
+        }
+        // The nearer seat is filled first, and neither aircraft is updated
+        // once complete.
+        let frame = step_side_by_side(&mut near, &mut far);
+        assert!(frame.starts_with("tick 3 seated 1/1    tick 4 seated 1/1"));
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    fn forbid_backtracking() {
+        let overshoot = |forbid: bool| {
+            let mut aircraft = Aircraft::new(3, 6);
+            for y in 0..5 {
+                aircraft.layout[0][y] = Tile::seat();
+                aircraft.layout[2][y] = Tile::seat();
+            }
+            aircraft.layout[0][5] = Tile::none();
+            aircraft.layout[2][5] = Tile::none();
+            aircraft.layout[1][5] = Tile::entrance();
+            aircraft.set_forbid_backtracking(forbid);
+
+            // A passenger that has gone two rows too far from the door.
+            let mut passenger = Person::new("Dave");
+            passenger.target_seat(0, 3);
+            passenger.set_door(1, 5);
+            aircraft.layout[1][1].occupy(passenger);
+            aircraft.reset();
+            for _ in 0..5 {
+                aircraft.step();
+            }
+            aircraft
+        };
+
+        let aircraft = overshoot(true);
+        assert!(aircraft.layout[1][1].is_occupied());
+        assert!(!aircraft.layout[0][3].is_occupied());
+
+        let aircraft = overshoot(false);
+        assert!(aircraft.layout[0][3].is_occupied());
+    }
 
     #[test]
-    fn clear() {
-        let mut aircraft = Aircraft {
-            size: (5, 5),
-            layout: Vec::<Vec<Tile>>::new(),
-            passengers: Vec::<Person>::new(),
-            targeted_seats: Vec::<(u16,u16)>::new(),
+    fn jet_bridge() {
+        let build = |bridge: u16| {
+            let mut aircraft = Aircraft::new(3, 4);
+            for y in 0..3 {
+                aircraft.layout[0][y] = Tile::seat();
+                aircraft.layout[2][y] = Tile::seat();
+            }
+            aircraft.layout[0][3] = Tile::none();
+            aircraft.layout[2][3] = Tile::none();
+            aircraft.layout[1][3] = Tile::entrance();
+            aircraft.set_jet_bridge_len(bridge).unwrap();
+            for y in 0..3 {
+                for x in &[0, 2] {
+                    let mut passenger = Person::new("Dave");
+                    passenger.target_seat(*x, y);
+                    aircraft.add_passenger(passenger);
+                }
+            }
+            aircraft
         };
 
-        aircraft.clear();
-        for i in 0..aircraft.size.0 {
-            for j in 0..aircraft.size.1 {
-                assert_eq!(aircraft.layout[i as usize][j as usize]
-                               .get_variant(),
-                           Variant::Aisle);
+        let direct = build(0).run_to_completion().unwrap().ticks;
+        assert_eq!(build(4).run_to_completion().unwrap().ticks, direct + 4);
+
+        // Passengers back up along the bridge while the door is shut.
+        let mut aircraft = build(4);
+        aircraft.set_door_window(1, 3, 10, None).unwrap();
+        aircraft.run_for(8);
+        assert_eq!(aircraft.bridge_occupancy(), 4);
+        assert!(aircraft.set_jet_bridge_len(2).is_err());
+        assert!(aircraft.run_to_completion().is_ok());
+        assert_eq!(aircraft.bridge_occupancy(), 0);
+    }
+
+    #[test]
+    fn passenger_census() {
+        let mut aircraft = Aircraft::new(5, 6);
+        for y in 0..5 {
+            for x in &[0, 1, 3, 4] {
+                aircraft.layout[*x][y] = Tile::seat();
+            }
+        }
+        for x in &[0, 1, 3, 4] {
+            aircraft.layout[*x][5] = Tile::none();
+        }
+        aircraft.layout[2][5] = Tile::entrance();
+        aircraft.set_jet_bridge_len(2).unwrap();
+        for y in 0..5 {
+            for x in &[1, 0, 3, 4] {
+                let mut passenger = Person::new("Dave");
+                passenger.target_seat(*x, y);
+                passenger.set_baggage(true);
+                aircraft.add_passenger(passenger);
             }
         }
+
+        assert_eq!(aircraft.passenger_census(), 20);
+        while !aircraft.is_complete() {
+            aircraft.step();
+            assert_eq!(aircraft.passenger_census(), 20);
+        }
     }
 
     #[test]
-    fn constructor() {
-        let aircraft = Aircraft::new(10, 10);
-        assert_eq!(aircraft.layout.len(), 10);
-        assert_eq!(aircraft.size, (10,10));
-        for i in 0..aircraft.size.0 {
-            for j in 0..aircraft.size.1 {
-                println!("Testing aircraft[{}][{}]", i, j);
-                assert_eq!(aircraft.layout[i as usize][j as usize]
-                               .get_variant(),
-                           Variant::Aisle);
+    fn stall_watchdog() {
+        let mut aircraft = Aircraft::new(3, 5);
+        for x in 0..3 {
+            for y in 0..5 {
+                aircraft.layout[x][y] = Tile::none();
             }
         }
+        for y in 1..4 {
+            aircraft.layout[1][y] = Tile::aisle();
+        }
+        aircraft.layout[1][4] = Tile::entrance();
+        for seat in &[(2, 0), (0, 1), (0, 2)] {
+            aircraft.layout[seat.0][seat.1] = Tile::seat();
+        }
+        // The passenger boarding last has a seat that can't be reached.
+        for seat in &[(2, 0), (0, 1), (0, 2)] {
+            let mut passenger = Person::new("Dave");
+            passenger.target_seat(seat.0, seat.1);
+            aircraft.add_passenger(passenger);
+        }
+
+        match aircraft.run_until_stalled(10) {
+            Err(RunError::Stalled { stats, stalled }) => {
+                assert!(stats.ticks < MAX_ITERATIONS);
+                assert_eq!(stalled.len(), 1);
+                assert_eq!(stats.critical_path.last().unwrap(), "Dave");
+                let (id, position) = stalled[0];
+                assert_eq!(id, 0);
+                let (x, y) = position.unwrap();
+                assert_eq!(aircraft.layout[x as usize][y as usize]
+                           .get_occupier().unwrap().get_id(), Some(0));
+            },
+            Err(e) => panic!("Unexpected error: {}", e),
+            Ok(_) => panic!("Passenger made it to seat despite obstacles"),
+        }
     }
 
     #[test]
-    fn add_passenger() {
-        println!("Testing add_passenger()");
-        let mut aircraft = Aircraft::new(6, 9);
-        let passenger = Person::new("Dave");
-        aircraft.add_passenger(passenger);
-        assert_eq!(aircraft.passengers.get(0).unwrap().get_name(), "Dave");
-        assert_eq!(aircraft.passengers.get(0).unwrap().get_seat(), None);
+    fn diagnose_conflict_and_unreachable() {
+        let mut conflict = Aircraft::new(3, 3);
+        conflict.layout[1][2] = Tile::entrance();
+        conflict.layout[0][0] = Tile::seat();
+        for name in &["Dave", "Jim"] {
+            let mut passenger = Person::new(name);
+            passenger.target_seat(0, 0);
+            conflict.add_passenger(passenger);
+        }
+        assert_eq!(conflict.diagnose_incomplete(),
+                   IncompleteReason::SeatConflict(vec![((0, 0), vec![0, 1])]));
+
+        let mut unreachable = infeasible_aircraft();
+        assert!(unreachable.run_to_completion().is_err());
+        assert_eq!(unreachable.diagnose_incomplete(),
+                   IncompleteReason::UnreachableSeats(vec![0]));
     }
 
     #[test]
-    fn get_size() {
-        let aircraft = Aircraft::new(5, 6);
-        assert_eq!(aircraft.get_size(), (5, 6));
+    fn diagnose_deadlock_and_stall() {
+        let mut aircraft = Aircraft::new(3, 4);
+        for y in 0..3 {
+            aircraft.layout[0][y] = Tile::seat();
+            aircraft.layout[2][y] = Tile::seat();
+        }
+        aircraft.layout[0][3] = Tile::none();
+        aircraft.layout[1][3] = Tile::entrance();
+        aircraft.layout[2][3] = Tile::none();
+        for (seat, name) in &[((0, 2), "Dave"), ((0, 0), "Jim")] {
+            let mut passenger = Person::new(name);
+            passenger.target_seat(seat.0, seat.1);
+            aircraft.add_passenger(passenger);
+        }
+        assert_eq!(aircraft.diagnose_incomplete(),
+                   IncompleteReason::Stalled(vec![0, 1]));
+
+        // Standing face to face in the aisle, neither can get past the other.
+        aircraft.passengers.clear();
+        let standing = [(0, (1, 1), (0, 2)), (1, (1, 2), (0, 0))];
+        for (id, position, seat) in &standing {
+            let mut passenger = Person::new("Dave");
+            passenger.set_id(*id);
+            passenger.target_seat(seat.0, seat.1);
+            aircraft.layout[position.0][position.1].occupy(passenger);
+        }
+        assert_eq!(aircraft.diagnose_incomplete(),
+                   IncompleteReason::Deadlock(vec![0, 1]));
+
+        // The passenger headed for the nearer seat is let past.
+        aircraft.set_yield_policy(YieldPolicy::CloserSeatFirst);
+        assert_eq!(aircraft.diagnose_incomplete(),
+                   IncompleteReason::Stalled(vec![0, 1]));
+        aircraft.set_yield_policy(YieldPolicy::None);
+
+        aircraft.layout[1][1].free();
+        assert_eq!(aircraft.diagnose_incomplete(),
+                   IncompleteReason::Stalled(vec![0, 1]));
     }
 
     #[test]
-    fn update() {
-        let mut aircraft = Aircraft::new(10, 10);
-        let mut passenger = Person::new("Dave");
-        passenger.target_seat(1,1);
+    fn row_vacancies() {
+        let mut aircraft = Aircraft::new(5, 3);
+        for x in &[0, 1, 3, 4] {
+            aircraft.layout[*x][1] = Tile::seat();
+        }
+        for x in &[1, 4] {
+            aircraft.layout[*x][1].occupy(Person::new("Dave"));
+        }
 
-        println!("Adding passenger");
-        aircraft.add_passenger(passenger);
-        assert_eq!(aircraft.passengers.len(), 1,
-                   "Unwanted passenger at initialisation");
+        assert_eq!(aircraft.row_vacancies(1), vec![(0, 1), (3, 1)]);
+        assert!(aircraft.row_vacancies(0).is_empty());
+    }
 
-        println!("Adding entrance and updating");
-        aircraft.layout[2][2] = Tile::entrance();
-        // aircraft.layout[2][3] = Tile::aisle();
-        aircraft.ascii_render();
-        aircraft.update();
-        aircraft.ascii_render();
-        assert_eq!(aircraft.passengers.len(), 0,
-                   "Passenger was not removed from passengers array");
-        assert!(aircraft.layout[2][2].is_occupied(),
-                "Passenger was not added to entrance tile");
+    #[test]
+    fn render_to() {
+        let mut aircraft = Aircraft::new(3, 2);
+        aircraft.layout[0][0] = Tile::seat();
+        aircraft.layout[1][1] = Tile::entrance();
+        aircraft.layout[2][0].occupy(Person::new("Dave"));
 
-        println!("Updating");
-        aircraft.update();
-        aircraft.ascii_render();
-        println!("Updating");
-        aircraft.update();
-        aircraft.ascii_render();
-        aircraft.update();
-        aircraft.ascii_render();
-        aircraft.update();
-        aircraft.ascii_render();
-        assert_eq!(aircraft.layout[2][2].is_occupied(), false,
-                   "Passenger did not move from entrance");
-        assert!(aircraft.layout[1][1].is_occupied(),
-                "Passenger did not make it to target seat");
+        let mut buffer = Vec::<u8>::new();
+        aircraft.render_to(&mut buffer).unwrap();
+
+        assert_eq!(buffer, b"   012\n  0#*@\n  1***\n".to_vec());
+    }
+
+    #[test]
+    fn custom_glyphs() {
+        let mut aircraft = Aircraft::new(3, 2);
+        aircraft.layout[0][0] = Tile::seat();
+        aircraft.layout[1][1] = Tile::entrance();
+        aircraft.layout[2][1] = Tile::none();
+        aircraft.layout[2][0].occupy(Person::new("Dave"));
+        aircraft.set_glyphs(GlyphSet {
+            occupied: '☺',
+            aisle: '.',
+            seat: 'h',
+            entrance: 'D',
+            none: ' ',
+        });
+
+        assert_eq!(aircraft.render_ascii(), "   012\n  0h.☺\n  1.D \n");
+    }
+
+    #[test]
+    fn centre_block_interior_seat() {
+        // A block of four seats between two aisles, joined at the door by a
+        // cross-aisle.
+        let mut aircraft = Aircraft::new(6, 4);
+        for x in 1..5 {
+            for y in 0..3 {
+                aircraft.layout[x][y] = Tile::none();
+            }
+            aircraft.layout[x][1] = Tile::seat();
+        }
+        aircraft.layout[0][3] = Tile::entrance();
+        // Everyone else is seated by the time the passenger boarding last
+        // comes to climb past the aisle seat to the interior seat beside it.
+        for seat in &[(2, 1), (1, 1), (3, 1), (4, 1)] {
+            let mut passenger = Person::new("Dave");
+            passenger.target_seat(seat.0, seat.1);
+            aircraft.add_passenger(passenger);
+        }
+
+        aircraft.set_event_log(true);
+        assert!(aircraft.run_to_completion().is_ok());
+        assert!(aircraft.audit_seating().is_empty());
+
+        let path: Vec<(u16, u16)> = aircraft.take_events().iter()
+            .filter_map(|e| match *e {
+                SimEvent::Moved { id: Some(0), to, .. } => Some(to),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(path[path.len() - 2..], [(1, 1), (2, 1)]);
+        // Climbing past the seated neighbour held it up.
+        let stats = &aircraft.get_stats().passengers[0];
+        assert!(stats.seated_tick.unwrap() - stats.boarded_tick.unwrap()
+                > path.len() as u16);
+    }
+
+    #[test]
+    #[should_panic(expected = "unseated after 1000 updates: 0 (Dave)")]
+    fn panic_on_timeout() {
+        let mut aircraft = infeasible_aircraft();
+        aircraft.set_panic_on_timeout(true);
+
+        let _ = aircraft.run_to_completion();
+    }
+
+    #[test]
+    fn reachable_now() {
+        let mut aircraft = Aircraft::new(3, 4);
+        for y in 0..3 {
+            aircraft.layout[0][y] = Tile::seat();
+            aircraft.layout[2][y] = Tile::seat();
+        }
+        aircraft.layout[1][3] = Tile::entrance();
+        let (door, seat) = ((1, 3), (0, 0));
+        assert!(aircraft.is_reachable_now(door, seat));
+
+        // A passenger can squeeze past one sitting in the way, but not past
+        // one standing in the aisle.
+        aircraft.layout[0][0].occupy(Person::new("Dave"));
+        assert!(aircraft.is_reachable_now(door, seat));
+        aircraft.layout[1][1].occupy(Person::new("Dave"));
+        assert!(!aircraft.is_reachable_now(door, seat));
+        assert!(aircraft.is_reachable_now(door, (2, 2)));
+
+        aircraft.layout[1][1].free();
+        assert!(aircraft.is_reachable_now(door, seat));
+    }
+
+    #[test]
+    fn beam_boarding() {
+        let board = |beam: bool| {
+            let mut aircraft = Aircraft::new(7, 11);
+            for x in 0..7 {
+                for y in 0..10 {
+                    if x != 3 {
+                        aircraft.layout[x][y] = Tile::seat();
+                    }
+                }
+                aircraft.layout[x][10] = Tile::none();
+            }
+            aircraft.layout[3][10] = Tile::entrance();
+            for seat in aircraft.seats_sorted_by(SeatSort::FrontToBack) {
+                let mut passenger = Person::new("Dave");
+                passenger.target_seat(seat.0, seat.1);
+                passenger.set_baggage(true);
+                aircraft.add_passenger(passenger);
+            }
+            aircraft.set_beam_boarding(beam);
+            aircraft
+        };
+
+        let mut beamed = board(true);
+        beamed.step();
+        // Every aisle tile is filled, with nobody sharing.
+        assert_eq!(beamed.get_stats().boarded, 11);
+        assert_eq!(beamed.passenger_census(), 60);
+        assert!(beamed.layout.iter().flatten().all(|t| !t.is_allowing()));
+
+        let beamed_ticks = beamed.run_to_completion().unwrap().ticks;
+        let sequential_ticks = board(false).run_to_completion().unwrap().ticks;
+        assert!(beamed_ticks < sequential_ticks);
+    }
+
+    #[test]
+    fn max_door_queue() {
+        let board = |baggage: bool| {
+            let mut aircraft = Aircraft::new(7, 11);
+            for x in 0..7 {
+                for y in 0..10 {
+                    if x != 3 {
+                        aircraft.layout[x][y] = Tile::seat();
+                    }
+                }
+                aircraft.layout[x][10] = Tile::none();
+            }
+            aircraft.layout[3][10] = Tile::entrance();
+            aircraft.set_jet_bridge_len(5).unwrap();
+            // Passengers board from the far end of the cabin, so only those
+            // stopping to stow hold up the ones behind them.
+            for seat in aircraft.seats_sorted_by(SeatSort::BackToFront) {
+                let mut passenger = Person::new("Dave");
+                passenger.target_seat(seat.0, seat.1);
+                passenger.set_baggage(baggage);
+                passenger.set_stow_delay(6);
+                aircraft.add_passenger(passenger);
+            }
+            aircraft.run_to_completion().unwrap();
+            aircraft.get_stats().max_door_queue
+        };
+
+        let slow = board(true);
+        let quick = board(false);
+        assert!(slow >= 10, "{}", slow);
+        assert!(quick <= 3, "{}", quick);
+    }
 
-        println!("Updating once too much");
-        aircraft.update();
-        aircraft.ascii_render();
-        assert!(aircraft.layout[1][1].is_occupied(),
-                "Passenger shouldn't have moved from their seat");
+    #[test]
+    fn reassigned_passenger() {
+        let ticks_to_settle = |reassigned: bool| {
+            let mut aircraft = Aircraft::new(3, 4);
+            aircraft.layout[1][3] = Tile::entrance();
+            aircraft.layout[0][3] = Tile::none();
+            aircraft.layout[2][3] = Tile::none();
+            aircraft.layout[0][0] = Tile::seat();
+            let mut passenger = Person::new("Dave");
+            passenger.target_seat(0, 0);
+            passenger.set_reassigned(reassigned);
+            aircraft.add_passenger(passenger);
+
+            let mut ticks = 0;
+            while aircraft.layout[0][0].get_occupier()
+                .filter(|p| p.is_settled()).is_none() {
+                aircraft.update();
+                ticks += 1;
+            }
+            ticks
+        };
+
+        assert_eq!(ticks_to_settle(true), ticks_to_settle(false) + 1);
     }
 
     #[test]
-    fn impassable_terrain() {
-        let mut aircraft = Aircraft::new(3,3);
-        let mut passenger = Person::new("Dave");
-        passenger.target_seat(2,2);
-        aircraft.layout[0][0] = Tile::entrance();
-        for coords in &[(0, 1), (1, 1), (1, 0)] {
-            aircraft.layout[coords.0][coords.1] = Tile::none();
+    fn move_filter() {
+        let mut aircraft = Aircraft::new(3, 6);
+        aircraft.layout[1][5] = Tile::entrance();
+        aircraft.layout[0][5] = Tile::none();
+        aircraft.layout[2][5] = Tile::none();
+        for y in 0..5 {
+            aircraft.layout[0][y] = Tile::seat();
+            aircraft.layout[2][y] = Tile::seat();
         }
-        aircraft.add_passenger(passenger);
-
-        for i in 0..100 {
-            aircraft.update();
+        // The frozen passenger boards last, so doesn't hold anyone else up.
+        for (name, seat) in &[("Frozen", (0, 3)), ("Dave", (2, 0)),
+                              ("Dave", (0, 1))] {
+            let mut passenger = Person::new(name);
+            passenger.target_seat(seat.0, seat.1);
+            aircraft.add_passenger(passenger);
         }
+        aircraft.set_move_filter(Some(Box::new(|view, chosen| {
+            if view.name == "Frozen" {
+                Behaviour::Wait
+            } else {
+                chosen
+            }
+        })));
+        aircraft.set_event_log(true);
+        aircraft.run_for(30);
 
-        assert_eq!(aircraft.layout[2][2].is_occupied(), false,
-                   "Passenger made it to seat despite obstacles");
+        let movers: Vec<Option<usize>> = aircraft.take_events().iter()
+            .filter_map(|e| match *e {
+                SimEvent::Moved { id, .. } => Some(id),
+                _ => None,
+            })
+            .collect();
+        assert!(!movers.contains(&Some(0)));
+        assert!(movers.contains(&Some(1)) && movers.contains(&Some(2)));
+        assert_eq!(aircraft.unseated(), vec![0]);
+        assert!(aircraft.layout[1][5].is_occupied());
     }
 
     #[test]
-    fn aisle_ignoring() {
-        let mut aircraft = Aircraft::new(5,5);
-        let mut passenger = Person::new("Dave");
-        passenger.target_seat(4, 0);
-        aircraft.layout[2][4] = Tile::entrance();
-        for i in 0..5 {
-            aircraft.layout[0][i] = Tile::seat();
-            aircraft.layout[1][i] = Tile::seat();
-            aircraft.layout[3][i] = Tile::seat();
-            aircraft.layout[4][i] = Tile::seat();
-        }
-        aircraft.add_passenger(passenger);
+    fn total_aisle_person_ticks() {
+        let board = |criteria: SeatSort| {
+            let mut aircraft = Aircraft::new(7, 11);
+            for x in 0..7 {
+                for y in 0..10 {
+                    if x != 3 {
+                        aircraft.layout[x][y] = Tile::seat();
+                    }
+                }
+                aircraft.layout[x][10] = Tile::none();
+            }
+            aircraft.layout[3][10] = Tile::entrance();
+            for seat in aircraft.seats_sorted_by(criteria) {
+                let mut passenger = Person::new("Dave");
+                passenger.target_seat(seat.0, seat.1);
+                passenger.set_baggage(true);
+                aircraft.add_passenger(passenger);
+            }
+            aircraft.run_to_completion().unwrap();
+            let stats = aircraft.get_stats().clone();
+            let per_passenger: u32 = stats.passengers.iter()
+                .map(|p| p.aisle_ticks as u32)
+                .sum();
+            assert_eq!(stats.total_aisle_person_ticks, per_passenger);
+            stats.total_aisle_person_ticks
+        };
 
-        for _ in 0..10 {
-            aircraft.ascii_render();
-            println!("========================");
-            aircraft.update();
-        }
-        assert!(aircraft.layout[4][0].is_occupied(),
-                "Passenger did not make it to seat");
+        // The last passenger added boards first, so the first of these boards
+        // the rows nearest the door first, blocking everyone behind.
+        let congested = board(SeatSort::FrontToBack);
+        let uncongested = board(SeatSort::BackToFront);
+        assert!(congested > uncongested);
     }
 
     #[test]
-    fn advanced_aisle_ignoring() {
-        let mut aircraft = Aircraft::new(3,3);
+    fn try_add_passenger() {
+        let mut aircraft = Aircraft::new(3, 3);
+        aircraft.layout[0][0] = Tile::seat();
+        aircraft.layout[2][0] = Tile::none();
         aircraft.layout[1][2] = Tile::entrance();
-        for i in 0..3 {
-            for j in &[0, 2] {
-                let mut passenger = Person::new("DEFAULT");
-                passenger.target_seat(*j, i);
-                aircraft.layout[*j as usize][i as usize] = Tile::seat();
+        let passenger = |seat: Option<(u16, u16)>| {
+            let mut passenger = Person::new("Dave");
+            if let Some((x, y)) = seat {
+                passenger.target_seat(x, y);
+            }
+            passenger
+        };
+
+        assert_eq!(aircraft.try_add_passenger(passenger(Some((1, 1)))),
+                   Err("Passenger's seat is an aisle"));
+        assert!(aircraft.try_add_passenger(passenger(Some((2, 0)))).is_err());
+        assert!(aircraft.try_add_passenger(passenger(Some((3, 0)))).is_err());
+        assert!(aircraft.try_add_passenger(passenger(None)).is_err());
+        assert_eq!(aircraft.get_stats().passengers.len(), 0);
+
+        assert!(aircraft.try_add_passenger(passenger(Some((0, 0)))).is_ok());
+        assert!(aircraft.try_add_passenger(passenger(Some((1, 2)))).is_ok());
+        assert_eq!(aircraft.get_stats().passengers.len(), 2);
+    }
+
+    #[test]
+    fn last_boarded() {
+        let mut aircraft = Aircraft::new(3, 4);
+        for y in 0..3 {
+            aircraft.layout[0][y] = Tile::seat();
+            aircraft.layout[2][y] = Tile::seat();
+        }
+        aircraft.layout[0][3] = Tile::none();
+        aircraft.layout[2][3] = Tile::none();
+        aircraft.layout[1][3] = Tile::entrance();
+        aircraft.set_jet_bridge_len(3).unwrap();
+        for y in 0..3 {
+            for x in &[0, 2] {
+                let mut passenger = Person::new("Dave");
+                passenger.target_seat(*x, y);
+                passenger.set_baggage(true);
                 aircraft.add_passenger(passenger);
             }
         }
-        // let mut passenger = Person::new("DAVE");
-        // passenger.target_seat(2, 0);
-        // aircraft.add_passenger(passenger);
-        
-        for _ in 0..10 {
-            aircraft.ascii_render();
-            println!("========================");
-            aircraft.update();
+        let calls = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorded = calls.clone();
+        aircraft.set_on_last_boarded(Some(Box::new(move |tick| {
+            recorded.lock().unwrap().push(tick);
+        })));
+
+        let total = aircraft.run_to_completion().unwrap().ticks;
+        let last_boarded = aircraft.get_stats().last_boarded_tick.unwrap();
+        assert_eq!(*calls.lock().unwrap(), vec![last_boarded]);
+        assert!(last_boarded <= total);
+        let boarded_ticks: Vec<u16> = aircraft.get_stats().passengers.iter()
+            .map(|p| p.boarded_tick.unwrap())
+            .collect();
+        assert_eq!(boarded_ticks.iter().max(), Some(&last_boarded));
+    }
+
+    #[test]
+    fn waiting_queue() {
+        let mut aircraft = Aircraft::new(3, 4);
+        aircraft.layout[1][3] = Tile::entrance();
+        assert!(aircraft.next_to_board().is_none());
+        for name in &["Dave", "Jim", "Sue"] {
+            let mut passenger = Person::new(name);
+            passenger.target_seat(1, 0);
+            aircraft.add_passenger(passenger);
         }
+        let names = |aircraft: &Aircraft| aircraft.waiting_queue().iter()
+            .map(|p| p.get_name())
+            .collect::<Vec<String>>();
+        let next = |aircraft: &Aircraft| aircraft.next_to_board().unwrap()
+            .get_name();
 
-        for i in 0..3 {
-            for j in &[0, 2] {
-                assert!(aircraft.layout[*j][i].is_occupied(),
-                        "Seat {},{} was not occupied", *j, i);
+        assert_eq!(names(&aircraft), vec!["Dave", "Jim", "Sue"]);
+        assert_eq!(next(&aircraft), "Sue");
+        aircraft.set_boarding_order(BoardingOrder::AsAdded);
+        assert_eq!(next(&aircraft), "Dave");
+        aircraft.set_boarding_order(BoardingOrder::Custom(vec![1, 2]));
+        assert_eq!(next(&aircraft), "Jim");
+
+        aircraft.update();
+        assert_eq!(names(&aircraft), vec!["Dave", "Sue"]);
+        assert_eq!(next(&aircraft), "Sue");
+        aircraft.update();
+        assert_eq!(next(&aircraft), "Dave");
+    }
+
+    #[test]
+    fn family_seats_window_first() {
+        let build = |group: Option<u16>| {
+            let mut aircraft = Aircraft::new(4, 4);
+            for x in 0..3 {
+                for y in 0..3 {
+                    aircraft.layout[x][y] = Tile::seat();
+                }
+                aircraft.layout[x][3] = Tile::none();
             }
-        }
+            aircraft.layout[3][3] = Tile::entrance();
+            aircraft.set_boarding_order(BoardingOrder::AsAdded);
+            // The family arrives at the gate aisle seat first.
+            for seat in &[(2, 1), (1, 1), (0, 1)] {
+                let mut passenger = Person::new("Family");
+                passenger.target_seat(seat.0, seat.1);
+                passenger.set_baggage(true);
+                passenger.set_group(group);
+                aircraft.add_passenger(passenger);
+            }
+            aircraft
+        };
+
+        let mut family = build(Some(0));
+        assert_eq!(family.next_to_board().unwrap().get_seat(), Some((0, 1)));
+        let together = family.run_to_completion().unwrap().ticks;
+        assert!(family.audit_seating().is_empty());
+        assert_eq!(family.get_stats().interferences, 0);
+
+        let mut strangers = build(None);
+        let apart = strangers.run_to_completion().unwrap().ticks;
+        assert!(strangers.get_stats().interferences > 0);
+        assert!(together <= apart);
     }
 
     #[test]
-    fn aisle_shifting() {
-        let mut aircraft = Aircraft::new(5,5);
+    fn seat_deadline() {
+        let build = |deadline| {
+            let mut aircraft = seat_pair_cabin(3);
+            for x in 0..2 {
+                aircraft.layout[x][1] = Tile::seat();
+            }
+            // The window seat is already filled, with no room to squeeze in.
+            for _ in 0..2 {
+                let mut squatter = Person::new("Squatter");
+                squatter.target_seat(0, 0);
+                if aircraft.layout[0][0].is_occupied() {
+                    aircraft.layout[0][0].pass_in(squatter);
+                } else {
+                    aircraft.layout[0][0].occupy(squatter);
+                }
+            }
+            let mut passenger = Person::new("Passenger");
+            passenger.target_seat(0, 0);
+            passenger.set_seat_deadline(deadline);
+            aircraft.add_passenger(passenger);
+            aircraft
+        };
 
-        aircraft.layout[2][4] = Tile::entrance();
-        for i in 0..5 {
-            aircraft.layout[0][i] = Tile::seat();
-            aircraft.layout[1][i] = Tile::seat();
-            aircraft.layout[3][i] = Tile::seat();
-            aircraft.layout[4][i] = Tile::seat();
-        }
+        assert!(build(None).run_to_completion().is_err());
 
-        let mut passenger = Person::new("DEFAULT");
-        passenger.target_seat(0,0);
-        aircraft.add_passenger(passenger);
+        // A passenger's own deadline takes the place of the aircraft's.
+        let mut aircraft = build(Some(15));
+        aircraft.set_seat_deadline(Some(10));
+        aircraft.run_to_completion().unwrap();
+        let stats = &aircraft.get_stats().passengers[0];
+        assert!(stats.reassigned_tick.unwrap() >= 15);
 
-        let mut passenger = Person::new("DEFAULT");
-        passenger.target_seat(1,0);
-        aircraft.add_passenger(passenger);
+        let mut aircraft = build(Some(10));
+        aircraft.run_to_completion().unwrap();
+        assert_eq!(aircraft.get_stats().passengers[0].seat, Some((1, 0)));
 
-        for _ in 0..10 {
-            println!("========================");
-            aircraft.update();
-            aircraft.ascii_render();
+        let mut aircraft = build(None);
+        aircraft.set_seat_deadline(Some(10));
+        aircraft.set_event_log(true);
+        aircraft.run_to_completion().unwrap();
+        assert!(aircraft.audit_seating().is_empty());
+        let stats = &aircraft.get_stats().passengers[0];
+        assert_eq!(stats.seat, Some((1, 0)));
+        assert_eq!(stats.reassigned_from, Some((0, 0)));
+        assert!(stats.reassigned_tick.unwrap() >= 10);
+        assert!(aircraft.take_events().iter().any(|e| match *e {
+            SimEvent::Reassigned { id, from, to, .. } => {
+                id == Some(0) && from == (0, 0) && to == (1, 0)
+            },
+            _ => false,
+        }));
+    }
+
+    #[test]
+    fn stats_summary() {
+        let mut aircraft = seat_pair_cabin(3);
+        for x in 0..2 {
+            let mut passenger = Person::new("Passenger");
+            passenger.target_seat(x, 0);
+            aircraft.add_passenger(passenger);
         }
+        let ticks = aircraft.run_to_completion().unwrap().ticks;
 
-        assert!(aircraft.layout[0][0].is_occupied());
-        assert!(aircraft.layout[1][0].is_occupied());
+        let stats = aircraft.get_stats();
+        let summary = stats.to_string();
+        assert!(summary.contains(&format!("Updates: {}", stats.ticks)));
+        assert!(summary.contains(&format!("Complete on update: {}", ticks)));
+        assert!(summary.contains("Seated: 2 of 2"));
+        assert!(summary.contains(&format!("Interferences: {}",
+                                          stats.interferences)));
+        assert!(summary.contains(&format!("Efficiency: {:.1}%",
+                                          stats.efficiency * 100.0)));
+        assert!(summary.contains("Aisle use: "));
     }
 
     #[test]
-    fn bad_order() {
-        let mut aircraft = Aircraft::new(5,5);
-        
-        for i in 0..5 {
-            for j in &[0,1,3,4] {
-                aircraft.layout[*j][i] = Tile::seat();
+    fn door_interval() {
+        // Two aisles, each with a door at its head, the second admitting
+        // passengers every `interval` updates.
+        let board = |interval: u16| {
+            let mut aircraft = Aircraft::new(6,7);
+            for x in 0..6 {
+                aircraft.layout[x][0] = Tile::none();
+                if x != 1 && x != 4 {
+                    for y in 1..7 {
+                        aircraft.layout[x][y] = Tile::seat();
+                        let mut passenger = Person::new("DEFAULT");
+                        passenger.target_seat(x as u16, y as u16);
+                        aircraft.add_passenger(passenger);
+                    }
+                }
             }
-        }
-        aircraft.layout[2][4] = Tile::entrance();
+            aircraft.layout[1][0] = Tile::entrance();
+            aircraft.layout[4][0] = Tile::entrance();
+            assert!(aircraft.set_door_interval(0, 1, interval).is_err());
+            assert!(aircraft.set_door_interval(4, 0, 0).is_err());
+            aircraft.set_door_interval(4, 0, interval).unwrap();
+            aircraft.set_event_log(true);
+            aircraft.run_to_completion().unwrap();
 
-        for i in 0..3 {
-            for j in &[0,1,4,3] {
-                let mut passenger = Person::new("DEFAULT");
-                passenger.target_seat(*j, i);
-                aircraft.add_passenger(passenger);
+            let mut narrow = Vec::<u16>::new();
+            let mut wide = 0;
+            for event in aircraft.take_events() {
+                if let SimEvent::Boarded { tick, position, .. } = event {
+                    match position {
+                        (4, 0) => narrow.push(tick),
+                        _ => wide += 1,
+                    }
+                }
             }
-        }
+            (wide, narrow)
+        };
 
+        let (wide, narrow) = board(1);
+        assert_eq!(wide, narrow.len());
 
-        for _ in 0..20 {
-            aircraft.ascii_render();
-            println!("==========");
-            aircraft.update();
-        }
+        let (wide, narrow) = board(3);
+        assert!(wide > narrow.len());
+        assert!(narrow.windows(2).all(|w| w[1] - w[0] >= 3));
 
-        // Check results
-        for i in 0..3 {
-            for j in &[0,1,4,3] {
-                assert!(aircraft.layout[*j][i].is_occupied());
-            }
-        }
+        // However long the interval, it can't overflow.
+        let (_, narrow) = board(u16::MAX);
+        assert_eq!(narrow.len(), 1);
     }
 
     #[test]
-    fn baggage() {
-        let mut aircraft = Aircraft::new(5,5);
-        
-        for i in 0..5 {
-            for j in &[0,1,3,4] {
-                aircraft.layout[*j][i] = Tile::seat();
-            }
+    fn delay_heatmap() {
+        let mut aircraft = single_aisle_cabin(5);
+        aircraft.set_boarding_order(BoardingOrder::AsAdded);
+        // The first passenger holds up the second while stowing its bag.
+        for seat in &[(0, 3), (0, 0)] {
+            let mut passenger = Person::new("DEFAULT");
+            passenger.target_seat(seat.0, seat.1);
+            passenger.set_baggage(true);
+            aircraft.add_passenger(passenger);
         }
-        aircraft.layout[2][4] = Tile::entrance();
-        
-        let mut person = Person::new("DEFAULT");
-        person.target_seat(0,0);
-        person.set_baggage(true);
-        aircraft.add_passenger(person);
+        aircraft.run_to_completion().unwrap();
 
-        for _ in 0..7 {
-            aircraft.update();
-            println!("==========");
-            aircraft.ascii_render();
+        let fill_ticks = aircraft.seat_fill_ticks();
+        assert!(fill_ticks[0][3].unwrap() < fill_ticks[0][0].unwrap());
+        assert_eq!(fill_ticks[2][3], None);
+        let delays = aircraft.delay_heatmap();
+        assert_eq!(delays[0][3], 0);
+        assert!(delays[0][0] > 0);
+        assert_eq!(delays[1][2], 0);
+    }
+
+    #[test]
+    fn shared_seat_owner_settles() {
+        let mut aircraft = seat_pair_cabin(2);
+        for seat in &[(0, 0), (1, 0)] {
+            let mut passenger = Person::new("DEFAULT");
+            passenger.target_seat(seat.0, seat.1);
+            aircraft.add_passenger(passenger);
         }
 
-        assert_eq!(aircraft.layout[0][0].is_occupied(), false);
+        // The window passenger is held up on its way past the aisle seat,
+        // with the aisle seat's owner squeezed in beside it.
+        let owner = aircraft.passengers.pop().unwrap();
+        let mut other = aircraft.passengers.pop().unwrap();
+        other.delay(10);
+        aircraft.layout[1][0].occupy(other);
+        aircraft.layout[1][0].pass_in(owner);
 
-        aircraft.update();
-        println!("==========");
-        aircraft.ascii_render();
+        aircraft.run_for(5);
+        let tile = &aircraft.layout[1][0];
+        assert_eq!(tile.get_occupier().unwrap().get_seat(), Some((1, 0)));
+        assert!(tile.get_occupier().unwrap().is_settled());
+        assert_eq!(tile.get_passer().unwrap().get_seat(), Some((0, 0)));
 
-        assert!(aircraft.layout[0][0].is_occupied());
+        aircraft.run_to_completion().unwrap();
+        assert!(aircraft.audit_seating().is_empty());
+        assert!(aircraft.layout[0][0].get_occupier().is_some());
+        assert!(!aircraft.layout[1][0].is_allowing());
     }
 
     #[test]
-    fn test_is_complete() {
-        let mut aircraft = Aircraft::new(5,5);
-        
-        for i in 0..5 {
-            for j in &[0,1,3,4] {
-                aircraft.layout[*j][i] = Tile::seat();
+    fn pair_interferences() {
+        let mut aircraft = Aircraft::new(4, 4);
+        for x in 0..3 {
+            for y in 0..3 {
+                aircraft.layout[x][y] = Tile::seat();
             }
+            aircraft.layout[x][3] = Tile::none();
         }
-        aircraft.layout[2][4] = Tile::entrance();
-
-        let mut passengers = Vec::<Person>::new();
-        for i in &[0,1,3,4] {
-            let mut passenger = Person::new("DEFAULT");
-            passenger.target_seat(*i, 0);
-            passengers.push(passenger);
-
-            let mut passenger = Person::new("DEFAULT");
-            passenger.target_seat(*i, 0);
+        aircraft.layout[3][3] = Tile::entrance();
+        aircraft.set_boarding_order(BoardingOrder::AsAdded);
+        // The window passengers board after the ones in the aisle seats, and
+        // pairs are told apart even when passengers share a name.
+        for (name, x, y) in &[("Aisle", 2, 1), ("Window", 0, 1),
+                              ("DEFAULT", 2, 0), ("DEFAULT", 0, 0)] {
+            let mut passenger = Person::new(name);
+            passenger.target_seat(*x, *y);
             aircraft.add_passenger(passenger);
         }
+        aircraft.run_to_completion().unwrap();
 
-        for _ in 0..15 {
-            aircraft.ascii_render();
-            aircraft.update();
-            println!("Status is: {}", aircraft.is_complete());
-        }
-        
-        assert!(aircraft.is_complete(),
-                "Not all passengers reached their seats");
+        let stats = aircraft.get_stats();
+        let pairs = &stats.pair_interferences;
+        assert!(pairs[&(1, 0)] > 0);
+        assert!(pairs[&(3, 2)] > 0);
+        assert!(!pairs.keys().any(|(a, _)| *a == 0 || *a == 2));
+        assert_eq!(pairs.values().sum::<u32>(), stats.interferences as u32);
+        assert!(stats.named_pair_interferences().iter()
+                .any(|(a, b, _)| a == "Window" && b == "Aisle"));
     }
 
     #[test]
-    fn advanced_stowing() {
-        let mut aircraft = Aircraft::new(5,5);
-        
-        for i in 0..5 {
-            for j in &[0,1,3,4] {
-                aircraft.layout[*j][i] = Tile::seat();
+    fn attendant_redistribution() {
+        let board = |interval: Option<u16>| {
+            let mut aircraft = Aircraft::new(5,5);
+            for i in 0..5 {
+                for j in &[0,1,3,4] {
+                    aircraft.layout[*j][i] = Tile::seat();
+                }
             }
-        }
-        aircraft.layout[2][4] = Tile::entrance();
+            aircraft.layout[2][4] = Tile::entrance();
+            aircraft.set_bin_capacity(Some(1));
+            aircraft.set_boarding_order(BoardingOrder::AsAdded);
+            aircraft.set_attendant_redistribution(interval);
 
-        for i in &[(0,0), (4,4)] {
-            let mut passenger = Person::new("DEFAULT");
-            passenger.target_seat(i.0, i.1);
+            // The first passenger fills the bins on row 3 before the rest
+            // board.
+            let mut early = Person::new("EARLY");
+            early.target_seat(3,3);
+            early.set_baggage(true);
+            aircraft.add_passenger(early);
+            aircraft.run_to_completion().unwrap();
+            let start = aircraft.get_stats().ticks;
+
+            for seat in &[(4,3), (1,3), (0,3)] {
+                let mut late = Person::new("LATE");
+                late.target_seat(seat.0, seat.1);
+                late.set_baggage(true);
+                aircraft.add_passenger(late);
+            }
+            let ticks = aircraft.run_to_completion().unwrap().ticks - start;
+            (ticks, aircraft.get_stats().gate_checked)
+        };
+
+        let (without, checked) = board(None);
+        assert_eq!(checked, 3);
+        let (with, checked) = board(Some(1));
+        assert!(checked < 3);
+        assert!(with < without);
+    }
+
+    #[test]
+    fn run_counters() {
+        let mut aircraft = seat_pair_cabin(3);
+        // The window passenger boards second, behind the one on the aisle.
+        for x in 0..2 {
+            let mut passenger = Person::new("Passenger");
+            passenger.target_seat(x, 0);
             passenger.set_baggage(true);
             aircraft.add_passenger(passenger);
         }
+        let stats = aircraft.run_to_completion().unwrap();
+        assert_eq!(stats, *aircraft.get_stats());
+        assert_eq!(stats.stows, 2);
+        assert_eq!(stats.interferences, 1);
+        assert_eq!(stats.shuffles, 1);
+        assert!(stats.waits > 0, "Nobody waited behind a stowing passenger");
+    }
 
-        for _ in 0..15 {
-            aircraft.ascii_render();
-            aircraft.update();
-            println!("Status is: {}", aircraft.is_complete());
+    #[test]
+    fn trajectory_table() {
+        let mut aircraft = single_aisle_cabin(4);
+        for y in 0..3 {
+            let mut passenger = Person::new(&y.to_string());
+            passenger.target_seat(0, y);
+            passenger.set_baggage(y == 1);
+            aircraft.add_passenger(passenger);
         }
-        
-        assert!(aircraft.is_complete(),
-                "Not all passengers reached their seats(?)");
+        aircraft.set_event_log(true);
+        aircraft.set_decision_trace(true);
+        let ticks = aircraft.run_to_completion().unwrap().ticks;
+        let events = aircraft.take_events();
+        let trace = aircraft.take_decision_trace();
+
+        let table = aircraft.trajectory_table(&events, &trace);
+        let stats = aircraft.get_stats();
+        let onboard: u16 = stats.passengers.iter()
+            .map(|p| ticks - p.boarded_tick.unwrap() + 1)
+            .sum();
+        assert_eq!(table.len(), onboard as usize);
+        for (id, passenger) in stats.passengers.iter().enumerate() {
+            let rows: Vec<&TrajectoryRow> = table.iter()
+                .filter(|row| row.id == id)
+                .collect();
+            assert!(rows.iter().all(|row| row.passenger == passenger.name));
+            let first = rows.first().unwrap();
+            assert_eq!(first.tick, passenger.boarded_tick.unwrap());
+            assert_eq!((first.x, first.y), (1, 3));
+            assert_eq!(first.variant, Variant::Entrance);
+            let last = rows.last().unwrap();
+            assert_eq!(last.tick, ticks);
+            assert_eq!(Some((last.x, last.y)), passenger.seat);
+            assert_eq!(last.variant, Variant::Seat);
+        }
+        assert!(table.iter().any(|row| row.behaviour == Some(Behaviour::Stow)));
+
+        let untraced = aircraft.trajectory_table(&events, &[]);
+        assert_eq!(untraced.len(), table.len());
+        assert!(untraced.iter().all(|row| row.behaviour.is_none()));
+    }
+
+    #[test]
+    fn vestibule_pooling() {
+        // A slow stower halfway down the aisle holds up everyone behind it.
+        let build = |pooling: bool| {
+            let mut aircraft = single_aisle_cabin(8);
+            aircraft.set_boarding_order(BoardingOrder::AsAdded);
+            aircraft.set_vestibule_pooling(pooling);
+            aircraft.set_event_log(true);
+
+            let mut stower = Person::new("STOWER");
+            stower.target_seat(0, 4);
+            stower.set_baggage(true);
+            stower.set_stow_delay(8);
+            aircraft.add_passenger(stower);
+            for y in 0..3 {
+                for x in &[0, 2] {
+                    let mut passenger = Person::new("FOLLOWER");
+                    passenger.target_seat(*x, y);
+                    aircraft.add_passenger(passenger);
+                }
+            }
+            aircraft
+        };
+        let board = |pooling: bool| {
+            let mut aircraft = build(pooling);
+            aircraft.run_to_completion().unwrap();
+            aircraft
+        };
+
+        let single_file = board(false);
+        assert_eq!(single_file.peak_occupancy()[1][7], 1);
+
+        let mut pooled = board(true);
+        assert_eq!(pooled.peak_occupancy()[1][7], 2);
+        assert!(pooled.audit_seating().is_empty());
+        // Nobody lost their place in the queue by pooling.
+        let boarded: Vec<u16> = pooled.get_stats().passengers.iter()
+            .map(|p| p.boarded_tick.unwrap())
+            .collect();
+        assert!(boarded.windows(2).all(|w| w[0] < w[1]));
+        assert!(pooled.get_stats().last_boarded_tick
+                < single_file.get_stats().last_boarded_tick);
+
+        let events = pooled.take_events();
+        let mut replayed = build(true);
+        replayed.replay(&events).unwrap();
+        assert!(replayed.is_complete());
     }
 }
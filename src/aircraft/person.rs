@@ -6,6 +6,9 @@
 use std::fmt;
 // use super::tile::{Variant, Tile, SimpleTile};
 
+const POSITION_HISTORY: usize = 4; // The number of recent positions kept to
+                                   // detect passengers moving back and forth
+
 /// This enum represents a possible move for a pasenger during an update.
 #[derive(Debug, PartialEq, Copy, Clone)]
 pub enum Behaviour {
@@ -14,17 +17,101 @@ pub enum Behaviour {
     Move_East,
     Move_West,
     Stow, // stow baggage
+    Settle, // settle into seat
     Wait,
 }
 
+impl fmt::Display for Behaviour {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", match self {
+            Behaviour::Move_North => "north",
+            Behaviour::Move_South => "south",
+            Behaviour::Move_East => "east",
+            Behaviour::Move_West => "west",
+            Behaviour::Stow => "stow",
+            Behaviour::Settle => "settle",
+            Behaviour::Wait => "wait",
+        })
+    }
+}
+
+impl std::str::FromStr for Behaviour {
+    type Err = &'static str;
+
+    /// Reads a `Behaviour` as written by its `Display` implementation.
+    fn from_str(s: &str) -> Result<Behaviour, &'static str> {
+        match s {
+            "north" => Ok(Behaviour::Move_North),
+            "south" => Ok(Behaviour::Move_South),
+            "east" => Ok(Behaviour::Move_East),
+            "west" => Ok(Behaviour::Move_West),
+            "stow" => Ok(Behaviour::Stow),
+            "settle" => Ok(Behaviour::Settle),
+            "wait" => Ok(Behaviour::Wait),
+            _ => Err("Unknown behaviour"),
+        }
+    }
+}
+
+/// How easily a passenger gets around the cabin.
+///
+/// `Reduced` passengers move at half speed, and `Wheelchair` passengers do so
+/// and also take extra time getting from the aisle into their seat, blocking
+/// the aisle while they do.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum Mobility {
+    Normal,
+    Reduced,
+    Wheelchair,
+}
+
 /// A single passenger
 ///
 /// `seat` represents a passenger's assigned seat, and baggage stores whether
-/// the passenger still has their carry-on luggage or not.
+/// the passenger still has their carry-on luggage or not. `id` is assigned by
+/// the `Aircraft` the passenger is added to, and is used to look up their
+/// statistics. `settled` is set once the passenger is in its seat with its
+/// baggage stowed. `delay` is the number of updates the passenger must wait
+/// before it can move again, and `mobility` how quickly it moves.
+/// `reserved_bin` is set for passengers with guaranteed space in the overhead
+/// bins on their row, who never have to look elsewhere for it. `recent`
+/// holds the last few tiles the passenger stood on, most recent last. `zone`
+/// is the boarding zone the passenger was called in, if boarding by zone.
+/// `transit` is set for people passing through the cabin without a seat, who
+/// target the door they leave by instead. `stow_delay` is the number of extra
+/// updates the passenger takes to stow its baggage, and `group` identifies the
+/// party it is travelling with, if any. `bin_priority` decides who gets the
+/// last space in a row's overhead bins when two passengers want it at once.
+/// `door` is the entrance the passenger boarded through, once it has.
+/// `reassigned` is set for passengers moved from the seat they chose, who stop
+/// to double-check the seat number on reaching their new one. `personality`
+/// seeds the random draws made for this passenger alone, if set, so that it
+/// behaves the same way whatever happens to the passengers around it.
+/// `must_board_first` is set for passengers with a tight connection, who are
+/// let through the entrance before anyone else whatever the boarding order.
+/// `seat_deadline` is how long this passenger has to reach its seat before it
+/// is moved to another, in place of the aircraft's deadline, if set.
+#[derive(Clone)]
 pub struct Person {
     name: String,
     seat: Option<(u16, u16)>,
     baggage: bool,
+    id: Option<usize>,
+    settled: bool,
+    delay: u8,
+    mobility: Mobility,
+    reserved_bin: bool,
+    recent: Vec<(u16, u16)>,
+    zone: Option<u16>,
+    transit: bool,
+    stow_delay: u8,
+    group: Option<u16>,
+    bin_priority: u8,
+    door: Option<(u16, u16)>,
+    reassigned: bool,
+    personality: Option<u64>,
+    must_board_first: bool,
+    seat_deadline: Option<u16>,
 }
 
 impl Person {
@@ -44,6 +131,22 @@ impl Person {
             name: n.to_string(),
             seat: None,
             baggage: false,
+            id: None,
+            settled: false,
+            delay: 0,
+            mobility: Mobility::Normal,
+            reserved_bin: false,
+            recent: Vec::<(u16, u16)>::new(),
+            zone: None,
+            transit: false,
+            stow_delay: 0,
+            group: None,
+            bin_priority: 0,
+            door: None,
+            reassigned: false,
+            personality: None,
+            must_board_first: false,
+            seat_deadline: None,
         }
     }
     
@@ -62,6 +165,23 @@ impl Person {
         self.seat = Some((x, y));
     }
 
+    /// Copies this passenger to target a different seat.
+    ///
+    /// Everything describing the passenger is kept, but anything to do with a
+    /// boarding it has been part of (its id, delay, position history, and so
+    /// on) is not, so the copy can be added to another aircraft.
+    pub fn clone_with_seat(&self, x: u16, y: u16) -> Person {
+        Person {
+            seat: Some((x, y)),
+            id: None,
+            settled: false,
+            delay: 0,
+            recent: Vec::<(u16, u16)>::new(),
+            door: None,
+            ..self.clone()
+        }
+    }
+
     /// Sets a passenger's `baggage` value to false.
     pub fn remove_baggage(&mut self) {
         if self.baggage {
@@ -84,6 +204,165 @@ impl Person {
         self.seat
     }
 
+    pub fn get_id(&self) -> Option<usize> {
+        self.id
+    }
+
+    pub(crate) fn set_id(&mut self, id: usize) {
+        self.id = Some(id);
+    }
+
+    /// Holds this passenger in place for a number of additional updates.
+    pub fn delay(&mut self, ticks: u8) {
+        self.delay = self.delay.saturating_add(ticks);
+    }
+
+    pub fn is_delayed(&self) -> bool {
+        self.delay > 0
+    }
+
+    pub fn get_mobility(&self) -> Mobility {
+        self.mobility
+    }
+
+    pub fn set_mobility(&mut self, m: Mobility) {
+        self.mobility = m;
+    }
+
+    pub fn has_reserved_bin(&self) -> bool {
+        self.reserved_bin
+    }
+
+    pub fn set_reserved_bin(&mut self, r: bool) {
+        self.reserved_bin = r;
+    }
+
+    pub fn get_zone(&self) -> Option<u16> {
+        self.zone
+    }
+
+    pub fn set_zone(&mut self, z: Option<u16>) {
+        self.zone = z;
+    }
+
+    pub fn is_transit(&self) -> bool {
+        self.transit
+    }
+
+    pub(crate) fn set_transit(&mut self, t: bool) {
+        self.transit = t;
+    }
+
+    pub fn get_stow_delay(&self) -> u8 {
+        self.stow_delay
+    }
+
+    pub fn set_stow_delay(&mut self, d: u8) {
+        self.stow_delay = d;
+    }
+
+    pub fn get_group(&self) -> Option<u16> {
+        self.group
+    }
+
+    pub fn set_group(&mut self, g: Option<u16>) {
+        self.group = g;
+    }
+
+    pub fn get_bin_priority(&self) -> u8 {
+        self.bin_priority
+    }
+
+    pub fn set_bin_priority(&mut self, p: u8) {
+        self.bin_priority = p;
+    }
+
+    pub fn is_reassigned(&self) -> bool {
+        self.reassigned
+    }
+
+    pub fn set_reassigned(&mut self, r: bool) {
+        self.reassigned = r;
+    }
+
+    pub fn get_personality(&self) -> Option<u64> {
+        self.personality
+    }
+
+    pub fn set_personality(&mut self, seed: Option<u64>) {
+        self.personality = seed;
+    }
+
+    pub fn must_board_first(&self) -> bool {
+        self.must_board_first
+    }
+
+    pub fn set_must_board_first(&mut self, first: bool) {
+        self.must_board_first = first;
+    }
+
+    pub fn get_seat_deadline(&self) -> Option<u16> {
+        self.seat_deadline
+    }
+
+    pub fn set_seat_deadline(&mut self, deadline: Option<u16>) {
+        self.seat_deadline = deadline;
+    }
+
+    pub fn get_door(&self) -> Option<(u16, u16)> {
+        self.door
+    }
+
+    pub(crate) fn set_door(&mut self, x: u16, y: u16) {
+        self.door = Some((x, y));
+    }
+
+    /// Stows this passenger's baggage, holding it in place for its
+    /// `stow_delay`.
+    pub(crate) fn stow(&mut self) {
+        self.remove_baggage();
+        self.delay(self.stow_delay);
+    }
+
+    /// Records that this passenger has arrived on the tile at `x`,`y`.
+    pub(crate) fn record_position(&mut self, x: u16, y: u16) {
+        if self.recent.len() == POSITION_HISTORY {
+            self.recent.remove(0);
+        }
+        self.recent.push((x, y));
+    }
+
+    /// Checks whether moving to the tile at `x`,`y` would continue this
+    /// passenger going back and forth between the same two tiles.
+    pub(crate) fn is_oscillating(&self, x: u16, y: u16) -> bool {
+        match self.recent.as_slice() {
+            [a, b, c, d] => a == c && b == d && (x, y) == *c,
+            _ => false,
+        }
+    }
+
+    /// Counts down this passenger's delay, returning whether it was delayed.
+    pub(crate) fn tick_delay(&mut self) -> bool {
+        if self.delay > 0 {
+            self.delay -= 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn is_settled(&self) -> bool {
+        self.settled
+    }
+
+    /// Marks this passenger as having reached its seat for good.
+    pub(crate) fn settle(&mut self) {
+        if !self.settled {
+            log::info!("Passenger settled");
+            self.settled = true;
+        }
+    }
+
     pub fn set_name(&mut self, n: &str) {
         self.name = n.to_string();
     }
@@ -123,6 +402,66 @@ mod tests {
         assert_eq!(person.seat.unwrap(), (6, 9));
     }
 
+    #[test]
+    fn clone_with_seat() {
+        let mut person = Person::new("Dave");
+        person.target_seat(1, 2);
+        person.set_baggage(true);
+        person.set_mobility(Mobility::Reduced);
+        person.set_reserved_bin(true);
+        person.set_zone(Some(2));
+        person.set_stow_delay(3);
+        person.set_group(Some(4));
+        person.set_bin_priority(5);
+        person.set_id(6);
+        person.delay(2);
+        person.record_position(1, 3);
+
+        let copy = person.clone_with_seat(4, 5);
+        assert_eq!(copy.get_seat(), Some((4, 5)));
+        assert_eq!(copy.get_name(), "Dave");
+        assert!(copy.has_baggage());
+        assert_eq!(copy.get_mobility(), Mobility::Reduced);
+        assert!(copy.has_reserved_bin());
+        assert_eq!(copy.get_zone(), Some(2));
+        assert_eq!(copy.get_stow_delay(), 3);
+        assert_eq!(copy.get_group(), Some(4));
+        assert_eq!(copy.get_bin_priority(), 5);
+        assert!(!copy.is_transit());
+
+        assert_eq!(copy.get_id(), None);
+        assert!(!copy.is_delayed());
+        assert!(copy.recent.is_empty());
+    }
+
+    #[test]
+    fn oscillation() {
+        let mut person = Person::new("Dave");
+        person.record_position(1, 1);
+        person.record_position(1, 2);
+        person.record_position(1, 1);
+        assert!(!person.is_oscillating(1, 1));
+
+        person.record_position(1, 2);
+        assert!(person.is_oscillating(1, 1));
+        assert!(!person.is_oscillating(1, 3));
+
+        person.record_position(1, 3);
+        assert!(!person.is_oscillating(1, 2));
+    }
+
+    #[test]
+    fn behaviour_round_trip() {
+        for behaviour in &[Behaviour::Move_North, Behaviour::Move_South,
+                           Behaviour::Move_East, Behaviour::Move_West,
+                           Behaviour::Stow, Behaviour::Settle,
+                           Behaviour::Wait] {
+            let written = behaviour.to_string();
+            assert_eq!(written.parse::<Behaviour>(), Ok(*behaviour));
+        }
+        assert!("sideways".parse::<Behaviour>().is_err());
+    }
+
     // #[test]
     // fn update() {
     //     let mut person = Person::new("Dave");
@@ -0,0 +1,212 @@
+//! Handles playing a boarding back at a fixed pace in real time.
+//!
+//! This wraps an `Aircraft` for use by visualisers, and is kept separate from
+//! it so that batch runs aren't slowed down by any timekeeping.
+
+use std::time::{Duration, Instant};
+
+use super::Aircraft;
+use super::stats::StepReport;
+
+/// A source of elapsed time.
+pub trait Clock {
+    /// Returns the time elapsed since the clock was created.
+    fn elapsed(&self) -> Duration;
+}
+
+/// A `Clock` following real time using `std::time::Instant`.
+pub struct InstantClock {
+    start: Instant,
+}
+
+impl InstantClock {
+    /// Constructor
+    pub fn new() -> InstantClock {
+        InstantClock {
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Default for InstantClock {
+    fn default() -> InstantClock {
+        InstantClock::new()
+    }
+}
+
+impl Clock for InstantClock {
+    fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+/// An aircraft updated once every `seconds_per_tick` seconds of real time.
+///
+/// # Examples
+///
+/// ```
+/// let mut simulation = RealtimeSimulation::new(aircraft, 0.5);
+///
+/// while !simulation.get_aircraft().is_complete() {
+///     for report in simulation.poll() {
+///         println!("Tick {}: {} moved", report.tick, report.moved);
+///     }
+/// }
+/// ```
+pub struct RealtimeSimulation<C: Clock = InstantClock> {
+    aircraft: Aircraft,
+    seconds_per_tick: f32,
+    clock: C,
+    ticks: u32,
+}
+
+impl RealtimeSimulation<InstantClock> {
+    /// Constructor
+    pub fn new(aircraft: Aircraft, seconds_per_tick: f32)
+        -> RealtimeSimulation<InstantClock> {
+        RealtimeSimulation::with_clock(aircraft,
+                                       seconds_per_tick,
+                                       InstantClock::new())
+    }
+}
+
+impl<C: Clock> RealtimeSimulation<C> {
+    /// Constructor, following the given clock instead of real time.
+    pub fn with_clock(aircraft: Aircraft, seconds_per_tick: f32, clock: C)
+        -> RealtimeSimulation<C> {
+        RealtimeSimulation {
+            aircraft,
+            seconds_per_tick,
+            clock,
+            ticks: 0,
+        }
+    }
+
+    /// Updates the aircraft once for every tick that has fallen due since the
+    /// last poll, reporting what happened on each.
+    ///
+    /// Nothing is updated once the aircraft is complete.
+    pub fn poll(&mut self) -> Vec<StepReport> {
+        let due = (self.clock.elapsed().as_secs_f32()
+                   / self.seconds_per_tick) as u32;
+        let mut reports = Vec::<StepReport>::new();
+        while self.ticks < due && !self.aircraft.is_complete() {
+            reports.push(self.aircraft.step());
+            self.ticks += 1;
+        }
+        reports
+    }
+
+    pub fn get_aircraft(&self) -> &Aircraft {
+        &self.aircraft
+    }
+
+    /// Stops the simulation, returning the aircraft.
+    pub fn into_aircraft(self) -> Aircraft {
+        self.aircraft
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::person::Person;
+    use super::super::tile::Variant;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    /// A `Clock` that only moves when told to, so that a `RealtimeSimulation`
+    /// can be driven deterministically.
+    ///
+    /// Clones share the same time, so one can be handed to a simulation while
+    /// another is kept to move it along.
+    #[derive(Clone, Default)]
+    struct MockClock {
+        now: Rc<Cell<Duration>>,
+    }
+
+    impl MockClock {
+        /// Constructor
+        ///
+        /// The clock starts at zero.
+        fn new() -> MockClock {
+            MockClock {
+                now: Rc::new(Cell::new(Duration::from_secs(0))),
+            }
+        }
+
+        /// Sets the time elapsed on the clock.
+        fn set(&self, elapsed: Duration) {
+            self.now.set(elapsed);
+        }
+
+        /// Moves the clock on by `duration`.
+        fn advance(&self, duration: Duration) {
+            self.now.set(self.now.get() + duration);
+        }
+    }
+
+    impl Clock for MockClock {
+        fn elapsed(&self) -> Duration {
+            self.now.get()
+        }
+    }
+
+    #[test]
+    fn ticks_at_configured_rate() {
+        let mut aircraft = Aircraft::new(3, 10);
+        aircraft.set_tile(1, 9, Variant::Entrance);
+        aircraft.set_tile(0, 0, Variant::Seat);
+        let mut passenger = Person::new("DEFAULT");
+        passenger.target_seat(0, 0);
+        aircraft.add_passenger(passenger);
+
+        let now = MockClock::new();
+        let mut simulation = RealtimeSimulation::with_clock(aircraft, 0.5,
+                                                            now.clone());
+
+        assert!(simulation.poll().is_empty());
+        now.set(Duration::from_millis(400));
+        assert!(simulation.poll().is_empty());
+        now.set(Duration::from_millis(1600));
+        let ticks: Vec<u16> = simulation.poll().iter()
+            .map(|r| r.tick)
+            .collect();
+        assert_eq!(ticks, vec![1, 2, 3]);
+        assert!(simulation.poll().is_empty());
+
+        // Stops once everyone is seated, however much time has passed.
+        now.set(Duration::from_secs(60));
+        let reports = simulation.poll();
+        assert!(reports.last().unwrap().complete);
+        assert!(reports.len() < 117);
+        assert!(simulation.get_aircraft().is_complete());
+    }
+
+    #[test]
+    fn door_opens_on_schedule() {
+        let mut aircraft = Aircraft::new(3, 10);
+        aircraft.set_tile(1, 9, Variant::Entrance);
+        aircraft.set_tile(0, 0, Variant::Seat);
+        aircraft.set_door_window(1, 9, 3, None).unwrap();
+        let mut passenger = Person::new("DEFAULT");
+        passenger.target_seat(0, 0);
+        aircraft.add_passenger(passenger);
+
+        let now = MockClock::new();
+        let mut simulation = RealtimeSimulation::with_clock(aircraft, 1.0,
+                                                            now.clone());
+        now.advance(Duration::from_secs(2));
+        let reports = simulation.poll();
+        assert_eq!(reports.len(), 2);
+        assert!(reports.iter().all(|r| !r.entered));
+
+        now.advance(Duration::from_secs(1));
+        let reports = simulation.poll();
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].tick, 3);
+        assert!(reports[0].entered);
+        assert_eq!(simulation.get_aircraft().get_stats().passengers[0]
+                       .boarded_tick, Some(3));
+    }
+}
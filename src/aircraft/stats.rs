@@ -0,0 +1,321 @@
+//! Holds structures used to record statistics about a boarding as it happens.
+//!
+//! These are filled in by `Aircraft` on every update and can be inspected once
+//! the aircraft has been fully boarded (or at any point before then), and two
+//! boardings compared with `BoardingStats::diff`.
+
+use std::collections::BTreeMap;
+
+/// Statistics about a single passenger.
+///
+/// `aisle_ticks` counts the updates this passenger spent standing on an aisle
+/// or entrance tile, where it blocks everyone behind it; `seated_ticks` counts
+/// the updates spent on a seat tile. `gate_checked` is set if its bag was
+/// checked at the door because the overhead bins were full. `boarded_tick` and
+/// `seated_tick` are the updates on which it came onboard and first reached
+/// its seat, `blocked_by` the id of the passenger that most recently stood in
+/// its way, `zone` the boarding zone it was called in, if any, and `seat` the
+/// seat it was assigned. If it was moved to another seat for not reaching its
+/// own in time, `reassigned_from` is the seat it was moved from and
+/// `reassigned_tick` the update on which it was moved, and `seat_deadline` is
+/// its own deadline for reaching its seat, if it was given one.
+/// `ideal_seated_tick` is the update on which it would have reached its seat
+/// had nobody been in its way, as worked out when it boarded.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PassengerStats {
+    pub name: String,
+    pub aisle_ticks: u16,
+    pub seated_ticks: u16,
+    pub gate_checked: bool,
+    pub boarded_tick: Option<u16>,
+    pub seated_tick: Option<u16>,
+    pub blocked_by: Option<usize>,
+    pub zone: Option<u16>,
+    pub seat: Option<(u16, u16)>,
+    pub reassigned_from: Option<(u16, u16)>,
+    pub reassigned_tick: Option<u16>,
+    pub seat_deadline: Option<u16>,
+    pub ideal_seated_tick: Option<u16>,
+}
+
+impl PassengerStats {
+    /// Constructor
+    pub fn new(name: &str) -> PassengerStats {
+        PassengerStats {
+            name: name.to_string(),
+            aisle_ticks: 0,
+            seated_ticks: 0,
+            gate_checked: false,
+            boarded_tick: None,
+            seated_tick: None,
+            blocked_by: None,
+            zone: None,
+            seat: None,
+            reassigned_from: None,
+            reassigned_tick: None,
+            seat_deadline: None,
+            ideal_seated_tick: None,
+        }
+    }
+}
+
+/// Statistics about an entire boarding.
+///
+/// `interferences` counts the times a passenger had to squeeze past another,
+/// and `pair_interferences` how many of those times each passenger squeezed
+/// past each other, by id, with the one squeezing past first (see
+/// `named_pair_interferences` for their names); `shuffles`
+/// counts just those into a seat, past a passenger already sitting. `waits`
+/// counts the updates passengers onboard spent standing still short of
+/// settling, whether blocked or serving a delay, and `stows` the times a
+/// passenger stowed its baggage. The event log has no record of passengers
+/// standing still, so `waits` isn't counted by `Aircraft::replay`.
+/// `passengers` holds one entry per passenger, in the order they were added to
+/// the aircraft. `efficiency` is the ratio of `theoretical_min_ticks` (the
+/// time the boarding would take with no congestion at all) to the time it
+/// actually took, between 0 and 1; it is only set once every targeted seat has
+/// been filled, on the update recorded in `complete_tick`. `row_dwell` holds,
+/// for each row, the number of updates between the first passenger seated on
+/// that row boarding and every targeted seat on it being filled; it is 0 for
+/// rows that haven't filled yet. `critical_path` holds the names of the chain
+/// of passengers that held up the last passenger to be seated, ending with that
+/// passenger, once the boarding is complete (or ending with the first passenger
+/// still unseated, when a run gives up), and `per_zone` the statistics
+/// for each boarding zone, if passengers were boarded by zone.
+/// `max_door_queue` is the longest queue seen at any door, counting the
+/// passengers waiting on the jet bridge and those standing nose to tail in the
+/// aisle from the entrance; a long queue there means boarding is held up at
+/// the door rather than further down the aisle. `total_aisle_person_ticks`
+/// totals, over every update, the passengers standing on aisle and entrance
+/// tiles; it measures how congested the boarding was, and is often a better
+/// thing to minimise than the time taken. `last_boarded_tick` is the update on
+/// which the last passenger waiting to board came onboard, after which the
+/// door could be closed.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct BoardingStats {
+    pub ticks: u16,
+    pub boarded: usize,
+    pub gate_checked: u16,
+    pub interferences: u16,
+    pub pair_interferences: BTreeMap<(usize, usize), u32>,
+    pub shuffles: u16,
+    pub waits: u32,
+    pub stows: u16,
+    pub max_door_queue: u16,
+    pub total_aisle_person_ticks: u32,
+    pub last_boarded_tick: Option<u16>,
+    pub complete_tick: Option<u16>,
+    pub theoretical_min_ticks: u16,
+    pub efficiency: f32,
+    pub row_dwell: Vec<u16>,
+    pub critical_path: Vec<String>,
+    pub per_zone: Vec<ZoneStats>,
+    pub passengers: Vec<PassengerStats>,
+}
+
+impl BoardingStats {
+    /// Constructor
+    pub fn new() -> BoardingStats {
+        BoardingStats {
+            ticks: 0,
+            boarded: 0,
+            gate_checked: 0,
+            interferences: 0,
+            pair_interferences: BTreeMap::<(usize, usize), u32>::new(),
+            shuffles: 0,
+            waits: 0,
+            stows: 0,
+            max_door_queue: 0,
+            total_aisle_person_ticks: 0,
+            last_boarded_tick: None,
+            complete_tick: None,
+            theoretical_min_ticks: 0,
+            efficiency: 0.0,
+            row_dwell: Vec::<u16>::new(),
+            critical_path: Vec::<String>::new(),
+            per_zone: Vec::<ZoneStats>::new(),
+            passengers: Vec::<PassengerStats>::new(),
+        }
+    }
+
+    /// Compares these statistics with those of another boarding, giving how
+    /// much each has changed from these to the other's.
+    pub fn diff(&self, other: &BoardingStats) -> StatsDiff {
+        let rows = self.row_dwell.len().max(other.row_dwell.len());
+        let dwell = |stats: &BoardingStats, row: usize| {
+            *stats.row_dwell.get(row).unwrap_or(&0) as i32
+        };
+
+        StatsDiff {
+            ticks: other.ticks as i32 - self.ticks as i32,
+            gate_checked: other.gate_checked as i32 - self.gate_checked as i32,
+            interferences: other.interferences as i32
+                - self.interferences as i32,
+            aisle_ticks: other.total_aisle_person_ticks as i32
+                - self.total_aisle_person_ticks as i32,
+            efficiency: other.efficiency - self.efficiency,
+            row_dwell: (0..rows)
+                .map(|row| dwell(other, row) - dwell(self, row))
+                .collect(),
+        }
+    }
+
+    /// Lists `pair_interferences` with each passenger's name in place of its
+    /// id, ordered by id.
+    pub fn named_pair_interferences(&self) -> Vec<(String, String, u32)> {
+        self.pair_interferences.iter()
+            .map(|(&(passer, occupier), &count)| {
+                (self.passengers[passer].name.clone(),
+                 self.passengers[occupier].name.clone(),
+                 count)
+            })
+            .collect()
+    }
+
+    /// Lists the seats that have been filled, in the order their passengers
+    /// reached them.
+    ///
+    /// Congestion means this usually differs from the order passengers boarded
+    /// in. Seats filled on the same update are listed in the order their
+    /// passengers were added.
+    pub fn realized_seat_order(&self) -> Vec<(u16, u16)> {
+        let mut seated: Vec<(u16, (u16, u16))> = self.passengers.iter()
+            .filter_map(|p| match (p.seated_tick, p.seat) {
+                (Some(tick), Some(seat)) => Some((tick, seat)),
+                _ => None,
+            })
+            .collect();
+        seated.sort_by_key(|(tick, _)| *tick);
+        seated.into_iter().map(|(_, seat)| seat).collect()
+    }
+
+    /// Groups the passengers' statistics by boarding zone, in zone order.
+    ///
+    /// Passengers without a zone are left out, so this is empty if nobody
+    /// boarded by zone.
+    pub(crate) fn zone_stats(&self) -> Vec<ZoneStats> {
+        let mut zones: Vec<u16> = self.passengers.iter()
+            .filter_map(|p| p.zone)
+            .collect();
+        zones.sort_unstable();
+        zones.dedup();
+
+        let mut per_zone: Vec<ZoneStats> = zones.iter()
+            .map(|&zone| {
+                let members = || self.passengers.iter()
+                    .filter(move |p| p.zone == Some(zone));
+                let complete = members().all(|p| p.seated_tick.is_some());
+                ZoneStats {
+                    zone,
+                    first_boarded: members().filter_map(|p| p.boarded_tick)
+                        .min(),
+                    complete_tick: if complete {
+                        members().filter_map(|p| p.seated_tick).max()
+                    } else {
+                        None
+                    },
+                    overlap: 0,
+                }
+            })
+            .collect();
+
+        for i in 1..per_zone.len() {
+            let previous = per_zone[i - 1].complete_tick;
+            if let (Some(complete), Some(next)) = (previous,
+                                                   per_zone[i].first_boarded) {
+                per_zone[i - 1].overlap = complete.saturating_sub(next);
+            }
+        }
+        per_zone
+    }
+}
+
+impl std::fmt::Display for BoardingStats {
+    /// Writes a summary of the boarding over several lines, e.g. for printing
+    /// once a run is over.
+    ///
+    /// Aisle use is the mean number of passengers standing on aisle and
+    /// entrance tiles per update.
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let seated = self.passengers.iter()
+            .filter(|p| p.seated_tick.is_some())
+            .count();
+        let aisle_use = if self.ticks > 0 {
+            self.total_aisle_person_ticks as f32 / self.ticks as f32
+        } else {
+            0.0
+        };
+
+        writeln!(f, "Updates: {}", self.ticks)?;
+        match self.complete_tick {
+            Some(tick) => writeln!(f, "Complete on update: {}", tick)?,
+            None => writeln!(f, "Complete on update: not complete")?,
+        }
+        writeln!(f, "Seated: {} of {}", seated, self.passengers.len())?;
+        writeln!(f, "Interferences: {}", self.interferences)?;
+        writeln!(f, "Bags checked at door: {}", self.gate_checked)?;
+        writeln!(f, "Aisle use: {:.2} passengers per update", aisle_use)?;
+        write!(f, "Efficiency: {:.1}%", self.efficiency * 100.0)
+    }
+}
+
+/// Statistics about a single boarding zone.
+///
+/// `first_boarded` is the update on which the first passenger in the zone came
+/// onboard, and `complete_tick` the update on which the last was seated.
+/// `overlap` is the number of updates the zone was still being seated after
+/// the next zone started boarding; zones that are well separated in time have
+/// none.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ZoneStats {
+    pub zone: u16,
+    pub first_boarded: Option<u16>,
+    pub complete_tick: Option<u16>,
+    pub overlap: u16,
+}
+
+/// The differences between the statistics of two boardings.
+///
+/// Each field is the value for the second boarding less that for the first,
+/// so negative values mean the second boarding did better on every count
+/// except `efficiency`. `aisle_ticks` totals the updates every passenger spent
+/// standing in the aisle, a measure of how congested it was.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatsDiff {
+    pub ticks: i32,
+    pub gate_checked: i32,
+    pub interferences: i32,
+    pub aisle_ticks: i32,
+    pub efficiency: f32,
+    pub row_dwell: Vec<i32>,
+}
+
+/// A summary of what happened during a single update.
+///
+/// `moved` and `stowed` count the passengers that moved to another tile and
+/// that stowed their baggage, `entered` is set if a passenger came onboard,
+/// and `seated_delta` is the change in the number of targeted seats that are
+/// occupied.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StepReport {
+    pub tick: u16,
+    pub moved: u16,
+    pub stowed: u16,
+    pub entered: bool,
+    pub seated_delta: i32,
+    pub complete: bool,
+}
+
+impl StepReport {
+    /// Constructor
+    pub fn new(tick: u16) -> StepReport {
+        StepReport {
+            tick,
+            moved: 0,
+            stowed: 0,
+            entered: false,
+            seated_delta: 0,
+            complete: false,
+        }
+    }
+}
@@ -3,8 +3,8 @@
 use std::fmt;
 use super::person::Person;
 
-const PASS_WAIT: u8 = 2; // The amount of additional steps taken when moving
-                         // past an occupied seat
+pub(crate) const PASS_WAIT: u8 = 2; // The amount of additional steps taken
+                                    // when moving past an occupied seat
 
 /// An enum representing the various possible types of tile that an `Aircraft`
 /// may contain.
@@ -20,12 +20,14 @@ pub enum Variant {
 ///
 /// Must have a `variant`, may hold one or two passengers. Two passengers are
 /// only held when another passenger is making their way past on an aisle.
+#[derive(Clone)]
 pub struct Tile {
     pub(crate) variant: Variant,
     occupier: Option<Person>,
     updated: bool,
     pass_counter: u8,
     allowing: Option<Person>,
+    displaced: u16,
 }
 
 impl Tile {
@@ -37,6 +39,7 @@ impl Tile {
             updated: false,
             pass_counter: 0,
             allowing: None,
+            displaced: 0,
         }
     }
 
@@ -48,6 +51,7 @@ impl Tile {
             updated: false,
             pass_counter: 0,
             allowing: None,
+            displaced: 0,
         }
     }
 
@@ -59,6 +63,7 @@ impl Tile {
             updated: false,
             pass_counter: 0,
             allowing: None,
+            displaced: 0,
         }
     }
 
@@ -70,20 +75,30 @@ impl Tile {
             updated: false,
             pass_counter: 0,
             allowing: None,
+            displaced: 0,
         }
     }
 
-    /// Places a passenger into this tile.
+    /// Places a passenger into this tile, which must not already have an
+    /// occupier.
     pub fn occupy(&mut self, p: Person) {
         if self.is_occupied() {
             log::warn!("A passenger is being displaced");
+            self.displaced += 1;
         }
+        debug_assert!(!self.is_occupied(), "A passenger is being displaced");
         self.occupier = Some(p);
         self.updated = true;
     }
 
-    /// Allows a second passenger to temporarily occupy this space.
+    /// Allows a second passenger to temporarily occupy this space, which must
+    /// not already have one passing it.
     pub fn pass_in(&mut self, p: Person) {
+        if self.is_allowing() {
+            log::warn!("A passer is being displaced");
+            self.displaced += 1;
+        }
+        debug_assert!(!self.is_allowing(), "A passer is being displaced");
         self.allowing = Some(p);
     }
 
@@ -93,6 +108,14 @@ impl Tile {
         return person.unwrap();
     }
 
+    /// Swaps this tile's occupier with the passenger passing it, if there is
+    /// one.
+    pub fn swap_places(&mut self) {
+        if self.occupier.is_some() && self.allowing.is_some() {
+            std::mem::swap(&mut self.occupier, &mut self.allowing);
+        }
+    }
+
     pub fn is_occupied(&self) -> bool {
         match self.occupier {
             Some(_) => true,
@@ -107,9 +130,30 @@ impl Tile {
         }
     }
 
-    /// Checks whether second occupant has been delayed enough to pass onwards.
-    pub fn pass_count(&mut self) -> bool {
-        if self.pass_counter >= PASS_WAIT {
+    /// Counts the passengers on this tile.
+    pub fn occupants(&self) -> u8 {
+        self.is_occupied() as u8 + self.is_allowing() as u8
+    }
+
+    /// Returns the number of passengers that have been lost from this tile by
+    /// another being placed where they were.
+    pub fn get_displaced(&self) -> u16 {
+        self.displaced
+    }
+
+    /// Returns the most passengers this tile can hold at once: its occupier
+    /// and one more passing it, or none at all for a `None` tile.
+    pub fn capacity(&self) -> u8 {
+        match self.variant {
+            Variant::None => 0,
+            _ => 2,
+        }
+    }
+
+    /// Checks whether second occupant has been delayed enough to pass onwards,
+    /// given the number of additional steps passing this tile takes.
+    pub fn pass_count(&mut self, wait: u8) -> bool {
+        if self.pass_counter >= wait {
             self.pass_counter = 0;
             return true;
         } else {
@@ -122,7 +166,13 @@ impl Tile {
         self.variant
     }
 
-    pub fn get_occupier(&mut self) -> Option<&Person> {
+    /// As `get_variant`, but always inlined, for the update loop.
+    #[inline(always)]
+    pub fn variant(&self) -> Variant {
+        self.variant
+    }
+
+    pub fn get_occupier(&self) -> Option<&Person> {
         return self.occupier.as_ref();
     }
 
@@ -169,6 +219,65 @@ impl fmt::Debug for Tile {
     }
 }
 
+/// A read-only snapshot of a single tile, without its passengers.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct SimpleTile {
+    variant: Variant,
+    occupied: bool,
+    allowing: bool,
+}
+
+impl SimpleTile {
+    /// Constructor
+    pub fn new(tile: &Tile) -> SimpleTile {
+        SimpleTile {
+            variant: tile.get_variant(),
+            occupied: tile.is_occupied(),
+            allowing: tile.is_allowing(),
+        }
+    }
+
+    pub fn get_variant(&self) -> Variant {
+        self.variant
+    }
+
+    pub fn is_occupied(&self) -> bool {
+        self.occupied
+    }
+
+    pub fn is_allowing(&self) -> bool {
+        self.allowing
+    }
+}
+
+/// The tiles surrounding a position, as seen by a passenger standing there.
+///
+/// Holds a `SimpleTile` for each of the nine tiles centred on the position, or
+/// `None` for those falling outside the aircraft.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct Neighbourhood {
+    cells: [Option<SimpleTile>; 9],
+}
+
+impl Neighbourhood {
+    /// Constructor
+    ///
+    /// `cells` is given row by row from the north-west corner.
+    pub fn new(cells: [Option<SimpleTile>; 9]) -> Neighbourhood {
+        Neighbourhood { cells }
+    }
+
+    /// Returns the tile `dx` tiles east and `dy` tiles south of the centre,
+    /// where both are between -1 and 1, or `None` if it is outside the
+    /// aircraft.
+    pub fn get(&self, dx: i8, dy: i8) -> Option<SimpleTile> {
+        if !(-1..=1).contains(&dx) || !(-1..=1).contains(&dy) {
+            return None;
+        }
+        self.cells[((dy + 1) * 3 + dx + 1) as usize]
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -224,4 +333,13 @@ mod tests {
         assert!(tile0.is_occupied(), "Tile 0 was not occupied post pass");
         assert_eq!(tile1.is_allowing(), false, "Tile 1 was still allowing");
     }
+
+    #[test]
+    #[should_panic]
+    #[cfg(debug_assertions)]
+    fn over_capacity() {
+        let mut tile = Tile::aisle();
+        tile.pass_in(Person::new("DEFAULT"));
+        tile.pass_in(Person::new("DEFAULT"));
+    }
 }
@@ -0,0 +1,51 @@
+//! Holds structures recording the decisions made by passengers on each update.
+//!
+//! This is the structured form of the `NEW MOVE`/`REJECTED` debug logging in
+//! `Aircraft::determine_move`, and is only collected when enabled with
+//! `Aircraft::set_decision_trace`.
+
+use super::person::Behaviour;
+use super::tile::Variant;
+
+/// The decision made by a single passenger on a single update.
+///
+/// `candidates` holds every move that was scored, alongside its distance from
+/// the passenger's goal, whether or not it was allowed; it is empty when the
+/// passenger did not need to consider any moves (e.g. when stowing).
+/// `passing` is set when the passenger was sharing its tile with another, and
+/// `boxed_in` when it had no legal move at all, so waited because it couldn't
+/// move rather than by choice.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MoveDecision {
+    pub passenger: String,
+    pub id: Option<usize>,
+    pub position: (u16, u16),
+    pub passing: bool,
+    pub candidates: Vec<(Behaviour, f32)>,
+    pub chosen: Behaviour,
+    pub boxed_in: bool,
+}
+
+/// Every decision made on a single update.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TickDecisions {
+    pub tick: u16,
+    pub decisions: Vec<MoveDecision>,
+}
+
+/// Where a single passenger was on a single update, as one row of the table
+/// built by `Aircraft::trajectory_table`.
+///
+/// `variant` is the kind of tile the passenger stood on, and `behaviour` the
+/// move it chose on that update, if it made one; passengers that have settled
+/// or have only just boarded make no decision.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrajectoryRow {
+    pub tick: u16,
+    pub id: usize,
+    pub passenger: String,
+    pub x: u16,
+    pub y: u16,
+    pub variant: Variant,
+    pub behaviour: Option<Behaviour>,
+}
@@ -28,7 +28,7 @@ fn main() {
     let aircraft = read_layout(Path::new(layout_file));
     let passengers = read_passengers(Path::new(passenger_list));
     
-    if aircraft.is_some() && passengers.is_some() {
+    if aircraft.is_ok() && passengers.is_ok() {
         let mut aircraft = aircraft.unwrap();
         SimpleLogger::new()
             .with_level(LevelFilter::Warn)
@@ -37,13 +37,21 @@ fn main() {
 
         log::info!("Initialised logger");
         for i in passengers.unwrap() {
-            aircraft.add_passenger(i);
+            if let Err(e) = aircraft.try_add_passenger(i) {
+                println!("{}", e);
+                println!("Invalid files given as input; exiting");
+                return;
+            }
         }
         match aircraft.run_to_completion() {
-            Ok(x) => println!("Completed in {} steps!", x),
+            Ok(stats) => println!("Completed in {} steps!",
+                                  stats.complete_tick.unwrap_or(stats.ticks)),
             _ => println!("Incorrect input files"),
         }
     } else {
+        for e in aircraft.err().iter().chain(passengers.err().iter()) {
+            println!("{}", e);
+        }
         println!("Invalid files given as input; exiting");
     }
 }
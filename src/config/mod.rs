@@ -1,16 +1,42 @@
 //! Handles configuration such as reading from files and generating boarding
 //! patterns.
 
-use std::fs::File;
-use std::path::Path;
+use std::cmp::Reverse;
+use std::error::Error;
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
 
-use super::aircraft::Aircraft;
+use super::aircraft::{Aircraft, BoardingOrder};
+use super::aircraft::grid;
+use super::aircraft::stats::BoardingStats;
 use super::aircraft::tile::Variant;
+use super::aircraft::trace::TrajectoryRow;
 use super::aircraft::person::Person;
 
-use rand::thread_rng;
+use rand::{thread_rng, Rng, SeedableRng};
 use rand::seq::SliceRandom;
 
+const SLOW_STOWER_DELAY: u8 = 3; // The number of extra steps a slow stower
+                                 // spends putting its bag away
+const ROBUSTNESS_SWAP_PROB: f32 = 0.1; // The chance of each pair of passengers
+                                      // swapping places in `robustness` runs
+
+/// The RNG used by seeded boarding patterns.
+///
+/// This is `StdRng` by default, or `SmallRng` when built with the `small_rng`
+/// feature.
+#[cfg(not(feature = "small_rng"))]
+pub type SeededRng = rand::rngs::StdRng;
+#[cfg(feature = "small_rng")]
+pub type SeededRng = rand::rngs::SmallRng;
+
+/// Creates a `SeededRng` from the given seed.
+pub fn seeded_rng(seed: u64) -> SeededRng {
+    SeededRng::seed_from_u64(seed)
+}
+
 /// Temporary holder for data about seats when reading from files.
 struct seat_data {
     seat_x: u16,
@@ -20,11 +46,11 @@ struct seat_data {
 
 impl seat_data {
     /// Constructor
-    fn new(x: u16, y: u16, variant: &str) -> seat_data {
+    fn new(x: u16, y: u16, variant: Variant) -> seat_data {
         seat_data {
             seat_x: x,
             seat_y: y,
-            variant: str_to_var(variant),
+            variant,
         }
     }
 
@@ -41,16 +67,28 @@ impl seat_data {
     }
 }
 
-/// Converts a string into an associated Variant.
+/// Converts a string into an associated Variant, or `None` if it names no
+/// variant.
 ///
 /// This is for use when calling Rust code from Python, as Python does not share
 /// the same enums.
-fn str_to_var(var: &str) -> Variant {
+fn str_to_var(var: &str) -> Option<Variant> {
+    match var {
+        "aisle" => Some(Variant::Aisle),
+        "seat" => Some(Variant::Seat),
+        "entrance" => Some(Variant::Entrance),
+        "none" => Some(Variant::None),
+        _ => None,
+    }
+}
+
+/// Converts a Variant into the string `str_to_var` reads it from.
+fn var_to_str(var: Variant) -> &'static str {
     match var {
-        "aisle" => Variant::Aisle,
-        "seat" => Variant::Seat,
-        "entrance" => Variant::Entrance,
-        _ => Variant::None, // Shouldn't happen
+        Variant::Aisle => "aisle",
+        Variant::Seat => "seat",
+        Variant::Entrance => "entrance",
+        Variant::None => "none",
     }
 }
 
@@ -81,9 +119,87 @@ pub fn standard_layout(mut size_x: u16, size_y: u16)
     return Ok(aircraft);
 }
 
+/// Generates an aircraft as `standard_layout` does, but with its only entrance
+/// at `door` instead of at the rear of the aisle.
+///
+/// The door can be anywhere along the aisle, or on either side of the cabin,
+/// in which case the seats between it and the aisle are replaced with a
+/// vestibule of aisle tiles joining the two. Returns an error if the door is
+/// anywhere else, or outside the aircraft.
+pub fn standard_layout_with_door(size_x: u16, size_y: u16, door: (u16, u16))
+    -> Result<Aircraft, &'static str> {
+    let mut aircraft = standard_layout(size_x, size_y)?;
+    let (size_x, size_y) = aircraft.get_size();
+    let aisle = size_x / 2;
+    if door.0 >= size_x || door.1 >= size_y {
+        return Err("Door is outside the aircraft");
+    }
+    if door.0 != aisle && door.0 != 0 && door.0 != size_x - 1 {
+        return Err("Door must be on the aisle or a side of the cabin");
+    }
+
+    aircraft.set_tile(aisle, size_y - 1, Variant::Aisle);
+    for x in door.0.min(aisle)..=door.0.max(aisle) {
+        aircraft.set_tile(x, door.1, Variant::Aisle);
+    }
+    aircraft.set_tile(door.0, door.1, Variant::Entrance);
+
+    Ok(aircraft)
+}
+
+/// Generates a wide-body aircraft with two aisles in the given size, dividing
+/// the seats on each row into three blocks, e.g. 3-3-3 or 2-4-2.
+///
+/// The outer blocks are the same width, with any seats left over going to the
+/// centre block. The entrance is at the rear of the left aisle, with the
+/// centre block's seats on that row replaced by a cross-aisle joining the two
+/// aisles. Returns an error if the aircraft is too narrow to fit a seat in
+/// each block, or too short to have a row of seats behind the cross-aisle.
+pub fn twin_aisle_layout(size_x: u16, size_y: u16)
+    -> Result<Aircraft, &'static str> {
+
+    log::info!("Generating twin-aisle aircraft");
+    if size_x < 5 {
+        return Err("Aircraft is too narrow for two aisles");
+    }
+    if size_y < 2 {
+        return Err("Aircraft is too short for a cross-aisle");
+    }
+
+    let mut aircraft = Aircraft::new(size_x, size_y);
+    let side = (size_x - 2) / 3;
+    let aisles = (side, size_x - 1 - side);
+    for y in 0..size_y {
+        for x in 0..size_x {
+            let cross_aisle = y == size_y - 1 && x > aisles.0 && x < aisles.1;
+            if x != aisles.0 && x != aisles.1 && !cross_aisle {
+                aircraft.set_tile(x, y, Variant::Seat);
+            }
+        }
+    }
+    aircraft.set_tile(aisles.0, size_y - 1, Variant::Entrance);
+
+    Ok(aircraft)
+}
+
 /// Generates a list of passengers that will board in standard back-first order
 /// with randomised positions on each row.
-pub fn random_back_first(mut size_x: u16, size_y: u16)
+pub fn random_back_first(size_x: u16, size_y: u16)
+    -> Result<Vec<Person>, &'static str> {
+    random_back_first_with_rng(size_x, size_y, &mut thread_rng())
+}
+
+/// As `random_back_first`, but shuffles each row using an RNG seeded with
+/// `seed`, so that the same seed always gives the same passengers.
+pub fn random_back_first_seeded(size_x: u16, size_y: u16, seed: u64)
+    -> Result<Vec<Person>, &'static str> {
+    random_back_first_with_rng(size_x, size_y, &mut seeded_rng(seed))
+}
+
+/// As `random_back_first`, but shuffles each row using the given RNG.
+pub fn random_back_first_with_rng<R: Rng + ?Sized>(mut size_x: u16,
+                                                   size_y: u16,
+                                                   rng: &mut R)
     -> Result<Vec<Person>, &'static str> {
 
     log::info!("Generating random back-first boarding pattern");
@@ -96,7 +212,7 @@ pub fn random_back_first(mut size_x: u16, size_y: u16)
 
     for y in 0..size_y {
         let mut x_coords: Vec<u16> = (0..size_x).collect();
-        x_coords.shuffle(&mut thread_rng());
+        x_coords.shuffle(rng);
         for x in x_coords {
             if x != aisle {
                 let mut person = Person::new("DEFAULT");
@@ -112,7 +228,22 @@ pub fn random_back_first(mut size_x: u16, size_y: u16)
 
 /// Generates a list of passengers that will board in standard front-first order
 /// with randomised positions on each row.
-pub fn random_front_first(mut size_x: u16, size_y: u16)
+pub fn random_front_first(size_x: u16, size_y: u16)
+    -> Result<Vec<Person>, &'static str> {
+    random_front_first_with_rng(size_x, size_y, &mut thread_rng())
+}
+
+/// As `random_front_first`, but shuffles each row using an RNG seeded with
+/// `seed`.
+pub fn random_front_first_seeded(size_x: u16, size_y: u16, seed: u64)
+    -> Result<Vec<Person>, &'static str> {
+    random_front_first_with_rng(size_x, size_y, &mut seeded_rng(seed))
+}
+
+/// As `random_front_first`, but shuffles each row using the given RNG.
+pub fn random_front_first_with_rng<R: Rng + ?Sized>(mut size_x: u16,
+                                                    size_y: u16,
+                                                    rng: &mut R)
     -> Result<Vec<Person>, &'static str> {
 
     log::info!("Generating random front-first boarding pattern");
@@ -125,7 +256,7 @@ pub fn random_front_first(mut size_x: u16, size_y: u16)
 
     for y in 0..size_y {
         let mut x_coords: Vec<u16> = (0..size_x).collect();
-        x_coords.shuffle(&mut thread_rng());
+        x_coords.shuffle(rng);
         for x in x_coords {
             if x != aisle {
                 let mut person = Person::new("DEFAULT");
@@ -141,7 +272,22 @@ pub fn random_front_first(mut size_x: u16, size_y: u16)
 
 /// Generates a list of passengers that will board in standard window-first
 /// order with randomised positions on each row.
-pub fn random_window_first(mut size_x: u16, size_y: u16)
+pub fn random_window_first(size_x: u16, size_y: u16)
+    -> Result<Vec<Person>, &'static str> {
+    random_window_first_with_rng(size_x, size_y, &mut thread_rng())
+}
+
+/// As `random_window_first`, but shuffles each column using an RNG seeded with
+/// `seed`.
+pub fn random_window_first_seeded(size_x: u16, size_y: u16, seed: u64)
+    -> Result<Vec<Person>, &'static str> {
+    random_window_first_with_rng(size_x, size_y, &mut seeded_rng(seed))
+}
+
+/// As `random_window_first`, but shuffles each column using the given RNG.
+pub fn random_window_first_with_rng<R: Rng + ?Sized>(mut size_x: u16,
+                                                     size_y: u16,
+                                                     rng: &mut R)
     -> Result<Vec<Person>, &'static str> {
 
     log::info!("Generating random aisle-first boarding pattern");
@@ -154,7 +300,7 @@ pub fn random_window_first(mut size_x: u16, size_y: u16)
 
     for x in 0..aisle {
         let mut y_coords: Vec<u16> = (0..size_y).collect();
-        y_coords.shuffle(&mut thread_rng());
+        y_coords.shuffle(rng);
         for y in y_coords {
             let mut person0 = Person::new("DEFAULT");
             let mut person1 = Person::new("DEFAULT");
@@ -174,7 +320,22 @@ pub fn random_window_first(mut size_x: u16, size_y: u16)
 
 /// Generates a list of passengers that will board in standard aisle-first
 /// order with randomised positions on each row.
-pub fn random_aisle_first(mut size_x: u16, size_y: u16)
+pub fn random_aisle_first(size_x: u16, size_y: u16)
+    -> Result<Vec<Person>, &'static str> {
+    random_aisle_first_with_rng(size_x, size_y, &mut thread_rng())
+}
+
+/// As `random_aisle_first`, but shuffles each column using an RNG seeded with
+/// `seed`.
+pub fn random_aisle_first_seeded(size_x: u16, size_y: u16, seed: u64)
+    -> Result<Vec<Person>, &'static str> {
+    random_aisle_first_with_rng(size_x, size_y, &mut seeded_rng(seed))
+}
+
+/// As `random_aisle_first`, but shuffles each column using the given RNG.
+pub fn random_aisle_first_with_rng<R: Rng + ?Sized>(mut size_x: u16,
+                                                    size_y: u16,
+                                                    rng: &mut R)
     -> Result<Vec<Person>, &'static str> {
 
     log::info!("Generating random aisle-first boarding pattern");
@@ -187,7 +348,7 @@ pub fn random_aisle_first(mut size_x: u16, size_y: u16)
 
     for x in 0..aisle {
         let mut y_coords: Vec<u16> = (0..size_y).collect();
-        y_coords.shuffle(&mut thread_rng());
+        y_coords.shuffle(rng);
         for y in y_coords {
             let mut person0 = Person::new("DEFAULT");
             let mut person1 = Person::new("DEFAULT");
@@ -205,8 +366,89 @@ pub fn random_aisle_first(mut size_x: u16, size_y: u16)
     Ok(persons)
 }
 
+/// Generates a list of passengers that will board by the Steffen method.
+///
+/// Window seats board first, then middle seats, then aisle seats. Within each,
+/// passengers board on one side of the aisle at a time, every other row
+/// starting from the one furthest from the entrance, so that each stops two
+/// rows short of the one before and can stow its bag at the same time. This
+/// matches `config/steffen.csv`, with the last passenger in the list boarding
+/// first under the default `BoardingOrder`.
+pub fn steffen_perfect(mut size_x: u16, size_y: u16)
+    -> Result<Vec<Person>, &'static str> {
+
+    log::info!("Generating Steffen boarding pattern");
+    if size_x % 2 == 0 {
+        log::warn!("Invalid size_x value; assuming closest odd value.");
+        size_x += 1;
+    }
+    let mut persons = Vec::<Person>::new();
+    let aisle: u16 = size_x / 2;
+
+    for x in 0..aisle {
+        for parity in &[1, 0] {
+            for column in &[aisle - (x + 1), aisle + (x + 1)] {
+                for y in (0..size_y).rev().filter(|y| y % 2 == *parity) {
+                    let mut person = Person::new("DEFAULT");
+                    person.target_seat(*column, y);
+                    person.set_baggage(true);
+                    persons.push(person);
+                }
+            }
+        }
+    }
+
+    Ok(persons)
+}
+
+/// Generates a list of passengers that will board back to front, one row at a
+/// time, with each row filling from the windows in towards the aisle.
+///
+/// Nobody ever has to squeeze past a seated passenger to reach their seat, so
+/// no seat interferences occur. The last passenger in the list boards first
+/// under the default `BoardingOrder`.
+pub fn no_shuffle_back_first(mut size_x: u16, size_y: u16)
+    -> Result<Vec<Person>, &'static str> {
+
+    log::info!("Generating no-shuffle back-first boarding pattern");
+    if size_x % 2 == 0 {
+        log::warn!("Invalid size_x value; assuming closest odd value.");
+        size_x += 1;
+    }
+    let mut persons = Vec::<Person>::new();
+    let aisle: u16 = size_x / 2;
+
+    for y in (0..size_y).rev() {
+        for x in 0..aisle {
+            for column in &[aisle - (x + 1), aisle + (x + 1)] {
+                let mut person = Person::new("DEFAULT");
+                person.target_seat(*column, y);
+                person.set_baggage(true);
+                persons.push(person);
+            }
+        }
+    }
+
+    Ok(persons)
+}
+
 /// Generates a list of passengers that will board in completely random order.
-pub fn random(mut size_x: u16, size_y: u16) -> Result<Vec<Person>, &'static str> {
+pub fn random(size_x: u16, size_y: u16)
+    -> Result<Vec<Person>, &'static str> {
+    random_with_rng(size_x, size_y, &mut thread_rng())
+}
+
+/// As `random`, but shuffles the passengers using an RNG seeded with `seed`.
+pub fn random_seeded(size_x: u16, size_y: u16, seed: u64)
+    -> Result<Vec<Person>, &'static str> {
+    random_with_rng(size_x, size_y, &mut seeded_rng(seed))
+}
+
+/// As `random`, but shuffles the passengers using the given RNG.
+pub fn random_with_rng<R: Rng + ?Sized>(mut size_x: u16,
+                                        size_y: u16,
+                                        rng: &mut R)
+    -> Result<Vec<Person>, &'static str> {
     log::info!("Generating random aisle-first boarding pattern");
     if size_x % 2 == 0 {
         log::warn!("Invalid size_x value; assuming closest odd value.");
@@ -224,7 +466,7 @@ pub fn random(mut size_x: u16, size_y: u16) -> Result<Vec<Person>, &'static str>
         }
     }
 
-    coords.shuffle(&mut thread_rng());
+    coords.shuffle(rng);
 
     for i in coords {
         let mut person = Person::new("DEFAULT");
@@ -238,101 +480,1849 @@ pub fn random(mut size_x: u16, size_y: u16) -> Result<Vec<Person>, &'static str>
     Ok(persons)
 }
 
-/// Reads a list of passengers from a correctly formatted csv file and returns
-/// them as a vector of `Person` objects.
-pub fn read_passengers(path: &Path) -> Option<Vec<Person>> {
+/// A fare class for `composite`, seated on rows `first_row` up to but not
+/// including `last_row` and boarded in `zones` groups of those rows.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClassRule {
+    pub name: String,
+    pub first_row: u16,
+    pub last_row: u16,
+    pub zones: u16,
+}
+
+impl ClassRule {
+    /// Constructor
+    pub fn new(name: &str, first_row: u16, last_row: u16, zones: u16)
+        -> ClassRule {
+        ClassRule {
+            name: name.to_string(),
+            first_row,
+            last_row,
+            zones,
+        }
+    }
+}
+
+/// Generates a list of passengers that will board class by class, in the
+/// order the classes are given, and by zone within each class, as airlines
+/// board first class before economy.
+///
+/// Each class's rows are split into zones as `random_zones` does, from the
+/// back of the class forward, with randomised positions within each zone
+/// using a `SeededRng` created from `seed`. Passengers are named after their
+/// class, and their zones are numbered on from one class to the next, from 0
+/// for the first to board. Rows not in any class are left empty. Returns an
+/// error if a class has no rows, rows outside the aircraft, rows shared with
+/// another class, or no zones or more zones than rows.
+pub fn composite(priorities: &[ClassRule], size_x: u16, size_y: u16, seed: u64)
+    -> Result<Vec<Person>, &'static str> {
+    composite_with_rng(priorities, size_x, size_y, &mut seeded_rng(seed))
+}
+
+/// As `composite`, but shuffles each zone using the given RNG.
+pub fn composite_with_rng<R: Rng + ?Sized>(priorities: &[ClassRule],
+                                           mut size_x: u16,
+                                           size_y: u16,
+                                           rng: &mut R)
+    -> Result<Vec<Person>, &'static str> {
+
+    log::info!("Generating {}-class boarding pattern", priorities.len());
+    if size_x % 2 == 0 {
+        log::warn!("Invalid size_x value; assuming closest odd value.");
+        size_x += 1;
+    }
+    for (i, class) in priorities.iter().enumerate() {
+        if class.first_row >= class.last_row || class.last_row > size_y {
+            return Err("Invalid rows for fare class");
+        }
+        if class.zones == 0 || class.zones > class.last_row - class.first_row {
+            return Err("Invalid number of zones");
+        }
+        if priorities[..i].iter().any(|other| {
+            class.first_row < other.last_row && other.first_row < class.last_row
+        }) {
+            return Err("Fare classes share rows");
+        }
+    }
+
     let mut persons = Vec::<Person>::new();
-    let file = File::open(path).expect("Invalid file path");
-    let mut rdr = csv::Reader::from_reader(file);
-    for result in rdr.records() {
-        let record = result.expect("Result is invalid record");
-        let mut data = Person::new(
-            &record[0],
-        );
-        data.target_seat(record[1].parse().expect("Invalid x coord"),
-                         record[2].parse().expect("Invalid y coord"));
-        data.set_baggage(match &record[3] {
-            "0" => false,
-            "1" => true,
-            _ => {
-                log::error!("Invalid baggage value, defaulting to 0");
-                false
-            },
-        });
-        persons.push(data);
+    let aisle: u16 = size_x / 2;
+    let mut zone: u16 = 0;
+    for class in priorities {
+        let rows = (class.last_row - class.first_row) as u32;
+        for class_zone in 0..class.zones as u32 {
+            let first = rows * class_zone / class.zones as u32;
+            let last = rows * (class_zone + 1) / class.zones as u32;
+            let mut coords: Vec<(u16,u16)> = Vec::new();
+            for row in first..last {
+                for x in 0..size_x {
+                    if x != aisle {
+                        coords.push((x, class.last_row - (row as u16 + 1)));
+                    }
+                }
+            }
+
+            coords.shuffle(rng);
+
+            for i in coords {
+                let mut person = Person::new(&class.name);
+                person.target_seat(i.0, i.1);
+                person.set_baggage(true);
+                person.set_zone(Some(zone));
+                persons.push(person);
+            }
+            zone += 1;
+        }
     }
-    return Some(persons);
+
+    Ok(persons)
 }
 
-/// Reads a list of tiles from a correctly formatted csv and returns them as an
-/// `Aircraft` object with that layout.
-pub fn read_layout(path: &Path) -> Option<Aircraft> {
-    let mut seats = Vec::<seat_data>::new();
-    let file = File::open(path).expect("Invalid file path");
-    let mut rdr = csv::Reader::from_reader(file);
-    
-    for result in rdr.records() {
-        let record = result.expect("Result is invalid record");
-        let data = seat_data::new(
-            record[0].parse().expect("Couldn't parse"),
-            record[1].parse().expect("Couldn't parse"),
-            &record[2],
-        );
-        seats.push(data);
+/// Generates a list of passengers that will board in `zones` groups of rows,
+/// from the back of the aircraft to the front, with randomised positions
+/// within each zone.
+///
+/// Each passenger's zone is set, numbered from 0 for the first to board, so
+/// that `BoardingStats::per_zone` is filled in. Returns an error if there are
+/// no zones or more zones than rows.
+pub fn random_zones(size_x: u16, size_y: u16, zones: u16)
+    -> Result<Vec<Person>, &'static str> {
+    random_zones_with_rng(size_x, size_y, zones, &mut thread_rng())
+}
+
+/// As `random_zones`, but shuffles each zone using an RNG seeded with `seed`.
+pub fn random_zones_seeded(size_x: u16, size_y: u16, zones: u16, seed: u64)
+    -> Result<Vec<Person>, &'static str> {
+    random_zones_with_rng(size_x, size_y, zones, &mut seeded_rng(seed))
+}
+
+/// As `random_zones`, but shuffles each zone using the given RNG.
+pub fn random_zones_with_rng<R: Rng + ?Sized>(mut size_x: u16,
+                                              size_y: u16,
+                                              zones: u16,
+                                              rng: &mut R)
+    -> Result<Vec<Person>, &'static str> {
+
+    log::info!("Generating random {}-zone boarding pattern", zones);
+    if zones == 0 || zones > size_y {
+        return Err("Invalid number of zones");
     }
-    
-    let (mut size_x, mut size_y) = (0, 0);
-    for i in &seats {
-        if i.get_x() > size_x { size_x = i.get_x() }
-        if i.get_y() > size_y { size_y = i.get_y() }
+    if size_x % 2 == 0 {
+        log::warn!("Invalid size_x value; assuming closest odd value.");
+        size_x += 1;
     }
-    size_x = size_x + 1;
-    size_y = size_y + 1;
+    let mut persons = Vec::<Person>::new();
+    let aisle: u16 = size_x / 2;
 
-    let mut aircraft = Aircraft::new(size_x, size_y);
-    for i in seats {
-        aircraft.set_tile(i.get_x(), i.get_y(), i.get_variant());
+    for zone in 0..zones {
+        // Rows are shared out as evenly as possible, counting from the back.
+        let first = size_y as u32 * zone as u32 / zones as u32;
+        let last = size_y as u32 * (zone as u32 + 1) / zones as u32;
+        let mut coords: Vec<(u16,u16)> = Vec::new();
+        for row in first..last {
+            for x in 0..size_x {
+                if x != aisle {
+                    coords.push((x, size_y - (row as u16 + 1)));
+                }
+            }
+        }
+
+        coords.shuffle(rng);
+
+        for i in coords {
+            let mut person = Person::new("DEFAULT");
+            person.target_seat(i.0, i.1);
+            person.set_baggage(true);
+            person.set_zone(Some(zone));
+            persons.push(person);
+        }
     }
 
-    return Some(aircraft);
+    Ok(persons)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Generates a list of passengers with one for every seat in the given
+/// aircraft, in the order given by `method`.
+///
+/// Unlike the other generators, this uses the aircraft's actual seat tiles, so
+/// works with any layout. `method` is one of `"back_first"`, `"front_first"`,
+/// `"window_first"`, `"aisle_first"`, `"both_ends"`, or `"random"`; seats that
+/// tie under the chosen method are shuffled using a `SeededRng` created from
+/// `seed`.
+///
+/// `"both_ends"` is meant for aircraft with more than one aisle. Rows are
+/// filled back to front, and each row from both ends at once, with the seats
+/// furthest from their nearest aisle first, so that every block of seats fills
+/// in towards the aisle it is entered from.
+pub fn fill_layout(aircraft: &Aircraft, method: &str, seed: u64)
+    -> Result<Vec<Person>, &'static str> {
+    fill_layout_with_rng(aircraft, method, &mut seeded_rng(seed))
+}
 
-    // #[test]
-    // fn test_read_passengers() {
-    //     let persons = read_passengers(Path::new("./config/test_passengers.csv")).unwrap();
+/// As `fill_layout`, but shuffles tied seats using the given RNG.
+pub fn fill_layout_with_rng<R: Rng + ?Sized>(aircraft: &Aircraft,
+                                             method: &str,
+                                             rng: &mut R)
+    -> Result<Vec<Person>, &'static str> {
 
-    //     assert_eq!(persons.len(), 2, "Incorrect number of records");
+    log::info!("Generating {} boarding pattern for layout", method);
+    let (size_x, size_y) = aircraft.get_size();
+    let mut coords: Vec<(u16,u16)> = Vec::new();
+    for x in 0..size_x {
+        for y in 0..size_y {
+            if aircraft.get_tile_variant(x, y) == Variant::Seat {
+                coords.push((x,y));
+            }
+        }
+    }
 
-    //     let person = persons.get(0).expect("First record not found");
+    coords.shuffle(rng);
 
-    //     assert_eq!(person.get_name(), "person0", "First record's name was wrong");
-    //     assert_eq!(person.get_seat().unwrap().0.to_string(), "0", "First record's x coord was wrong");
-    // }
+    // The sort is stable, so tied seats stay shuffled.
+    match method {
+        "back_first" => coords.sort_by_key(|s| Reverse(s.1)),
+        "front_first" => coords.sort_by_key(|s| s.1),
+        "window_first" => coords.sort_by_key(|s| {
+            Reverse(aircraft.aisle_distance(s.0, s.1))
+        }),
+        "aisle_first" => coords.sort_by_key(|s| {
+            aircraft.aisle_distance(s.0, s.1)
+        }),
+        "both_ends" => coords.sort_by_key(|s| {
+            (Reverse(s.1), Reverse(aircraft.aisle_distance(s.0, s.1)))
+        }),
+        "random" => (),
+        _ => return Err("Unknown boarding method"),
+    }
 
-    #[test]
-    fn test_read_layout() {
-        let aircraft = read_layout(Path::new("./config/test_layout.csv")).unwrap();
+    let mut persons = Vec::<Person>::new();
+    for i in coords {
+        let mut person = Person::new("DEFAULT");
 
-        assert_eq!((5,5), aircraft.get_size());
+        person.target_seat(i.0, i.1);
+        person.set_baggage(true);
+
+        persons.push(person);
     }
 
-    #[test]
-    fn test_str_to_var() {
-        assert_eq!(str_to_var("aisle"), Variant::Aisle);
-        assert_eq!(str_to_var("seat"), Variant::Seat);
-        assert_eq!(str_to_var("entrance"), Variant::Entrance);
-        assert_eq!(str_to_var("none"), Variant::None);
-        assert_eq!(str_to_var("invalid"), Variant::None);
+    Ok(persons)
+}
+
+/// Reorders a list of passengers slightly, as passengers queueing at the gate
+/// don't keep perfectly to their boarding order.
+///
+/// Each pair of neighbouring passengers is swapped with probability
+/// `swap_prob`, working from the front of the list to the back, using a
+/// `SeededRng` created from `seed`. Panics if `swap_prob` isn't between 0 and
+/// 1.
+pub fn perturb_order(passengers: &mut [Person], swap_prob: f32, seed: u64) {
+    perturb_order_with_rng(passengers, swap_prob, &mut seeded_rng(seed));
+}
+
+/// As `perturb_order`, but makes its random choices using the given RNG.
+pub fn perturb_order_with_rng<R: Rng + ?Sized>(passengers: &mut [Person],
+                                               swap_prob: f32,
+                                               rng: &mut R) {
+    for i in 1..passengers.len() {
+        if rng.gen_bool(swap_prob as f64) {
+            passengers.swap(i - 1, i);
+        }
     }
+}
 
-    #[test]
-    fn rand_back_first() {
-        let list = random_back_first(5, 10).unwrap();
+/// Gives each passenger its own personality seed, drawn using a `SeededRng`
+/// created from `seed`, for the random draws later made about it alone.
+///
+/// Seeds are handed out in the order given, so this should be called before
+/// the passengers are reordered; after that each passenger's draws stay the
+/// same however the others change.
+pub fn assign_personalities(passengers: &mut [Person], seed: u64) {
+    let mut rng = seeded_rng(seed);
+    for person in passengers.iter_mut() {
+        person.set_personality(Some(rng.gen()));
+    }
+}
 
-        assert_eq!(list.len(), 40);
+/// Gives each passenger a randomly drawn stow delay, the number of extra
+/// updates it takes to stow its baggage, as stowing is usually quick but
+/// occasionally very slow.
+///
+/// Delays are drawn from a log-normal distribution with the given `mean`, whose
+/// spread is set by `shape` (the standard deviation of its logarithm), using a
+/// `SeededRng` created from `seed`, then rounded and capped at `u8::MAX`. A
+/// passenger with a personality draws its delay from a `SeededRng` created from
+/// that instead. Returns an error if `mean` or `shape` is negative.
+pub fn assign_stow_times(passengers: &mut [Person],
+                         mean: f64,
+                         shape: f64,
+                         seed: u64)
+    -> Result<(), &'static str> {
+    assign_stow_times_with_rng(passengers, mean, shape, &mut seeded_rng(seed))
+}
+
+/// As `assign_stow_times`, but draws delays using the given RNG.
+pub fn assign_stow_times_with_rng<R: Rng + ?Sized>(passengers: &mut [Person],
+                                                   mean: f64,
+                                                   shape: f64,
+                                                   rng: &mut R)
+    -> Result<(), &'static str> {
+    if mean < 0.0 || shape < 0.0 {
+        return Err("Stow time mean and shape must not be negative");
+    }
+    // Chosen so that the distribution's mean is `mean`.
+    let mu = mean.ln() - shape * shape / 2.0;
+    for person in passengers.iter_mut() {
+        let z = match person.get_personality() {
+            Some(personality) => standard_normal(&mut seeded_rng(personality)),
+            None => standard_normal(rng),
+        };
+        let delay = (mu + shape * z).exp().round().min(u8::MAX as f64);
+        person.set_stow_delay(delay as u8);
+    }
+    Ok(())
+}
+
+/// Draws a sample from the standard normal distribution, by the Box-Muller
+/// transform.
+fn standard_normal<R: Rng + ?Sized>(rng: &mut R) -> f64 {
+    let u: f64 = 1.0 - rng.gen::<f64>();
+    let v: f64 = rng.gen();
+    (-2.0 * u.ln()).sqrt() * (2.0 * std::f64::consts::PI * v).cos()
+}
+
+/// Boards an aircraft up to update `arrival_tick`, then adds a rush of
+/// passengers off a late connecting flight, given as names and seats.
+///
+/// Rush passengers have no baggage and normal mobility, so make for their seats
+/// as quickly as any passenger can, and board ahead of anyone still waiting
+/// under the default `BoardingOrder`. Returns an error, without updating the
+/// aircraft, if it has already passed `arrival_tick` or a rush passenger's
+/// seat isn't a seat.
+pub fn add_connection_rush(aircraft: &mut Aircraft,
+                           names_and_seats: &[(&str, (u16, u16))],
+                           arrival_tick: u16)
+    -> Result<(), &'static str> {
+    if aircraft.get_stats().ticks > arrival_tick {
+        return Err("Boarding is already past the rush's arrival");
+    }
+    let (size_x, size_y) = aircraft.get_size();
+    for (_, (x, y)) in names_and_seats {
+        if *x >= size_x || *y >= size_y
+        || aircraft.get_tile_variant(*x, *y) != Variant::Seat {
+            return Err("Rush passenger's seat is not a seat");
+        }
+    }
+
+    while aircraft.get_stats().ticks < arrival_tick {
+        aircraft.update();
+    }
+    for (name, (x, y)) in names_and_seats {
+        let mut person = Person::new(name);
+        person.target_seat(*x, *y);
+        aircraft.add_passenger(person);
+    }
+    Ok(())
+}
+
+/// Generates a plausible flight: a standard-layout aircraft of the given size
+/// and a mixed list of passengers to board it.
+///
+/// `load_factor` is the fraction of seats that are sold, `baggage_prob` the
+/// chance each passenger has carry-on baggage, `slow_stower_frac` the fraction
+/// that take `SLOW_STOWER_DELAY` extra steps to stow it, and `group_frac` the
+/// fraction travelling in pairs, who sit next to each other and board one
+/// after the other. Passengers are otherwise seated and ordered at random using
+/// a `SeededRng` created from `seed`, and the aircraft is set to board them in
+/// the order given. Returns an error if any fraction isn't between 0 and 1.
+pub fn realistic_flight(size_x: u16,
+                        size_y: u16,
+                        load_factor: f64,
+                        baggage_prob: f64,
+                        slow_stower_frac: f64,
+                        group_frac: f64,
+                        seed: u64)
+    -> Result<(Aircraft, Vec<Person>), &'static str> {
+
+    log::info!("Generating realistic flight");
+    for fraction in &[load_factor, baggage_prob, slow_stower_frac, group_frac] {
+        if !(0.0..=1.0).contains(fraction) {
+            return Err("Fractions must be between 0 and 1");
+        }
+    }
+    let mut rng = seeded_rng(seed);
+    let mut aircraft = standard_layout(size_x, size_y)?;
+    aircraft.set_boarding_order(BoardingOrder::AsAdded);
+
+    let mut seats: Vec<(u16,u16)> =
+        fill_layout_with_rng(&aircraft, "random", &mut rng)?.iter()
+            .map(|p| p.get_seat().unwrap())
+            .collect();
+    seats.truncate((seats.len() as f64 * load_factor).round() as usize);
+    seats.sort_by_key(|s| (s.1, s.0));
+
+    // Pairs up neighbouring seats until enough passengers are in groups.
+    let mut pairs = (seats.len() as f64 * group_frac / 2.0).round() as usize;
+    let mut parties = Vec::<Vec<(u16,u16)>>::new();
+    let mut i = 0;
+    while i < seats.len() {
+        let (x, y) = seats[i];
+        if pairs > 0 && i + 1 < seats.len() && seats[i + 1] == (x + 1, y) {
+            parties.push(vec![seats[i], seats[i + 1]]);
+            pairs -= 1;
+            i += 2;
+        } else {
+            parties.push(vec![seats[i]]);
+            i += 1;
+        }
+    }
+    parties.shuffle(&mut rng);
+
+    let mut persons = Vec::<Person>::new();
+    for (group, party) in parties.iter().enumerate() {
+        for seat in party {
+            let mut person = Person::new("DEFAULT");
+            person.target_seat(seat.0, seat.1);
+            person.set_baggage(rng.gen_bool(baggage_prob));
+            if rng.gen_bool(slow_stower_frac) {
+                person.set_stow_delay(SLOW_STOWER_DELAY);
+            }
+            if party.len() > 1 {
+                person.set_group(Some(group as u16));
+            }
+            persons.push(person);
+        }
+    }
+
+    Ok((aircraft, persons))
+}
+
+/// Checks that boarding is deterministic by boarding a standard-layout
+/// aircraft of the given size twice, with passengers generated by
+/// `fill_layout` from `method` and `seed`.
+///
+/// Returns true if both boardings end with identical `BoardingStats` and every
+/// passenger in the same place, or false if they differ or the scenario can't
+/// be set up.
+pub fn verify_deterministic(method: &str, size_x: u16, size_y: u16, seed: u64)
+    -> bool {
+    runs_match(|| {
+        let mut aircraft = standard_layout(size_x, size_y)?;
+        for i in fill_layout(&aircraft, method, seed)? {
+            aircraft.add_passenger(i);
+        }
+        Ok(aircraft)
+    })
+}
+
+/// Boards two aircraft set up by `scenario`, checking whether they end up
+/// identical.
+fn runs_match<F>(mut scenario: F) -> bool
+    where F: FnMut() -> Result<Aircraft, &'static str> {
+    let mut results = Vec::<(BoardingStats, u64)>::new();
+    for _ in 0..2 {
+        let mut aircraft = match scenario() {
+            Ok(aircraft) => aircraft,
+            Err(e) => {
+                log::warn!("Couldn't set up scenario: {}", e);
+                return false;
+            },
+        };
+        // A boarding that times out should still do so identically.
+        let _ = aircraft.run_to_completion();
+        results.push((aircraft.get_stats().clone(), aircraft.fingerprint()));
+    }
+    results[0] == results[1]
+}
+
+/// Boards a standard-layout aircraft of the given size `runs` times, with
+/// passengers generated by `fill_layout` from `method`, returning the number of
+/// steps each boarding took.
+///
+/// Run `i` uses the seed `base_seed + i`. Returns an error if the scenario
+/// can't be set up or any boarding times out.
+pub fn benchmark(method: &str,
+                 size_x: u16,
+                 size_y: u16,
+                 runs: u16,
+                 base_seed: u64)
+    -> Result<Vec<u16>, &'static str> {
+    benchmark_perturbed(method, size_x, size_y, runs, base_seed, 0.0)
+}
+
+/// As `benchmark`, but with the passengers of each run reordered by
+/// `perturb_order` with `swap_prob`, using the run's seed.
+fn benchmark_perturbed(method: &str,
+                       size_x: u16,
+                       size_y: u16,
+                       runs: u16,
+                       base_seed: u64,
+                       swap_prob: f32)
+    -> Result<Vec<u16>, &'static str> {
+    let mut ticks = Vec::<u16>::new();
+    for i in 0..runs {
+        let seed = base_seed + i as u64;
+        let mut aircraft = standard_layout(size_x, size_y)?;
+        let mut passengers = fill_layout(&aircraft, method, seed)?;
+        if swap_prob > 0.0 {
+            perturb_order(&mut passengers, swap_prob, seed);
+        }
+        for person in passengers {
+            aircraft.add_passenger(person);
+        }
+        match aircraft.run_to_completion() {
+            Ok(stats) => {
+                ticks.push(stats.complete_tick.unwrap_or(stats.ticks))
+            },
+            Err(_) => return Err("Boarding timed out"),
+        }
+    }
+    Ok(ticks)
+}
+
+/// The results of a `sweep`, giving the mean number of steps taken to board
+/// with each method and aircraft size.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SweepResult {
+    pub methods: Vec<String>,
+    pub sizes: Vec<(u16,u16)>,
+    pub runs: u16,
+    pub base_seed: u64,
+    /// Indexed `[method][size]`, in the order `methods` and `sizes` are given.
+    pub mean_ticks: Vec<Vec<f64>>,
+}
+
+/// Benchmarks every method on every aircraft size, `runs` times each, giving
+/// a matrix of mean boarding times.
+///
+/// Every cell uses the same seeds, starting from `base_seed`, as `benchmark`
+/// does. Returns an error if any method is unknown or any scenario can't be
+/// set up or boarded.
+pub fn sweep(methods: &[&str], sizes: &[(u16,u16)], runs: u16, base_seed: u64)
+    -> Result<SweepResult, &'static str> {
+    if runs == 0 {
+        return Err("A sweep needs at least one run");
+    }
+    let mut mean_ticks = Vec::<Vec<f64>>::new();
+    for method in methods {
+        log::info!("Sweeping {} across {} sizes", method, sizes.len());
+        let mut row = Vec::<f64>::new();
+        for (size_x, size_y) in sizes {
+            row.push(sweep_cell(method, *size_x, *size_y, runs, base_seed)?);
+        }
+        mean_ticks.push(row);
+    }
+
+    Ok(SweepResult {
+        methods: methods.iter().map(|m| m.to_string()).collect(),
+        sizes: sizes.to_vec(),
+        runs,
+        base_seed,
+        mean_ticks,
+    })
+}
+
+/// As `sweep`, but saves each cell to the csv file at `checkpoint` as soon as
+/// it is done, so that a sweep that is interrupted can be carried on from
+/// where it left off by calling this again with the same file.
+///
+/// Cells already in the file for the same number of runs and base seed are
+/// read from it rather than benchmarked again. Returns an error if the file
+/// can't be read or written, or as `sweep` does.
+pub fn sweep_with_checkpoint(methods: &[&str],
+                             sizes: &[(u16,u16)],
+                             runs: u16,
+                             base_seed: u64,
+                             checkpoint: &Path)
+    -> Result<SweepResult, ConfigError> {
+    let error = |message: &str| ConfigError::new(checkpoint, None, None,
+                                                 message);
+    if runs == 0 {
+        return Err(error("A sweep needs at least one run"));
+    }
+
+    let mut done = Vec::<(String, (u16, u16), f64)>::new();
+    if checkpoint.exists() {
+        for (line, record) in read_records(checkpoint, 6)? {
+            let path = checkpoint;
+            let cell_runs: u16 = parse_field(path, line, &record, 3,
+                                             "Invalid run count")?;
+            let cell_seed: u64 = parse_field(path, line, &record, 4,
+                                             "Invalid seed")?;
+            if cell_runs == runs && cell_seed == base_seed {
+                let size = (parse_field(path, line, &record, 1,
+                                        "Invalid x size")?,
+                            parse_field(path, line, &record, 2,
+                                        "Invalid y size")?);
+                let mean = parse_field(path, line, &record, 5,
+                                       "Invalid mean")?;
+                done.push((record[0].to_string(), size, mean));
+            }
+        }
+    } else {
+        std::fs::write(checkpoint, "METHOD,X,Y,RUNS,SEED,MEAN_TICKS\n")
+            .map_err(|e| error(&e.to_string()))?;
+    }
+
+    let mut file = OpenOptions::new().append(true).open(checkpoint)
+        .map_err(|e| error(&e.to_string()))?;
+    let mut mean_ticks = Vec::<Vec<f64>>::new();
+    for method in methods {
+        let mut row = Vec::<f64>::new();
+        for &size in sizes {
+            let saved = done.iter()
+                .find(|(m, s, _)| m == method && *s == size)
+                .map(|(_, _, mean)| *mean);
+            let mean = match saved {
+                Some(mean) => mean,
+                None => {
+                    let mean = sweep_cell(method, size.0, size.1, runs,
+                                          base_seed)
+                        .map_err(error)?;
+                    writeln!(file, "{},{},{},{},{},{}", method, size.0,
+                             size.1, runs, base_seed, mean)
+                        .map_err(|e| error(&e.to_string()))?;
+                    mean
+                },
+            };
+            row.push(mean);
+        }
+        mean_ticks.push(row);
+    }
+
+    Ok(SweepResult {
+        methods: methods.iter().map(|m| m.to_string()).collect(),
+        sizes: sizes.to_vec(),
+        runs,
+        base_seed,
+        mean_ticks,
+    })
+}
+
+/// Writes a table built by `Aircraft::trajectory_table` to the csv file at
+/// `path`, one line per row under a header, for loading into other tools.
+///
+/// Variants are written as `read_layout` reads them, and a row without a
+/// behaviour has that column left empty.
+pub fn write_trajectory(rows: &[TrajectoryRow], path: &Path)
+    -> Result<(), ConfigError> {
+    let error = |e: csv::Error| ConfigError::new(path, None, None,
+                                                 &e.to_string());
+    let mut wtr = csv::Writer::from_path(path).map_err(error)?;
+    wtr.write_record(["TICK", "ID", "PASSENGER", "X", "Y", "VARIANT",
+                      "BEHAVIOUR"]).map_err(error)?;
+    for row in rows {
+        let behaviour = match row.behaviour {
+            Some(behaviour) => behaviour.to_string(),
+            None => String::new(),
+        };
+        wtr.write_record([row.tick.to_string(), row.id.to_string(),
+                          row.passenger.clone(), row.x.to_string(),
+                          row.y.to_string(),
+                          var_to_str(row.variant).to_string(), behaviour])
+            .map_err(error)?;
+    }
+    wtr.flush().map_err(|e| ConfigError::new(path, None, None,
+                                             &e.to_string()))
+}
+
+/// Finds the mean number of steps taken to board over `runs` runs of
+/// `benchmark`.
+fn sweep_cell(method: &str,
+              size_x: u16,
+              size_y: u16,
+              runs: u16,
+              base_seed: u64) -> Result<f64, &'static str> {
+    let ticks = benchmark(method, size_x, size_y, runs, base_seed)?;
+    let total: f64 = ticks.iter().map(|&t| t as f64).sum();
+    Ok(total / runs as f64)
+}
+
+/// How consistently a method boards an aircraft, as found by `robustness`.
+///
+/// `mean`, `variance`, and `worst_case` are of the number of steps taken to
+/// board over every run, with passengers reordered with `swap_prob`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RobustnessReport {
+    pub method: String,
+    pub runs: u16,
+    pub base_seed: u64,
+    pub swap_prob: f32,
+    pub mean: f64,
+    pub variance: f64,
+    pub worst_case: u16,
+}
+
+/// Benchmarks a method on a standard-layout aircraft of the given size `runs`
+/// times, as `benchmark` does, but with passengers not quite keeping to their
+/// boarding order, reporting how much the time taken varies.
+///
+/// Passengers are reordered by `perturb_order` with a fixed swap probability,
+/// using each run's seed. Returns an error if `runs` is 0, or as `benchmark`
+/// does.
+pub fn robustness(method: &str,
+                  size_x: u16,
+                  size_y: u16,
+                  runs: u16,
+                  base_seed: u64)
+    -> Result<RobustnessReport, &'static str> {
+    if runs == 0 {
+        return Err("Robustness needs at least one run");
+    }
+    log::info!("Measuring robustness of {} over {} runs", method, runs);
+    let ticks = benchmark_perturbed(method, size_x, size_y, runs, base_seed,
+                                    ROBUSTNESS_SWAP_PROB)?;
+    let mean = ticks.iter().map(|&t| t as f64).sum::<f64>() / runs as f64;
+    let variance = ticks.iter()
+        .map(|&t| (t as f64 - mean).powi(2))
+        .sum::<f64>() / runs as f64;
+
+    Ok(RobustnessReport {
+        method: method.to_string(),
+        runs,
+        base_seed,
+        swap_prob: ROBUSTNESS_SWAP_PROB,
+        mean,
+        variance,
+        worst_case: *ticks.iter().max().unwrap(),
+    })
+}
+
+/// What `optimize_assignment` minimises.
+///
+/// `Ticks` is the number of steps taken to board, and `AisleTicks` the
+/// `total_aisle_person_ticks` spent by passengers standing in the aisle.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Objective {
+    Ticks,
+    AisleTicks,
+}
+
+/// Assigns seats to passengers boarding in a fixed order so as to board the
+/// given aircraft as well as possible by `objective`.
+///
+/// Starting from a random assignment, pairs of passengers have their seats
+/// swapped at random `iterations` times, with each swap kept if a simulated
+/// boarding of the aircraft's layout scores no worse for it. Passengers are
+/// returned in `arrival_order`, and board in that order. Returns an error if
+/// there are more passengers than seats.
+pub fn optimize_assignment(aircraft: &Aircraft,
+                           arrival_order: &[&str],
+                           iterations: u16,
+                           objective: Objective)
+    -> Result<Vec<Person>, &'static str> {
+    optimize_assignment_with_rng(aircraft,
+                                 arrival_order,
+                                 iterations,
+                                 objective,
+                                 &mut thread_rng())
+}
+
+/// As `optimize_assignment`, but makes its random choices using the given RNG.
+pub fn optimize_assignment_with_rng<R: Rng + ?Sized>(aircraft: &Aircraft,
+                                                     arrival_order: &[&str],
+                                                     iterations: u16,
+                                                     objective: Objective,
+                                                     rng: &mut R)
+    -> Result<Vec<Person>, &'static str> {
+
+    log::info!("Optimising seat assignment for {} passengers",
+               arrival_order.len());
+    let mut seats: Vec<(u16,u16)> =
+        fill_layout_with_rng(aircraft, "random", rng)?.iter()
+            .map(|p| p.get_seat().unwrap())
+            .collect();
+    if arrival_order.len() > seats.len() {
+        return Err("More passengers than seats");
+    }
+    let n = arrival_order.len();
+
+    let mut best = boarding_score(aircraft, arrival_order, &seats, objective);
+    if n > 1 {
+        for _ in 0..iterations {
+            // Swapping with a seat nobody is assigned is also allowed, so that
+            // every seat can be tried.
+            let a = rng.gen_range(0..n);
+            let b = rng.gen_range(0..seats.len());
+            seats.swap(a, b);
+            let score =
+                boarding_score(aircraft, arrival_order, &seats, objective);
+            if score <= best {
+                best = score;
+            } else {
+                seats.swap(a, b);
+            }
+        }
+    }
+    log::info!("Best assignment scores {} by {:?}", best, objective);
+
+    Ok(arrival_order.iter().zip(seats.iter())
+        .map(|(name, seat)| {
+            let mut person = Person::new(name);
+            person.target_seat(seat.0, seat.1);
+            person.set_baggage(true);
+            person
+        })
+        .collect())
+}
+
+/// Simulates boarding a copy of the aircraft's layout with passengers arriving
+/// in order and taking the seats given, returning the number of steps taken or
+/// `u16::MAX` if they could not all be seated.
+fn boarding_ticks(aircraft: &Aircraft, names: &[&str], seats: &[(u16,u16)])
+    -> u16 {
+    simulate_assignment(aircraft, names, seats).map_or(u16::MAX, |stats| {
+        stats.complete_tick.unwrap_or(stats.ticks)
+    })
+}
+
+/// As `boarding_ticks`, but returns the value of `objective` for the boarding,
+/// or `u32::MAX` if the passengers could not all be seated.
+fn boarding_score(aircraft: &Aircraft,
+                  names: &[&str],
+                  seats: &[(u16,u16)],
+                  objective: Objective) -> u32 {
+    simulate_assignment(aircraft, names, seats).map_or(u32::MAX, |stats| {
+        match objective {
+            Objective::Ticks => {
+                stats.complete_tick.unwrap_or(stats.ticks) as u32
+            },
+            Objective::AisleTicks => stats.total_aisle_person_ticks,
+        }
+    })
+}
+
+/// Boards a copy of the aircraft's layout as described for `boarding_ticks`,
+/// returning its statistics if every passenger was seated.
+fn simulate_assignment(aircraft: &Aircraft,
+                       names: &[&str],
+                       seats: &[(u16,u16)]) -> Option<BoardingStats> {
+    let (size_x, size_y) = aircraft.get_size();
+    let mut copy = Aircraft::new(size_x, size_y);
+    for x in 0..size_x {
+        for y in 0..size_y {
+            copy.set_tile(x, y, aircraft.get_tile_variant(x, y));
+        }
+    }
+    copy.set_boarding_order(BoardingOrder::AsAdded);
+    for (name, seat) in names.iter().zip(seats.iter()) {
+        let mut person = Person::new(name);
+        person.target_seat(seat.0, seat.1);
+        person.set_baggage(true);
+        copy.add_passenger(person);
+    }
+
+    copy.run_to_completion().ok()
+}
+
+/// Finds how much each passenger delays the boarding of an aircraft, by
+/// boarding it once with every passenger and again with each passenger left
+/// out in turn.
+///
+/// The aircraft should be set up but not yet boarded; it is copied for every
+/// run, keeping its settings, and passengers are added in the order given.
+/// Returns each passenger's name with the number of extra steps the boarding
+/// takes because of it, most delaying first, or an error if any boarding
+/// times out. This boards the aircraft once per passenger, so can be slow.
+pub fn marginal_delays(aircraft: &Aircraft, passengers: &[Person])
+    -> Result<Vec<(String, i32)>, &'static str> {
+    let snapshot = aircraft.snapshot();
+    let board = |leave_out: Option<usize>| {
+        let (size_x, size_y) = aircraft.get_size();
+        let mut copy = Aircraft::new(size_x, size_y);
+        copy.resume(&snapshot)?;
+        for (i, person) in passengers.iter().enumerate() {
+            if Some(i) != leave_out {
+                copy.add_passenger(person.clone());
+            }
+        }
+        copy.run_to_completion()
+            .map(|stats| stats.complete_tick.unwrap_or(stats.ticks))
+            .map_err(|_| "Boarding timed out")
+    };
+
+    log::info!("Finding marginal delays of {} passengers", passengers.len());
+    let total = board(None)? as i32;
+    let mut delays = Vec::<(String, i32)>::new();
+    for (i, person) in passengers.iter().enumerate() {
+        delays.push((person.get_name(), total - board(Some(i))? as i32));
+    }
+    delays.sort_by_key(|(_, delay)| Reverse(*delay));
+    Ok(delays)
+}
+
+/// An error reading a configuration file, giving where in the file it is.
+///
+/// `line` and `column` count from 1, and are `None` when the error isn't in
+/// any particular part of the file (e.g. the file doesn't exist).
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigError {
+    pub path: PathBuf,
+    pub line: Option<u64>,
+    pub column: Option<usize>,
+    pub message: String,
+}
+
+impl ConfigError {
+    /// Constructor
+    fn new(path: &Path,
+           line: Option<u64>,
+           column: Option<usize>,
+           message: &str) -> ConfigError {
+        ConfigError {
+            path: path.to_path_buf(),
+            line,
+            column,
+            message: message.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.path.display())?;
+        if let Some(line) = self.line {
+            write!(f, ":{}", line)?;
+            if let Some(column) = self.column {
+                write!(f, ":{}", column)?;
+            }
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+impl Error for ConfigError {}
+
+/// Reads the records of a csv file with headers, each with at least `fields`
+/// fields, along with the line each is on.
+fn read_records(path: &Path, fields: usize)
+    -> Result<Vec<(u64, csv::StringRecord)>, ConfigError> {
+    let file = File::open(path)
+        .map_err(|e| ConfigError::new(path, None, None, &e.to_string()))?;
+    let mut rdr = csv::Reader::from_reader(file);
+    let mut records = Vec::<(u64, csv::StringRecord)>::new();
+    for result in rdr.records() {
+        let record = result.map_err(|e| {
+            let line = e.position().map(|p| p.line());
+            ConfigError::new(path, line, None, &e.to_string())
+        })?;
+        let line = record.position().map_or(0, |p| p.line());
+        if record.len() < fields {
+            return Err(ConfigError::new(path,
+                                        Some(line),
+                                        Some(record.len() + 1),
+                                        "Missing field"));
+        }
+        records.push((line, record));
+    }
+    Ok(records)
+}
+
+/// Parses the field at `column` (counting from 0) of a csv record.
+fn parse_field<T: std::str::FromStr>(path: &Path,
+                                     line: u64,
+                                     record: &csv::StringRecord,
+                                     column: usize,
+                                     message: &str)
+    -> Result<T, ConfigError> {
+    record[column].trim().parse()
+        .map_err(|_| ConfigError::new(path, Some(line), Some(column + 1),
+                                      message))
+}
+
+/// Reads a list of passengers from a correctly formatted csv file and returns
+/// them as a vector of `Person` objects.
+///
+/// Returns an error giving the line and column of the first field that can't
+/// be read.
+pub fn read_passengers(path: &Path) -> Result<Vec<Person>, ConfigError> {
+    let mut persons = Vec::<Person>::new();
+    for (line, record) in read_records(path, 4)? {
+        let mut data = Person::new(
+            &record[0],
+        );
+        let x = parse_field(path, line, &record, 1, "Invalid x coord")?;
+        let y = parse_field(path, line, &record, 2, "Invalid y coord")?;
+        data.target_seat(x, y);
+        data.set_baggage(match record[3].trim() {
+            "0" => false,
+            "1" => true,
+            _ => return Err(ConfigError::new(path, Some(line), Some(4),
+                                             "Invalid baggage value")),
+        });
+        persons.push(data);
+    }
+    return Ok(persons);
+}
+
+/// Reads a list of tiles from a correctly formatted csv and returns them as an
+/// `Aircraft` object with that layout.
+///
+/// Returns an error giving the line and column of the first field that can't
+/// be read, or an error if no entrance leads to an aisle beside a seat.
+pub fn read_layout(path: &Path) -> Result<Aircraft, ConfigError> {
+    let mut seats = Vec::<seat_data>::new();
+    for (line, record) in read_records(path, 3)? {
+        let data = seat_data::new(
+            parse_field(path, line, &record, 0, "Invalid x coord")?,
+            parse_field(path, line, &record, 1, "Invalid y coord")?,
+            str_to_var(record[2].trim()).ok_or_else(|| {
+                ConfigError::new(path, Some(line), Some(3),
+                                 "Invalid tile variant")
+            })?,
+        );
+        seats.push(data);
+    }
+    
+    let (mut size_x, mut size_y) = (0, 0);
+    for i in &seats {
+        if i.get_x() > size_x { size_x = i.get_x() }
+        if i.get_y() > size_y { size_y = i.get_y() }
+    }
+    size_x = size_x + 1;
+    size_y = size_y + 1;
+
+    let mut aircraft = Aircraft::new(size_x, size_y);
+    for i in seats {
+        aircraft.set_tile(i.get_x(), i.get_y(), i.get_variant());
+    }
+    check_entrance(path, &aircraft)?;
+
+    return Ok(aircraft);
+}
+
+/// Checks that passengers can board an aircraft read from `path`, i.e. that it
+/// has an entrance from which an aisle or entrance tile beside a seat can be
+/// reached.
+fn check_entrance(path: &Path, aircraft: &Aircraft) -> Result<(), ConfigError> {
+    let layout = aircraft.get_layout();
+    let beside_seat = |x: usize, y: usize| {
+        (x > 0 && layout[x - 1][y].get_variant() == Variant::Seat)
+        || (x + 1 < layout.len()
+            && layout[x + 1][y].get_variant() == Variant::Seat)
+    };
+
+    let mut entrances = Vec::<(usize, usize)>::new();
+    for (x, column) in layout.iter().enumerate() {
+        for (y, tile) in column.iter().enumerate() {
+            if tile.get_variant() == Variant::Entrance {
+                entrances.push((x, y));
+            }
+        }
+    }
+    if entrances.is_empty() {
+        return Err(ConfigError::new(path, None, None,
+                                    "Layout has no entrance tile"));
+    }
+
+    for entrance in entrances {
+        let reached = grid::bfs(layout, entrance, |tile| {
+            matches!(tile.get_variant(), Variant::Aisle | Variant::Entrance)
+        });
+        if reached.keys().any(|&(x, y)| beside_seat(x, y)) {
+            return Ok(());
+        }
+    }
+    Err(ConfigError::new(path, None, None,
+                         "Layout has no entrance connected to an aisle \
+                          beside a seat"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::aircraft::SeatSort;
+    use super::super::aircraft::events::SimEvent;
+    use super::super::aircraft::person::Behaviour;
+
+    // #[test]
+    // fn test_read_passengers() {
+    //     let persons = read_passengers(Path::new("./config/test_passengers.csv")).unwrap();
+
+    //     assert_eq!(persons.len(), 2, "Incorrect number of records");
+
+    //     let person = persons.get(0).expect("First record not found");
+
+    //     assert_eq!(person.get_name(), "person0", "First record's name was wrong");
+    //     assert_eq!(person.get_seat().unwrap().0.to_string(), "0", "First record's x coord was wrong");
+    // }
+
+    #[test]
+    fn test_read_layout() {
+        let aircraft = read_layout(Path::new("./config/test_layout.csv")).unwrap();
+
+        assert_eq!((5,5), aircraft.get_size());
+    }
+
+    #[test]
+    fn test_str_to_var() {
+        assert_eq!(str_to_var("aisle"), Some(Variant::Aisle));
+        assert_eq!(str_to_var("seat"), Some(Variant::Seat));
+        assert_eq!(str_to_var("entrance"), Some(Variant::Entrance));
+        assert_eq!(str_to_var("none"), Some(Variant::None));
+        assert_eq!(str_to_var("invalid"), None);
+    }
+
+    #[test]
+    fn rand_back_first() {
+        let list = random_back_first(5, 10).unwrap();
+
+        assert_eq!(list.len(), 40);
+    }
+
+    #[test]
+    fn steffen_perfect() {
+        let list = super::steffen_perfect(6, 12).unwrap();
+        assert_eq!(list.len(), random_back_first(6, 12).unwrap().len());
+        assert!(list.iter().all(|p| p.has_baggage()));
+
+        let expected: Vec<(u16,u16)> =
+            read_passengers(Path::new("./config/steffen.csv")).unwrap()
+            .iter()
+            .map(|p| p.get_seat().unwrap())
+            .collect();
+        let seats: Vec<(u16,u16)> = list.iter()
+            .map(|p| p.get_seat().unwrap())
+            .collect();
+        assert_eq!(seats, expected);
+    }
+
+    #[test]
+    fn composite() {
+        let classes = [ClassRule::new("First", 0, 2, 1),
+                       ClassRule::new("Economy", 2, 12, 3)];
+        let list = super::composite(&classes, 7, 12, 5).unwrap();
+        assert_eq!(list.len(), 72);
+
+        let economy = list.iter()
+            .position(|p| p.get_name() == "Economy")
+            .unwrap();
+        assert_eq!(economy, 12);
+        assert!(list[..economy].iter().all(|p| {
+            p.get_name() == "First" && p.get_seat().unwrap().1 < 2
+                && p.get_zone() == Some(0)
+        }));
+        assert!(list[economy..].iter().all(|p| p.get_name() == "Economy"));
+        let zones: Vec<u16> = list[economy..].iter()
+            .map(|p| p.get_zone().unwrap())
+            .collect();
+        assert!(zones.windows(2).all(|w| w[0] <= w[1]));
+        assert_eq!((zones[0], zones[zones.len() - 1]), (1, 3));
+
+        let mut aircraft = standard_layout(7, 12).unwrap();
+        aircraft.set_boarding_order(BoardingOrder::AsAdded);
+        for person in list {
+            aircraft.add_passenger(person);
+        }
+        aircraft.run_to_completion().unwrap();
+
+        let overlapping = [ClassRule::new("First", 0, 3, 1),
+                           ClassRule::new("Economy", 2, 12, 3)];
+        assert!(super::composite(&overlapping, 7, 12, 5).is_err());
+        let too_many_zones = [ClassRule::new("First", 0, 2, 3)];
+        assert!(super::composite(&too_many_zones, 7, 12, 5).is_err());
+    }
+
+    #[test]
+    fn rand_with_fixed_rng() {
+        // A constant RNG always swaps with the first element when shuffling,
+        // which rotates each row left by one.
+        let mut rng = rand::rngs::mock::StepRng::new(0, 0);
+        let list = random_back_first_with_rng(5, 2, &mut rng).unwrap();
+        let seats: Vec<(u16,u16)> = list.iter()
+            .map(|p| p.get_seat().unwrap())
+            .collect();
+
+        assert_eq!(seats, vec![(1,1), (3,1), (4,1), (0,1),
+                               (1,0), (3,0), (4,0), (0,0)]);
+    }
+
+    #[test]
+    fn seeded_rng_is_repeatable() {
+        let first = random_with_rng(7, 10, &mut seeded_rng(42)).unwrap();
+        let second = random_with_rng(7, 10, &mut seeded_rng(42)).unwrap();
+
+        for (a, b) in first.iter().zip(second.iter()) {
+            assert_eq!(a.get_seat(), b.get_seat());
+        }
+    }
+
+    #[test]
+    fn congested_efficiency() {
+        let mut aircraft = standard_layout(7, 10).unwrap();
+        aircraft.set_boarding_order(BoardingOrder::AsAdded);
+        let list = random_front_first_with_rng(7, 10, &mut seeded_rng(5))
+            .unwrap();
+        for i in list {
+            aircraft.add_passenger(i);
+        }
+        aircraft.run_to_completion().unwrap();
+
+        let efficiency = aircraft.get_stats().efficiency;
+        println!("Efficiency: {}", efficiency);
+        assert!(efficiency > 0.0 && efficiency < 0.75);
+    }
+
+    #[test]
+    fn fill_loaded_layout() {
+        let aircraft = read_layout(Path::new("./config/test_layout.csv"))
+            .unwrap();
+        let seats = aircraft.seats_sorted_by(SeatSort::FrontToBack);
+
+        let list = fill_layout(&aircraft, "back_first", 7).unwrap();
+        assert_eq!(list.len(), seats.len());
+        let mut filled: Vec<(u16,u16)> = list.iter()
+            .map(|p| p.get_seat().unwrap())
+            .collect();
+        assert!(filled.windows(2).all(|w| w[0].1 >= w[1].1));
+        filled.sort_by_key(|s| (s.1, s.0));
+        assert_eq!(filled, seats);
+
+        let again = fill_layout(&aircraft, "back_first", 7).unwrap();
+        for (a, b) in list.iter().zip(again.iter()) {
+            assert_eq!(a.get_seat(), b.get_seat());
+        }
+
+        assert!(fill_layout(&aircraft, "sideways", 7).is_err());
+    }
+
+    #[test]
+    fn front_first_row_dwell() {
+        // Boarding through a door at the front.
+        let mut aircraft = standard_layout(7, 10).unwrap();
+        aircraft.set_tile(3, 9, Variant::Aisle);
+        aircraft.set_tile(3, 0, Variant::Entrance);
+        aircraft.set_boarding_order(BoardingOrder::AsAdded);
+        let list = random_front_first_with_rng(7, 10, &mut seeded_rng(3));
+        for i in list.unwrap() {
+            aircraft.add_passenger(i);
+        }
+        aircraft.run_to_completion().unwrap();
+
+        // Passengers for the back rows have to get past everyone still
+        // stowing their bags in front of them, so take longer to get seated.
+        let dwell = &aircraft.get_stats().row_dwell;
+        println!("Row dwell: {:?}", dwell);
+        assert_eq!(dwell.len(), 10);
+        assert!(dwell.iter().all(|d| *d > 0));
+        let front: u16 = dwell[..5].iter().sum();
+        let back: u16 = dwell[5..].iter().sum();
+        assert!(front < back);
+        assert!(dwell[0] < dwell[9]);
+    }
+
+    #[test]
+    fn optimized_assignment() {
+        let aircraft = standard_layout(5, 6).unwrap();
+        let names = ["a", "b", "c", "d", "e", "f", "g", "h", "i", "j"];
+
+        // The optimiser starts from the same random assignment when given the
+        // same seed.
+        let random: Vec<(u16,u16)> =
+            fill_layout_with_rng(&aircraft, "random", &mut seeded_rng(5))
+                .unwrap().iter()
+                .map(|p| p.get_seat().unwrap())
+                .collect();
+        let optimized = optimize_assignment_with_rng(&aircraft, &names, 30,
+                                                     Objective::Ticks,
+                                                     &mut seeded_rng(5))
+            .unwrap();
+        let seats: Vec<(u16,u16)> = optimized.iter()
+            .map(|p| p.get_seat().unwrap())
+            .collect();
+        assert_eq!(optimized[0].get_name(), "a");
+
+        let before = boarding_ticks(&aircraft, &names, &random);
+        let after = boarding_ticks(&aircraft, &names, &seats);
+        println!("Random: {}, optimised: {}", before, after);
+        assert!(after <= before);
+
+        // Starting from the same assignment, minimising time spent in the
+        // aisle never leaves more of it than there was to begin with.
+        let optimized = optimize_assignment_with_rng(&aircraft, &names, 30,
+                                                     Objective::AisleTicks,
+                                                     &mut seeded_rng(5))
+            .unwrap();
+        let seats: Vec<(u16,u16)> = optimized.iter()
+            .map(|p| p.get_seat().unwrap())
+            .collect();
+        let aisle = Objective::AisleTicks;
+        assert!(boarding_score(&aircraft, &names, &seats, aisle)
+                <= boarding_score(&aircraft, &names, &random, aisle));
+
+        let too_many = ["a"; 30];
+        assert!(optimize_assignment(&aircraft, &too_many, 1, Objective::Ticks)
+                .is_err());
+    }
+
+    #[test]
+    fn diff_back_first_and_steffen() {
+        let layout = Path::new("./config/standard_layout.csv");
+        let mut back_first = read_layout(layout).unwrap();
+        let list = random_back_first_with_rng(7, 12, &mut seeded_rng(1));
+        for i in list.unwrap() {
+            back_first.add_passenger(i);
+        }
+        back_first.run_to_completion().unwrap();
+
+        let mut steffen = read_layout(layout).unwrap();
+        let list = read_passengers(Path::new("./config/steffen.csv"));
+        for i in list.unwrap() {
+            steffen.add_passenger(i);
+        }
+        steffen.run_to_completion().unwrap();
+
+        let diff = back_first.get_stats().diff(steffen.get_stats());
+        println!("{:?}", diff);
+        assert!(diff.ticks < 0);
+        assert!(diff.efficiency > 0.0);
+        assert_eq!(diff.row_dwell.len(), 12);
+        assert_eq!(steffen.get_stats().diff(back_first.get_stats()).ticks,
+                   -diff.ticks);
+    }
+
+    #[test]
+    fn zone_completion_order() {
+        let mut aircraft = standard_layout(7, 12).unwrap();
+        aircraft.set_boarding_order(BoardingOrder::AsAdded);
+        let list = random_zones_with_rng(7, 12, 3, &mut seeded_rng(5)).unwrap();
+        assert_eq!(list.len(), 72);
+        for i in list {
+            aircraft.add_passenger(i);
+        }
+        aircraft.run_to_completion().unwrap();
+
+        let per_zone = &aircraft.get_stats().per_zone;
+        assert_eq!(per_zone.len(), 3);
+        for pair in per_zone.windows(2) {
+            assert!(pair[0].first_boarded < pair[1].first_boarded);
+            assert!(pair[0].complete_tick.unwrap()
+                    < pair[1].complete_tick.unwrap());
+        }
+        assert_eq!(per_zone[2].complete_tick,
+                   aircraft.get_stats().complete_tick);
+        assert_eq!(per_zone[2].overlap, 0);
+
+        assert!(random_zones(7, 12, 0).is_err());
+        assert!(random_zones(7, 12, 13).is_err());
+    }
+
+    #[test]
+    fn deterministic() {
+        assert!(verify_deterministic("random", 7, 10, 3));
+        assert!(verify_deterministic("back_first", 7, 10, 3));
+        assert!(!verify_deterministic("sideways", 7, 10, 3));
+
+        // Unseeded passengers board differently every time.
+        assert!(!runs_match(|| {
+            let mut aircraft = standard_layout(7, 10)?;
+            for i in random(7, 10)? {
+                aircraft.add_passenger(i);
+            }
+            Ok(aircraft)
+        }));
+    }
+
+    #[test]
+    fn realistic_flight_distributions() {
+        let (mut aircraft, list) =
+            realistic_flight(7, 40, 0.8, 0.6, 0.25, 0.5, 11).unwrap();
+        assert_eq!(list.len(), 192);
+
+        let fraction = |f: &dyn Fn(&Person) -> bool| {
+            list.iter().filter(|p| f(p)).count() as f64 / list.len() as f64
+        };
+        assert!((fraction(&|p| p.has_baggage()) - 0.6).abs() < 0.1);
+        assert!((fraction(&|p| p.get_stow_delay() > 0) - 0.25).abs() < 0.1);
+        assert!((fraction(&|p| p.get_group().is_some()) - 0.5).abs() < 0.1);
+
+        // Groups sit together and board one after the other.
+        for pair in list.windows(2) {
+            if pair[0].get_group().is_some()
+            && pair[0].get_group() == pair[1].get_group() {
+                let (a, b) = (pair[0].get_seat().unwrap(),
+                              pair[1].get_seat().unwrap());
+                assert_eq!((a.0 + 1, a.1), b);
+            }
+        }
+
+        for i in list {
+            aircraft.add_passenger(i);
+        }
+        assert!(aircraft.run_to_completion().is_ok());
+
+        assert!(realistic_flight(7, 40, 1.2, 0.6, 0.25, 0.5, 11).is_err());
+    }
+
+    #[test]
+    fn config_error_location() {
+        let path = std::env::temp_dir().join("aircraft_sim_bad_passengers.csv");
+        std::fs::write(&path, "NAME,X,Y,BAGGAGE\nx,0,0,1\nx,one,1,1\n")
+            .unwrap();
+        let e = read_passengers(&path).unwrap_err();
+        assert_eq!((e.line, e.column), (Some(3), Some(2)));
+        assert_eq!(e.to_string(),
+                   format!("{}:3:2: Invalid x coord", path.display()));
+
+        std::fs::write(&path, "NAME,X,Y,BAGGAGE\nx,0,0,1\nx,0,1,yes\n")
+            .unwrap();
+        let e = read_passengers(&path).unwrap_err();
+        assert_eq!(e.to_string(),
+                   format!("{}:3:4: Invalid baggage value", path.display()));
+
+        std::fs::write(&path, "X,Y,VARIANT\n0,0,seat\n1,0\n").unwrap();
+        let e = read_layout(&path).err().unwrap();
+        assert_eq!(e.line, Some(3));
+
+        std::fs::write(&path, "X,Y,VARIANT\n0,0,seat\n1,0,galley\n").unwrap();
+        let e = read_layout(&path).err().unwrap();
+        assert_eq!(e.to_string(),
+                   format!("{}:3:3: Invalid tile variant", path.display()));
+        std::fs::remove_file(&path).unwrap();
+
+        let e = read_layout(Path::new("./config/missing.csv")).err().unwrap();
+        assert_eq!(e.line, None);
+        assert!(e.to_string().starts_with("./config/missing.csv: "));
+    }
+
+    #[test]
+    fn layout_entrance_check() {
+        let path = std::env::temp_dir().join("aircraft_sim_no_entrance.csv");
+        std::fs::write(&path, "X,Y,VARIANT\n0,0,seat\n1,0,aisle\n1,1,aisle\n")
+            .unwrap();
+        let e = read_layout(&path).err().unwrap();
+        assert_eq!(e.message, "Layout has no entrance tile");
+
+        // An entrance walled off from the aisle by `None` tiles.
+        std::fs::write(&path, "X,Y,VARIANT\n0,0,seat\n1,0,aisle\n\
+                               0,1,none\n1,1,none\n1,2,entrance\n")
+            .unwrap();
+        let e = read_layout(&path).err().unwrap();
+        assert!(e.message.contains("no entrance connected"));
+
+        std::fs::write(&path, "X,Y,VARIANT\n0,0,seat\n1,0,aisle\n\
+                               1,1,entrance\n")
+            .unwrap();
+        assert!(read_layout(&path).is_ok());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn realized_front_first_order() {
+        let mut aircraft = standard_layout(7, 12).unwrap();
+        aircraft.set_tile(3, 11, Variant::Aisle);
+        aircraft.set_tile(3, 0, Variant::Entrance);
+        aircraft.set_boarding_order(BoardingOrder::AsAdded);
+        let list = random_front_first_with_rng(7, 12, &mut seeded_rng(8));
+        for i in list.unwrap() {
+            aircraft.add_passenger(i);
+        }
+        aircraft.run_to_completion().unwrap();
+
+        let order = aircraft.get_stats().realized_seat_order();
+        assert_eq!(order.len(), 72);
+        let mean_row = |seats: &[(u16, u16)]| {
+            seats.iter().map(|s| s.1 as f32).sum::<f32>() / seats.len() as f32
+        };
+        assert!(mean_row(&order[..24]) < mean_row(&order[24..48]));
+        assert!(mean_row(&order[24..48]) < mean_row(&order[48..]));
+    }
+
+    #[test]
+    fn layout_with_door() {
+        let boarded_at = |mut aircraft: Aircraft| {
+            aircraft.set_event_log(true);
+            for i in fill_layout(&aircraft, "random", 2).unwrap() {
+                aircraft.add_passenger(i);
+            }
+            aircraft.run_to_completion().unwrap();
+            assert!(aircraft.audit_seating().is_empty());
+            let mut doors: Vec<(u16, u16)> = aircraft.take_events().iter()
+                .filter_map(|e| match *e {
+                    SimEvent::Boarded { position, .. } => Some(position),
+                    _ => None,
+                })
+                .collect();
+            doors.dedup();
+            doors
+        };
+
+        let front = standard_layout_with_door(7, 8, (3, 0)).unwrap();
+        assert_eq!(front.get_tile_variant(3, 7), Variant::Aisle);
+        assert_eq!(boarded_at(front), vec![(3, 0)]);
+
+        // The seats between a side door and the aisle become a vestibule.
+        let side = standard_layout_with_door(7, 8, (0, 4)).unwrap();
+        assert_eq!(side.get_tile_variant(1, 4), Variant::Aisle);
+        assert_eq!(side.get_tile_variant(4, 4), Variant::Seat);
+        assert_eq!(boarded_at(side), vec![(0, 4)]);
+
+        assert!(standard_layout_with_door(7, 8, (1, 4)).is_err());
+        assert!(standard_layout_with_door(7, 8, (3, 8)).is_err());
+    }
+
+    #[test]
+    fn perturb_order() {
+        let queue = || (0..100)
+            .map(|i| Person::new(&i.to_string()))
+            .collect::<Vec<Person>>();
+        let inversions = |passengers: &[Person]| {
+            let order: Vec<u32> = passengers.iter()
+                .map(|p| p.get_name().parse().unwrap())
+                .collect();
+            (0..order.len())
+                .flat_map(|i| (i + 1..order.len()).map(move |j| (i, j)))
+                .filter(|&(i, j)| order[i] > order[j])
+                .count()
+        };
+
+        let mut unchanged = queue();
+        super::perturb_order(&mut unchanged, 0.0, 5);
+        assert_eq!(inversions(&unchanged), 0);
+
+        let mut few = queue();
+        super::perturb_order(&mut few, 0.1, 5);
+        let mut many = queue();
+        super::perturb_order(&mut many, 0.5, 5);
+        assert!(inversions(&few) > 0);
+        assert!(inversions(&many) > inversions(&few));
+    }
+
+    #[test]
+    fn connection_rush() {
+        let mut aircraft = standard_layout(7, 10).unwrap();
+        let rush = [("Rush 1", (0, 1)), ("Rush 2", (5, 4)), ("Rush 3", (2, 8))];
+        for i in fill_layout(&aircraft, "back_first", 3).unwrap() {
+            if rush.iter().all(|(_, seat)| Some(*seat) != i.get_seat()) {
+                aircraft.add_passenger(i);
+            }
+        }
+        let booked = aircraft.get_stats().passengers.len();
+
+        add_connection_rush(&mut aircraft, &rush, 20).unwrap();
+        assert_eq!(aircraft.get_stats().ticks, 20);
+        assert!(add_connection_rush(&mut aircraft, &rush, 10).is_err());
+        aircraft.run_to_completion().unwrap();
+        assert!(aircraft.audit_seating().is_empty());
+
+        for stats in &aircraft.get_stats().passengers[booked..] {
+            let boarded = stats.boarded_tick.unwrap();
+            assert!(boarded > 20);
+            assert!(stats.seated_tick.unwrap() - boarded < 20,
+                    "{} took too long", stats.name);
+        }
+    }
+
+    #[test]
+    fn twin_aisle_layout() {
+        let aircraft = super::twin_aisle_layout(10, 6).unwrap();
+        let row: Vec<Variant> = (0..10)
+            .map(|x| aircraft.get_tile_variant(x, 0))
+            .collect();
+        let seat = Variant::Seat;
+        let aisle = Variant::Aisle;
+        assert_eq!(row, vec![seat, seat, aisle, seat, seat, seat, seat,
+                             aisle, seat, seat]);
+        assert_eq!(aircraft.get_tile_variant(2, 5), Variant::Entrance);
+        assert_eq!(aircraft.get_tile_variant(4, 5), Variant::Aisle);
+        assert!(super::twin_aisle_layout(4, 6).is_err());
+        assert!(super::twin_aisle_layout(10, 1).is_err());
+
+        // A 3-3-3 cabin, with each passenger taking the aisle nearer its seat.
+        let mut aircraft = super::twin_aisle_layout(11, 8).unwrap();
+        aircraft.set_event_log(true);
+        for passenger in fill_layout(&aircraft, "random", 3).unwrap() {
+            aircraft.add_passenger(passenger);
+        }
+        aircraft.run_to_completion().unwrap();
+        assert!(aircraft.audit_seating().is_empty());
+
+        let seats: Vec<(u16, u16)> = aircraft.get_stats().passengers.iter()
+            .map(|p| p.seat.unwrap())
+            .collect();
+        for event in aircraft.take_events() {
+            if let SimEvent::Moved { id: Some(id), to, .. } = event {
+                let seat_x = seats[id].0;
+                if to.1 < 7 && to.0 == 3 {
+                    assert!(seat_x <= 5, "Passenger for {:?} in left aisle",
+                            seats[id]);
+                }
+                if to.1 < 7 && to.0 == 7 {
+                    assert!(seat_x >= 5, "Passenger for {:?} in right aisle",
+                            seats[id]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn both_ends() {
+        // A 3-4-3 cabin, with a cross-aisle at the door joining the aisles.
+        let mut aircraft = Aircraft::new(12, 8);
+        for x in 0..12 {
+            for y in 1..8 {
+                if x != 3 && x != 8 {
+                    aircraft.set_tile(x, y, Variant::Seat);
+                }
+            }
+        }
+        aircraft.set_tile(3, 0, Variant::Entrance);
+        aircraft.set_boarding_order(BoardingOrder::AsAdded);
+        aircraft.set_event_log(true);
+        let passengers = fill_layout(&aircraft, "both_ends", 4).unwrap();
+        let rows: Vec<u16> = passengers.iter()
+            .map(|p| p.get_seat().unwrap().1)
+            .collect();
+        assert!(rows.windows(2).all(|pair| pair[0] >= pair[1]));
+        let first_row: Vec<u16> = passengers[..10].iter()
+            .map(|p| aircraft.aisle_distance(p.get_seat().unwrap().0, 7))
+            .collect();
+        assert_eq!(first_row, vec![3, 3, 2, 2, 2, 2, 1, 1, 1, 1]);
+
+        for i in passengers {
+            aircraft.add_passenger(i);
+        }
+        aircraft.run_to_completion().unwrap();
+        assert!(aircraft.audit_seating().is_empty());
+
+        // Each block is entered from its own aisle, with the centre block
+        // split between them.
+        let seats: Vec<(u16, u16)> = aircraft.get_stats().passengers.iter()
+            .map(|p| p.seat.unwrap())
+            .collect();
+        for event in aircraft.take_events() {
+            if let SimEvent::Moved { id: Some(id), to, .. } = event {
+                let seat_x = seats[id].0;
+                if to.1 > 0 && to.0 == 8 {
+                    assert!(seat_x > 5, "Passenger for {:?} in right aisle",
+                            seats[id]);
+                }
+                if to.1 > 0 && to.0 == 3 {
+                    assert!(seat_x < 6, "Passenger for {:?} in left aisle",
+                            seats[id]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn sweep() {
+        let sizes = [(7, 6), (7, 10)];
+        let result = super::sweep(&["back_first", "random"], &sizes, 2, 11)
+            .unwrap();
+
+        assert_eq!(result.methods, vec!["back_first", "random"]);
+        assert_eq!(result.sizes, sizes.to_vec());
+        assert_eq!(result.mean_ticks.len(), 2);
+        for row in &result.mean_ticks {
+            assert_eq!(row.len(), 2);
+            assert!(row.iter().all(|&ticks| ticks > 0.0));
+            // A longer aircraft takes longer to board.
+            assert!(row[1] > row[0]);
+        }
+        let ticks = benchmark("random", 7, 10, 2, 11).unwrap();
+        assert_eq!(result.mean_ticks[1][1],
+                   (ticks[0] as f64 + ticks[1] as f64) / 2.0);
+
+        assert!(super::sweep(&["sideways"], &sizes, 2, 11).is_err());
+    }
+
+    #[test]
+    fn sweep_with_checkpoint() {
+        let path = std::env::temp_dir().join("aircraft_sim_sweep.csv");
+        let _ = std::fs::remove_file(&path);
+        let methods = ["back_first", "random"];
+        let sizes = [(7, 6), (7, 10)];
+        let full = super::sweep(&methods, &sizes, 2, 11).unwrap();
+        let checkpointed = super::sweep_with_checkpoint(&methods, &sizes, 2,
+                                                        11, &path).unwrap();
+        assert_eq!(checkpointed, full);
+
+        // Interrupted after the first two cells, one of them marked to show
+        // it is read back rather than benchmarked again.
+        let saved = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = saved.lines().collect();
+        assert_eq!(lines.len(), 5);
+        let first = format!("back_first,7,6,2,11,{}", full.mean_ticks[0][0]);
+        assert_eq!(lines[1], first);
+        std::fs::write(&path, format!("{}\nback_first,7,6,2,11,1\n{}\n",
+                                      lines[0], lines[2])).unwrap();
+        let resumed = super::sweep_with_checkpoint(&methods, &sizes, 2, 11,
+                                                   &path).unwrap();
+        assert_eq!(resumed.mean_ticks[0][0], 1.0);
+        assert_eq!(resumed.mean_ticks[0][1], full.mean_ticks[0][1]);
+        assert_eq!(resumed.mean_ticks[1], full.mean_ticks[1]);
+        assert_eq!(std::fs::read_to_string(&path).unwrap().lines().count(), 5);
+
+        // Cells for another seed aren't reused.
+        let reseeded = super::sweep_with_checkpoint(&methods, &sizes, 2, 12,
+                                                    &path).unwrap();
+        assert_eq!(reseeded, super::sweep(&methods, &sizes, 2, 12).unwrap());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn assign_stow_times() {
+        let mut passengers: Vec<Person> = (0..2000)
+            .map(|i| Person::new(&i.to_string()))
+            .collect();
+        super::assign_stow_times(&mut passengers, 4.0, 0.8, 9).unwrap();
+
+        let delays: Vec<u8> = passengers.iter()
+            .map(|p| p.get_stow_delay())
+            .collect();
+        let mean = delays.iter().map(|&d| d as f64).sum::<f64>()
+            / delays.len() as f64;
+        assert!((mean - 4.0).abs() < 0.3, "mean stow delay was {}", mean);
+        // Most stow quickly, but a few take far longer than average.
+        let median = {
+            let mut sorted = delays.clone();
+            sorted.sort_unstable();
+            sorted[sorted.len() / 2]
+        };
+        assert!((median as f64) < mean);
+        assert!(delays.iter().any(|&d| d >= 12));
+
+        assert!(super::assign_stow_times(&mut passengers, -1.0, 0.8, 9)
+            .is_err());
+    }
+
+    #[test]
+    fn personalities() {
+        let scenario = || {
+            let mut aircraft = standard_layout(7, 10).unwrap();
+            let mut passengers = fill_layout(&aircraft, "random", 4).unwrap();
+            assign_personalities(&mut passengers, 21);
+            super::assign_stow_times(&mut passengers, 2.0, 1.0, 8).unwrap();
+            aircraft.set_event_log(true);
+            for i in passengers {
+                aircraft.add_passenger(i);
+            }
+            aircraft.run_to_completion().unwrap();
+            aircraft.take_events()
+        };
+        assert_eq!(scenario(), scenario());
+
+        // A passenger's draws don't depend on who else is drawn for, or when.
+        let mut passengers = fill_layout(&standard_layout(7, 10).unwrap(),
+                                         "random", 4).unwrap();
+        assign_personalities(&mut passengers, 21);
+        super::assign_stow_times(&mut passengers, 2.0, 1.0, 8).unwrap();
+        let delays: Vec<u8> = passengers.iter()
+            .map(|p| p.get_stow_delay())
+            .collect();
+        let mut reordered = passengers.clone();
+        reordered.reverse();
+        super::assign_stow_times(&mut reordered[5..], 2.0, 1.0, 3).unwrap();
+        assert!(reordered[5..].iter().rev()
+            .zip(delays.iter())
+            .all(|(p, &delay)| p.get_stow_delay() == delay));
+    }
+
+    #[test]
+    fn marginal_delays() {
+        let aircraft = standard_layout(7, 6).unwrap();
+        let mut passengers = fill_layout(&aircraft, "back_first", 2).unwrap();
+        for (i, person) in passengers.iter_mut().enumerate() {
+            person.set_name(&i.to_string());
+        }
+        passengers[10].set_name("Slow");
+        passengers[10].set_stow_delay(20);
+
+        let delays = super::marginal_delays(&aircraft, &passengers).unwrap();
+        assert_eq!(delays.len(), passengers.len());
+        assert_eq!(delays[0].0, "Slow");
+        assert!(delays[0].1 > delays[1].1);
+    }
+
+    #[test]
+    fn robustness() {
+        let report = super::robustness("back_first", 7, 10, 8, 5).unwrap();
+        assert_eq!(report.runs, 8);
+        assert!(report.worst_case as f64 >= report.mean);
+        assert!(report.variance > 0.0);
+
+        assert!(super::robustness("back_first", 7, 10, 0, 5).is_err());
+    }
+
+    #[test]
+    fn write_trajectory() {
+        let mut aircraft = standard_layout(5, 4).unwrap();
+        let mut passenger = Person::new("Window");
+        passenger.target_seat(0, 1);
+        aircraft.add_passenger(passenger);
+        aircraft.set_event_log(true);
+        aircraft.run_to_completion().unwrap();
+        let events = aircraft.take_events();
+        let table = aircraft.trajectory_table(&events, &[]);
+
+        let path = std::env::temp_dir().join("aircraft_sim_trajectory.csv");
+        super::write_trajectory(&table, &path).unwrap();
+        let written = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = written.lines().collect();
+        assert_eq!(lines[0], "TICK,ID,PASSENGER,X,Y,VARIANT,BEHAVIOUR");
+        assert_eq!(lines.len(), table.len() + 1);
+        let last = table.last().unwrap();
+        assert_eq!(*lines.last().unwrap(),
+                   format!("{},0,Window,0,1,seat,", last.tick));
+
+        let row = TrajectoryRow {
+            tick: 3,
+            id: 1,
+            passenger: "Smith, J".to_string(),
+            x: 2,
+            y: 1,
+            variant: Variant::Aisle,
+            behaviour: Some(Behaviour::Move_North),
+        };
+        super::write_trajectory(&[row], &path).unwrap();
+        let written = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(written.lines().nth(1).unwrap(),
+                   "3,1,\"Smith, J\",2,1,aisle,north");
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn seeded_patterns() {
+        let seats = |list: Vec<Person>| -> Vec<(u16,u16)> {
+            list.iter().map(|p| p.get_seat().unwrap()).collect()
+        };
+        // The exact order depends on the RNG, so only holds for the default.
+        #[cfg(not(feature = "small_rng"))]
+        assert_eq!(seats(random_back_first_seeded(5, 2, 42).unwrap()),
+                   vec![(0,1), (4,1), (3,1), (1,1),
+                        (3,0), (4,0), (1,0), (0,0)]);
+
+        type Pattern = fn(u16, u16, u64) -> Result<Vec<Person>, &'static str>;
+        let patterns: [Pattern; 5] = [random_back_first_seeded,
+                                      random_front_first_seeded,
+                                      random_window_first_seeded,
+                                      random_aisle_first_seeded,
+                                      random_seeded];
+        for pattern in &patterns {
+            assert_eq!(seats(pattern(7, 10, 3).unwrap()),
+                       seats(pattern(7, 10, 3).unwrap()));
+        }
+        assert_eq!(seats(random_zones_seeded(7, 10, 3, 3).unwrap()),
+                   seats(random_zones_with_rng(7, 10, 3,
+                                               &mut seeded_rng(3)).unwrap()));
+    }
+
+    #[test]
+    fn no_shuffle_back_first() {
+        let list = super::no_shuffle_back_first(7, 10).unwrap();
+        assert_eq!(list.len(), 60);
+        assert_eq!(list.last().unwrap().get_seat(), Some((6, 0)));
+        assert_eq!(list[0].get_seat(), Some((2, 9)));
+
+        let mut aircraft = standard_layout(7, 10).unwrap();
+        for passenger in list {
+            aircraft.add_passenger(passenger);
+        }
+        // The door is at the front, so the back row boards first.
+        let first = aircraft.next_to_board().unwrap().get_seat().unwrap();
+        assert_eq!(first.1, 0);
+        aircraft.run_to_completion().unwrap();
+        assert_eq!(aircraft.get_stats().interferences, 0);
+        assert!(aircraft.audit_seating().is_empty());
     }
 }
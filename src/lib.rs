@@ -17,7 +17,7 @@ use std::thread;
 use simple_logger::SimpleLogger;
 use log::LevelFilter;
 
-use aircraft::Aircraft;
+use aircraft::{Aircraft, RunError};
 use aircraft::tile::Variant;
 use config::*;
 
@@ -60,7 +60,10 @@ impl PyAircraft {
     /// not be output. This can be modified to change the displayed log level.
     #[staticmethod]
     fn initialise_logger() -> PyResult<()> {
-        SimpleLogger::new().with_level().init().expect("Failed to initialise logger");
+        SimpleLogger::new()
+            .with_level(LevelFilter::Info)
+            .init()
+            .expect("Failed to initialise logger");
         log::info!("Initialised logger");
 
         Ok(())
@@ -90,16 +93,19 @@ impl PyAircraft {
             let new_aircraft = read_layout(Path::new(layout_path));
             let passengers = read_passengers(Path::new(passengers_path));
             
-            if new_aircraft.is_some() && passengers.is_some() {
-                let mut new_aircraft = new_aircraft.unwrap();
-                for i in passengers.unwrap() {
-                    new_aircraft.add_passenger(i);
-                }
-                self.aircraft = Some(new_aircraft);
-                self.size = self.aircraft.as_ref().unwrap().get_size();
-                Ok(())
-            } else {
-                Err(PyTypeError::new_err("Error2"))
+            match (new_aircraft, passengers) {
+                (Ok(mut new_aircraft), Ok(passengers)) => {
+                    for i in passengers {
+                        new_aircraft.try_add_passenger(i)
+                            .map_err(PyTypeError::new_err)?;
+                    }
+                    self.aircraft = Some(new_aircraft);
+                    self.size = self.aircraft.as_ref().unwrap().get_size();
+                    Ok(())
+                },
+                (Err(e), _) | (_, Err(e)) => {
+                    Err(PyTypeError::new_err(e.to_string()))
+                },
             }
         } else {
             Err(PyTypeError::new_err("Error2"))
@@ -398,7 +404,8 @@ impl PyAircraft {
 /// Takes two vectors of filepaths, one for layout files and one for passenger
 /// files, and simulates each pair. Every aircraft is simulated in parallel with
 /// one another and the resulting times taken are returned as a vector in the
-/// same order they were passed in.
+/// same order they were passed in. Returns an error if any passenger's seat
+/// can't be sat in, rather than simulating a boarding that never completes.
 #[pyfunction]
 fn mass_sim(layouts:Vec<&str>,passenger_lists:Vec<&str>) -> PyResult<Vec<u16>> {
     // If a different number of files are passed in for each argument, the input
@@ -411,7 +418,7 @@ fn mass_sim(layouts:Vec<&str>,passenger_lists:Vec<&str>) -> PyResult<Vec<u16>> {
 
     // This vector holds the handles for each thread, allowing them to be joined
     // after completion.
-    let mut jobs:Vec<thread::JoinHandle<Result<u16,&'static str>>> = Vec::new();
+    let mut jobs:Vec<thread::JoinHandle<Result<u16,RunError>>> = Vec::new();
 
     for i in 0..layouts.len() {
         let aircraft = read_layout(Path::new(layouts.get(i).unwrap()));
@@ -419,15 +426,17 @@ fn mass_sim(layouts:Vec<&str>,passenger_lists:Vec<&str>) -> PyResult<Vec<u16>> {
                                                    .get(i)
                                                    .unwrap()));
         
-        if aircraft.is_some() && passengers.is_some() {
+        if aircraft.is_ok() && passengers.is_ok() {
             let mut aircraft = aircraft.unwrap();
             for i in passengers.unwrap() {
-                aircraft.add_passenger(i);
+                aircraft.try_add_passenger(i)
+                    .map_err(PyTypeError::new_err)?;
             }
 
             // A new thread for each aircraft simulation is created.
             jobs.push(thread::spawn(move || {
                 aircraft.run_to_completion()
+                    .map(|stats| stats.complete_tick.unwrap_or(stats.ticks))
             }));
         }
     }
@@ -472,7 +481,7 @@ fn core_mass_sim(layouts:Vec<&str>,passenger_lists:Vec<&str>) -> Result<Vec<u16>
 
     // This vector holds the handles for each thread, allowing them to be joined
     // after completion.
-    let mut jobs:Vec<thread::JoinHandle<Result<u16,&'static str>>> = Vec::new();
+    let mut jobs:Vec<thread::JoinHandle<Result<u16,RunError>>> = Vec::new();
 
     for i in 0..layouts.len() {
         let aircraft = read_layout(Path::new(layouts.get(i).unwrap()));
@@ -480,15 +489,16 @@ fn core_mass_sim(layouts:Vec<&str>,passenger_lists:Vec<&str>) -> Result<Vec<u16>
                                                    .get(i)
                                                    .unwrap()));
         
-        if aircraft.is_some() && passengers.is_some() {
+        if aircraft.is_ok() && passengers.is_ok() {
             let mut aircraft = aircraft.unwrap();
             for i in passengers.unwrap() {
-                aircraft.add_passenger(i);
+                aircraft.try_add_passenger(i)?;
             }
 
             // A new thread for each aircraft simulation is created.
             jobs.push(thread::spawn(move || {
                 aircraft.run_to_completion()
+                    .map(|stats| stats.complete_tick.unwrap_or(stats.ticks))
             }));
         }
     }
@@ -532,7 +542,7 @@ mod tests {
 
         for i in passengers {
             println!("Adding passenger: {}", i.get_name());
-            aircraft.add_passenger(i);
+            aircraft.try_add_passenger(i).expect("Invalid passenger");
         }
 
         for _ in 0..30 {